@@ -0,0 +1,296 @@
+//! Alert rule evaluation.
+
+use crate::config::{AlertCondition, AlertRule, Config};
+use crate::model::{ChangeKind, TopologyDiff, UsbDevice, UsbTopology, key_matches};
+
+/// A fired alert.
+#[derive(Debug, Clone)]
+pub struct Alert {
+    /// Name of the rule that fired.
+    pub rule: String,
+    /// Event type ("bus_usage", "unconfigured", "over_current", "disappeared").
+    pub event: &'static str,
+    /// Human-readable description.
+    pub message: String,
+    /// Event details, exported to hooks as `USBBW_*` environment variables.
+    pub env: Vec<(String, String)>,
+    /// Hook command to run, if any.
+    pub hook: Option<String>,
+    /// Whether to send a terminal notification.
+    pub notify: bool,
+}
+
+impl Alert {
+    fn new(rule: &AlertRule, event: &'static str, message: String) -> Self {
+        Self {
+            rule: rule.display_name(),
+            event,
+            message,
+            env: Vec::new(),
+            hook: rule.hook.clone(),
+            notify: rule.notify,
+        }
+    }
+
+    fn with(mut self, name: &str, value: impl ToString) -> Self {
+        self.env.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    fn with_device(self, device: &UsbDevice, config: &Config) -> Self {
+        let name = config.device_name(device);
+        self.with("USBBW_DEVICE_KEY", device.config_key())
+            .with("USBBW_DEVICE_NAME", name)
+            .with("USBBW_DEVICE_PATH", &device.path.0)
+    }
+
+    /// All environment variables for a hook, including rule, event and message.
+    pub fn hook_env(&self) -> Vec<(String, String)> {
+        let mut env = vec![
+            ("USBBW_RULE".to_string(), self.rule.clone()),
+            ("USBBW_EVENT".to_string(), self.event.to_string()),
+            ("USBBW_MESSAGE".to_string(), self.message.clone()),
+        ];
+        env.extend(self.env.iter().cloned());
+        env
+    }
+}
+
+/// Evaluate the configured alert rules against a topology change.
+///
+/// Rules are edge-triggered: a bus only alerts when its usage crosses the
+/// threshold, not on every refresh while it stays above it.
+pub fn evaluate_alerts(
+    config: &Config,
+    old: &UsbTopology,
+    new: &UsbTopology,
+    diff: &TopologyDiff,
+) -> Vec<Alert> {
+    let mut alerts = Vec::new();
+
+    for rule in &config.alerts {
+        match &rule.condition {
+            AlertCondition::BusUsage { above_percent, bus } => {
+                for b in new.buses_sorted() {
+                    if bus
+                        .as_ref()
+                        .is_some_and(|r| !config.bus_matches(b.bus_num, r))
+                    {
+                        continue;
+                    }
+                    let usage = b.periodic_usage_percent();
+                    let was_above = old
+                        .buses
+                        .get(&b.bus_num)
+                        .is_some_and(|ob| ob.periodic_usage_percent() > *above_percent);
+                    if usage > *above_percent && !was_above {
+                        let bus_label = config
+                            .bus_label(b.bus_num)
+                            .unwrap_or_else(|| format!("Bus {}", b.bus_num));
+                        alerts.push(
+                            Alert::new(
+                                rule,
+                                "bus_usage",
+                                format!(
+                                    "{} periodic bandwidth at {:.1}% (above {}%)",
+                                    bus_label, usage, above_percent
+                                ),
+                            )
+                            .with("USBBW_BUS", b.bus_num)
+                            .with("USBBW_BUS_LABEL", bus_label)
+                            .with("USBBW_USAGE_PERCENT", format!("{:.1}", usage))
+                            .with("USBBW_THRESHOLD_PERCENT", above_percent),
+                        );
+                    }
+                }
+            }
+            AlertCondition::Unconfigured { device } => {
+                for change in &diff.changes {
+                    // Also reported for devices that arrive unconfigured
+                    if change.kind != ChangeKind::Unconfigured
+                        || device
+                            .as_ref()
+                            .is_some_and(|k| !key_matches(&change.key, k))
+                    {
+                        continue;
+                    }
+                    if let Some(dev) = change.new_path.as_ref().and_then(|p| new.get_device(p)) {
+                        alerts.push(
+                            Alert::new(
+                                rule,
+                                "unconfigured",
+                                format!(
+                                    "{} [{}] failed to configure",
+                                    config.device_name(dev),
+                                    dev.path
+                                ),
+                            )
+                            .with_device(dev, config),
+                        );
+                    }
+                }
+            }
+            AlertCondition::OverCurrent => {
+                for oc in &diff.over_current {
+                    alerts.push(
+                        Alert::new(
+                            rule,
+                            "over_current",
+                            format!(
+                                "Over-current on bus {} port {}: count {} -> {}",
+                                oc.bus_num, oc.port_num, oc.from_count, oc.to_count
+                            ),
+                        )
+                        .with("USBBW_CONTROLLER", &oc.controller)
+                        .with("USBBW_BUS", oc.bus_num)
+                        .with("USBBW_PORT", oc.port_num)
+                        .with("USBBW_OVER_CURRENT_COUNT", oc.to_count),
+                    );
+                }
+            }
+            AlertCondition::Disappeared { device } => {
+                for change in &diff.changes {
+                    if change.kind != ChangeKind::Removed || !key_matches(&change.key, device) {
+                        continue;
+                    }
+                    if let Some(dev) = change.old_path.as_ref().and_then(|p| old.get_device(p)) {
+                        alerts.push(
+                            Alert::new(
+                                rule,
+                                "disappeared",
+                                format!("{} [{}] disappeared", config.device_name(dev), dev.path),
+                            )
+                            .with_device(dev, config),
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    alerts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::BusRef;
+    use crate::model::{Direction, Endpoint, TransferType, diff_topologies};
+
+    fn config(toml: &str) -> Config {
+        toml::from_str(toml).unwrap()
+    }
+
+    fn make_device(path: &str, serial: &str, is_configured: bool) -> UsbDevice {
+        UsbDevice {
+            product: Some("Probe".to_string()),
+            serial: Some(serial.to_string()),
+            is_configured,
+            ..UsbDevice::fixture(path, 0x0d28, 0x0204)
+        }
+    }
+
+    #[test]
+    fn test_parse_rules() {
+        let config = config(
+            r#"
+            [[alerts]]
+            name = "Full"
+            when = "bus_usage"
+            above_percent = 90
+            notify = true
+
+            [[alerts]]
+            when = "disappeared"
+            device = "0d28:0204"
+            hook = "true"
+            "#,
+        );
+        assert_eq!(config.alerts.len(), 2);
+        assert!(matches!(
+            config.alerts[0].condition,
+            AlertCondition::BusUsage { above_percent, bus: None } if above_percent == 90.0
+        ));
+        assert!(config.alerts[0].notify);
+        assert_eq!(config.alerts[1].display_name(), "disappeared");
+    }
+
+    #[test]
+    fn test_disappeared_and_unconfigured() {
+        let config = config(
+            r#"
+            [[alerts]]
+            when = "disappeared"
+            device = "0D28:0204"
+
+            [[alerts]]
+            when = "unconfigured"
+            "#,
+        );
+        let old = UsbTopology::fixture(1, vec![make_device("1-1", "ABC", true)]);
+        let new = UsbTopology::fixture(1, vec![make_device("1-2", "XYZ", false)]);
+        let diff = diff_topologies(&old, &new);
+        let alerts = evaluate_alerts(&config, &old, &new, &diff);
+        let events: Vec<_> = alerts.iter().map(|a| a.event).collect();
+        assert!(events.contains(&"disappeared"));
+        assert!(events.contains(&"unconfigured"));
+
+        let disappeared = alerts.iter().find(|a| a.event == "disappeared").unwrap();
+        assert!(
+            disappeared
+                .hook_env()
+                .contains(&("USBBW_DEVICE_PATH".to_string(), "1-1".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_bus_usage_by_stable_key() {
+        let mut config = config(
+            r#"
+            [[alerts]]
+            when = "bus_usage"
+            above_percent = 0
+            bus = "0000:c1:00.4/usb2"
+
+            [[alerts]]
+            when = "bus_usage"
+            above_percent = 0
+            bus = 2
+            "#,
+        );
+        assert!(matches!(
+            &config.alerts[1].condition,
+            AlertCondition::BusUsage {
+                bus: Some(BusRef::Number(2)),
+                ..
+            }
+        ));
+
+        let old = UsbTopology::fixture(1, vec![]);
+        let camera = UsbDevice {
+            endpoints: vec![Endpoint {
+                address: 0x81,
+                transfer_type: TransferType::Isochronous,
+                direction: Direction::In,
+                max_packet_size: 1024,
+                b_interval: 1,
+                interval_str: "125us".to_string(),
+                interface: 1,
+            }],
+            ..make_device("1-1", "ABC", true)
+        };
+        let new = UsbTopology::fixture(1, vec![camera]);
+        config.resolve_topology(&new);
+        let diff = diff_topologies(&old, &new);
+        let alerts: Vec<_> = evaluate_alerts(&config, &old, &new, &diff)
+            .into_iter()
+            .filter(|a| a.event == "bus_usage")
+            .collect();
+        assert_eq!(alerts.len(), 1);
+        assert!(
+            alerts[0]
+                .hook_env()
+                .contains(&("USBBW_BUS".to_string(), "1".to_string()))
+        );
+    }
+}
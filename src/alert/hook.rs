@@ -0,0 +1,51 @@
+//! Alert actions: shell hooks and terminal notifications.
+
+use super::engine::Alert;
+use std::process::{Command, Stdio};
+
+/// Run the alert's hook command (if any) via `sh -c` without blocking.
+///
+/// The child is reaped on a background thread so long-running hooks do not
+/// stall the refresh loop. With `quiet` (under the TUI) the hook's stderr is
+/// discarded so it cannot draw over the screen; otherwise it is passed
+/// through and a failing exit status is reported.
+pub fn run_hook(alert: &Alert, quiet: bool) -> std::io::Result<()> {
+    let Some(hook) = &alert.hook else {
+        return Ok(());
+    };
+
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(hook)
+        .envs(alert.hook_env())
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(if quiet {
+            Stdio::null()
+        } else {
+            Stdio::inherit()
+        })
+        .spawn()?;
+    let rule = alert.rule.clone();
+    std::thread::spawn(move || {
+        let status = child.wait();
+        if quiet {
+            return;
+        }
+        match status {
+            Ok(status) if !status.success() => {
+                eprintln!("Warning: Alert hook [{}] {}", rule, status);
+            }
+            Err(e) => eprintln!("Warning: Alert hook [{}] failed: {}", rule, e),
+            Ok(_) => {}
+        }
+    });
+    Ok(())
+}
+
+/// Terminal bell followed by an OSC 9 desktop notification.
+pub fn notification_sequence(alert: &Alert) -> String {
+    // Control characters would terminate the escape sequence early
+    let message: String = alert.message.chars().filter(|c| !c.is_control()).collect();
+    format!("\x07\x1b]9;usbbw: {}\x07", message)
+}
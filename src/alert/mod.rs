@@ -0,0 +1,7 @@
+//! Alert rules evaluated on topology changes, with hook and terminal notifications.
+
+mod engine;
+mod hook;
+
+pub use engine::{Alert, evaluate_alerts};
+pub use hook::{notification_sequence, run_hook};
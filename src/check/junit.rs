@@ -0,0 +1,70 @@
+//! JUnit XML report for check results.
+
+use super::rules::CheckResult;
+use crate::output::escape_xml;
+
+/// Generate a JUnit XML report (one test case per rule).
+pub fn generate_junit(results: &[CheckResult]) -> String {
+    let failures = results.iter().filter(|r| !r.passed).count();
+    let mut output = String::new();
+
+    output.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    output.push_str(&format!(
+        "<testsuites name=\"usbbw\" tests=\"{}\" failures=\"{}\">\n",
+        results.len(),
+        failures
+    ));
+    output.push_str(&format!(
+        "  <testsuite name=\"usbbw check\" tests=\"{}\" failures=\"{}\">\n",
+        results.len(),
+        failures
+    ));
+
+    for result in results {
+        output.push_str(&format!(
+            "    <testcase classname=\"usbbw.expect\" name=\"{}\">\n",
+            escape_xml(&result.name)
+        ));
+        if result.passed {
+            output.push_str(&format!(
+                "      <system-out>{}</system-out>\n",
+                escape_xml(&result.message)
+            ));
+        } else {
+            output.push_str(&format!(
+                "      <failure message=\"{}\"/>\n",
+                escape_xml(&result.message)
+            ));
+        }
+        output.push_str("    </testcase>\n");
+    }
+
+    output.push_str("  </testsuite>\n");
+    output.push_str("</testsuites>\n");
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_junit_failure() {
+        let results = vec![
+            CheckResult {
+                name: "bus 3 usage < 60%".to_string(),
+                passed: true,
+                message: "12.0% used".to_string(),
+            },
+            CheckResult {
+                name: "device 0d28:0204".to_string(),
+                passed: false,
+                message: "not present <missing>".to_string(),
+            },
+        ];
+        let xml = generate_junit(&results);
+        assert!(xml.contains("tests=\"2\" failures=\"1\""));
+        assert!(xml.contains("<failure message=\"not present &lt;missing&gt;\"/>"));
+        assert!(xml.contains("name=\"bus 3 usage &lt; 60%\""));
+    }
+}
@@ -0,0 +1,7 @@
+//! Declarative topology checks (`usbbw check`).
+
+mod junit;
+mod rules;
+
+pub use junit::generate_junit;
+pub use rules::{CheckResult, run_checks};
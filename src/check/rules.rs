@@ -0,0 +1,283 @@
+//! Evaluation of `[expect]` rules against a topology.
+
+use crate::config::{Config, ExpectBus, ExpectDevice};
+use crate::model::{UsbDevice, UsbSpeed, UsbTopology, key_matches};
+
+/// Outcome of a single expectation.
+#[derive(Debug, Clone)]
+pub struct CheckResult {
+    /// Rule name.
+    pub name: String,
+    /// Did the rule pass?
+    pub passed: bool,
+    /// Explanation (what was found).
+    pub message: String,
+}
+
+impl CheckResult {
+    fn pass(name: String, message: impl Into<String>) -> Self {
+        Self {
+            name,
+            passed: true,
+            message: message.into(),
+        }
+    }
+
+    fn fail(name: String, message: impl Into<String>) -> Self {
+        Self {
+            name,
+            passed: false,
+            message: message.into(),
+        }
+    }
+}
+
+/// Evaluate all `[expect]` rules from the config.
+pub fn run_checks(topology: &UsbTopology, config: &Config) -> Vec<CheckResult> {
+    let mut results = Vec::new();
+
+    for expect in &config.expect.devices {
+        results.push(check_device(topology, config, expect));
+    }
+
+    for expect in &config.expect.buses {
+        results.push(check_bus(topology, config, expect));
+    }
+
+    if config.expect.no_unconfigured {
+        let name = "no unconfigured devices".to_string();
+        let unconfigured: Vec<String> = topology
+            .buses_sorted()
+            .into_iter()
+            .flat_map(|b| b.devices_tree_order())
+            .filter(|d| !d.is_configured)
+            .map(|d| format!("{} [{}]", config.device_name(d), d.path))
+            .collect();
+        results.push(if unconfigured.is_empty() {
+            CheckResult::pass(name, "all devices configured")
+        } else {
+            CheckResult::fail(name, format!("unconfigured: {}", unconfigured.join(", ")))
+        });
+    }
+
+    results
+}
+
+fn check_device(topology: &UsbTopology, config: &Config, expect: &ExpectDevice) -> CheckResult {
+    let name = expect.name.clone().unwrap_or_else(|| {
+        let mut name = format!("device {}", expect.device);
+        if let Some(port) = &expect.port {
+            name.push_str(&format!(" at port \"{}\"", port));
+        }
+        if let Some(path) = &expect.path {
+            name.push_str(&format!(" behind {}", path));
+        }
+        if let Some(controller) = &expect.controller {
+            name.push_str(&format!(" on {}", controller));
+        }
+        if let Some(speed) = &expect.speed {
+            name.push_str(&format!(" at {}", speed));
+        }
+        name
+    });
+
+    let speed = match expect.speed.as_deref().map(|s| (s, UsbSpeed::from_name(s))) {
+        Some((s, None)) => return CheckResult::fail(name, format!("unknown speed \"{}\"", s)),
+        Some((_, speed)) => speed,
+        None => None,
+    };
+
+    let matching: Vec<&UsbDevice> = topology
+        .buses_sorted()
+        .into_iter()
+        .flat_map(|b| b.devices_tree_order())
+        .filter(|d| key_matches(&d.config_key(), &expect.device))
+        .collect();
+
+    if matching.is_empty() {
+        return CheckResult::fail(name, "not present");
+    }
+
+    let mut problems = Vec::new();
+    for device in &matching {
+        let mut issues = Vec::new();
+
+        if let Some(port) = &expect.port {
+            match physical_port(topology, config, device) {
+                Some(actual) if actual == *port => {}
+                Some(actual) => issues.push(format!("at port \"{}\"", actual)),
+                None => issues.push("not behind a labeled port".to_string()),
+            }
+        }
+
+        if let Some(path) = &expect.path
+            && device.path.0 != *path
+            && !device.path.0.starts_with(&format!("{}.", path))
+        {
+            issues.push(format!("not behind {}", path));
+        }
+
+        if let Some(controller) = &expect.controller {
+            let actual = device
+                .path
+                .bus_num()
+                .and_then(|n| topology.get_controller_for_bus(n));
+            let on_controller = actual.is_some_and(|c| {
+                c.pci_address == *controller
+                    || config.controller_label(&c.pci_address).as_ref() == Some(controller)
+            });
+            if !on_controller {
+                issues.push(format!(
+                    "on controller {}",
+                    actual.map(|c| c.pci_address.as_str()).unwrap_or("unknown")
+                ));
+            }
+        }
+
+        if let Some(speed) = speed
+            && device.speed != speed
+        {
+            issues.push(format!("at {}", device.speed.short_name()));
+        }
+
+        if !issues.is_empty() {
+            problems.push(format!(
+                "{} [{}] {}",
+                config.device_name(device),
+                device.path,
+                issues.join(", ")
+            ));
+        }
+    }
+
+    if problems.is_empty() {
+        let paths: Vec<&str> = matching.iter().map(|d| d.path.0.as_str()).collect();
+        CheckResult::pass(name, format!("found at {}", paths.join(", ")))
+    } else {
+        CheckResult::fail(name, problems.join("; "))
+    }
+}
+
+fn check_bus(topology: &UsbTopology, config: &Config, expect: &ExpectBus) -> CheckResult {
+    let bus = topology
+        .buses_sorted()
+        .into_iter()
+        .find(|b| config.bus_matches(b.bus_num, &expect.bus));
+    let label = bus
+        .and_then(|b| config.bus_label(b.bus_num))
+        .unwrap_or_else(|| format!("Bus {}", expect.bus));
+    let name = format!("{} usage < {}%", label, expect.max_usage_percent);
+
+    let Some(bus) = bus else {
+        return CheckResult::fail(name, "bus not present");
+    };
+
+    let usage = bus.periodic_usage_percent();
+    let message = format!("{:.1}% used", usage);
+    if usage < expect.max_usage_percent {
+        CheckResult::pass(name, message)
+    } else {
+        CheckResult::fail(name, message)
+    }
+}
+
+/// Label of the physical port a device is plugged into, following hubs up
+/// to the nearest device with an ACPI location.
+fn physical_port(topology: &UsbTopology, config: &Config, device: &UsbDevice) -> Option<String> {
+    let mut current = Some(device);
+    while let Some(dev) = current {
+        if let Some(label) = dev
+            .physical_location
+            .as_ref()
+            .and_then(|loc| config.physical_port_label(loc))
+        {
+            return Some(label);
+        }
+        current = dev.path.parent().and_then(|p| topology.get_device(&p));
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_device(path: &str, serial: &str, speed: UsbSpeed, is_configured: bool) -> UsbDevice {
+        UsbDevice {
+            speed,
+            product: Some("Probe".to_string()),
+            serial: Some(serial.to_string()),
+            is_configured,
+            ..UsbDevice::fixture(path, 0x0d28, 0x0204)
+        }
+    }
+
+    #[test]
+    fn test_bus_by_stable_key() {
+        let mut config: Config = toml::from_str(
+            r#"
+            [buses]
+            "0000:c1:00.4/usb2" = "Rear"
+
+            [[expect.buses]]
+            bus = "0000:c1:00.4/usb2"
+            max_usage_percent = 60
+
+            [[expect.buses]]
+            bus = "0000:c3:00.0/usb2"
+            max_usage_percent = 60
+            "#,
+        )
+        .unwrap();
+        let topology = UsbTopology::fixture(3, vec![]);
+        config.resolve_topology(&topology);
+
+        let results = run_checks(&topology, &config);
+        assert_eq!(results[0].name, "Rear usage < 60%");
+        assert!(results[0].passed);
+        assert_eq!(results[1].name, "Bus 0000:c3:00.0/usb2 usage < 60%");
+        assert_eq!(results[1].message, "bus not present");
+    }
+
+    #[test]
+    fn test_device_expectations() {
+        let config: Config = toml::from_str(
+            r#"
+            [expect]
+            no_unconfigured = true
+
+            [[expect.devices]]
+            device = "0d28:0204:ABC"
+            path = "3-1"
+            speed = "high"
+
+            [[expect.devices]]
+            device = "0d28:0204"
+            speed = "480M"
+
+            [[expect.devices]]
+            device = "1234:5678"
+
+            [[expect.buses]]
+            bus = 3
+            max_usage_percent = 60
+            "#,
+        )
+        .unwrap();
+        let topology = UsbTopology::fixture(
+            3,
+            vec![
+                make_device("3-1", "ABC", UsbSpeed::High, true),
+                make_device("3-2", "DEF", UsbSpeed::Full, false),
+            ],
+        );
+
+        let results = run_checks(&topology, &config);
+        let passed: Vec<bool> = results.iter().map(|r| r.passed).collect();
+        // ABC behind 3-1 at high speed; DEF fails "all probes at High Speed";
+        // 1234:5678 missing; bus usage fine; DEF unconfigured
+        assert_eq!(passed, vec![true, false, false, true, false]);
+        assert!(results[1].message.contains("at 12M"));
+        assert_eq!(results[2].message, "not present");
+    }
+}
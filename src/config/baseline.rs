@@ -0,0 +1,412 @@
+//! Hardware baselines selected by DMI board identity.
+//!
+//! A baseline is a config file with controller, bus and port labels for one
+//! machine model. It declares the DMI values it applies to:
+//!
+//! ```toml
+//! [baseline]
+//! name = "Framework Laptop 13 (AMD Ryzen AI 300 Series)"
+//!
+//! [baseline.match]
+//! sys_vendor = "Framework"
+//! board_name = "FRANMGCP*"
+//! ```
+//!
+//! The matching baseline is applied underneath the user's config, so nobody
+//! has to `inherit` it by path.
+
+use super::loader::{ConfigError, ConfigLayer};
+use serde::Deserialize;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Directory the kernel exposes DMI identification strings in.
+pub const DMI_ID_DIR: &str = "/sys/class/dmi/id";
+
+/// System-wide baselines directory.
+pub const SYSTEM_BASELINES_DIR: &str = "/etc/usbbw/baselines";
+
+/// Baselines compiled into the binary, by name.
+const BUNDLED: &[(&str, &str)] = &[(
+    "framework-franmgcp",
+    include_str!("../../configs/framework-franmgcp.toml"),
+)];
+
+/// DMI identity of the running machine.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Dmi {
+    pub sys_vendor: Option<String>,
+    pub product_name: Option<String>,
+    pub board_name: Option<String>,
+}
+
+impl Dmi {
+    /// Read the DMI identity from sysfs (empty fields if unavailable).
+    pub fn read() -> Self {
+        Self::read_from(Path::new(DMI_ID_DIR))
+    }
+
+    /// Read the DMI identity from a `dmi/id`-style directory.
+    pub fn read_from(dir: &Path) -> Self {
+        let read = |name: &str| {
+            std::fs::read_to_string(dir.join(name))
+                .ok()
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+        };
+        Self {
+            sys_vendor: read("sys_vendor"),
+            product_name: read("product_name"),
+            board_name: read("board_name"),
+        }
+    }
+
+    /// Whether any field could be read.
+    pub fn is_empty(&self) -> bool {
+        self.sys_vendor.is_none() && self.product_name.is_none() && self.board_name.is_none()
+    }
+}
+
+impl fmt::Display for Dmi {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let field = |v: &Option<String>| v.clone().unwrap_or_else(|| "?".to_string());
+        write!(
+            f,
+            "{} / {} / {}",
+            field(&self.sys_vendor),
+            field(&self.product_name),
+            field(&self.board_name)
+        )
+    }
+}
+
+/// `[baseline]` table declaring what a baseline file applies to.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct BaselineInfo {
+    /// Human-readable machine name.
+    #[serde(default)]
+    pub name: Option<String>,
+
+    /// DMI values the machine must have.
+    #[serde(default, rename = "match")]
+    pub dmi: DmiMatch,
+}
+
+/// DMI values a baseline matches. Comparison ignores case; a trailing `*`
+/// matches any suffix (e.g. a board revision). Unset fields match anything.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct DmiMatch {
+    #[serde(default)]
+    pub sys_vendor: Option<String>,
+    #[serde(default)]
+    pub product_name: Option<String>,
+    #[serde(default)]
+    pub board_name: Option<String>,
+}
+
+impl DmiMatch {
+    fn fields<'a>(&'a self, dmi: &'a Dmi) -> [(Option<&'a str>, Option<&'a str>); 3] {
+        [
+            (self.sys_vendor.as_deref(), dmi.sys_vendor.as_deref()),
+            (self.product_name.as_deref(), dmi.product_name.as_deref()),
+            (self.board_name.as_deref(), dmi.board_name.as_deref()),
+        ]
+    }
+
+    /// Number of fields matched, or `None` if any set field does not match.
+    /// A match with no fields set never applies.
+    pub fn score(&self, dmi: &Dmi) -> Option<usize> {
+        let mut score = 0;
+        for (pattern, value) in self.fields(dmi) {
+            let Some(pattern) = pattern else {
+                continue;
+            };
+            if !value.is_some_and(|value| dmi_value_matches(pattern, value)) {
+                return None;
+            }
+            score += 1;
+        }
+        (score > 0).then_some(score)
+    }
+}
+
+impl fmt::Display for DmiMatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let parts: Vec<String> = [
+            ("sys_vendor", &self.sys_vendor),
+            ("product_name", &self.product_name),
+            ("board_name", &self.board_name),
+        ]
+        .into_iter()
+        .filter_map(|(key, value)| value.as_ref().map(|v| format!("{}={}", key, v)))
+        .collect();
+        if parts.is_empty() {
+            write!(f, "(never auto-selected)")
+        } else {
+            write!(f, "{}", parts.join(" "))
+        }
+    }
+}
+
+fn dmi_value_matches(pattern: &str, value: &str) -> bool {
+    let value = value.to_lowercase();
+    let pattern = pattern.to_lowercase();
+    match pattern.strip_suffix('*') {
+        Some(prefix) => value.starts_with(prefix),
+        None => value == pattern,
+    }
+}
+
+/// Where a baseline was found.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BaselineSource {
+    /// Compiled into the binary.
+    Bundled,
+    /// A file in a baselines directory.
+    File(PathBuf),
+}
+
+/// A hardware baseline config.
+#[derive(Debug, Clone)]
+pub struct Baseline {
+    /// File stem, used to select the baseline by name.
+    pub id: String,
+    pub source: BaselineSource,
+    pub content: String,
+    pub info: BaselineInfo,
+}
+
+impl Baseline {
+    fn parse(id: &str, source: BaselineSource, content: String) -> Result<Self, ConfigError> {
+        #[derive(Deserialize)]
+        struct Declaration {
+            #[serde(default)]
+            baseline: BaselineInfo,
+        }
+        let info = toml::from_str::<Declaration>(&content)?.baseline;
+        Ok(Self {
+            id: id.to_string(),
+            source,
+            content,
+            info,
+        })
+    }
+
+    /// Path shown for the baseline: the file, or `bundled:<id>`.
+    pub fn path(&self) -> PathBuf {
+        match &self.source {
+            BaselineSource::Bundled => PathBuf::from(format!("bundled:{}", self.id)),
+            BaselineSource::File(path) => path.clone(),
+        }
+    }
+
+    /// The baseline as the lowest config layer.
+    ///
+    /// Bundled baselines cannot `inherit`; file baselines resolve it
+    /// relative to their directory like any config file.
+    pub fn layer(&self) -> Result<Vec<ConfigLayer>, ConfigError> {
+        match &self.source {
+            BaselineSource::Bundled => {
+                let value: toml::Value = toml::from_str(&self.content)?;
+                Ok(vec![ConfigLayer {
+                    path: self.path(),
+                    value,
+                }])
+            }
+            BaselineSource::File(path) => super::Config::load_layers(path),
+        }
+    }
+}
+
+/// Baseline directories, highest priority first: per-user, then system.
+pub fn baseline_dirs() -> Vec<PathBuf> {
+    dirs::config_dir()
+        .map(|p| p.join("usbbw").join("baselines"))
+        .into_iter()
+        .chain(std::iter::once(PathBuf::from(SYSTEM_BASELINES_DIR)))
+        .collect()
+}
+
+/// All baselines in `dirs` plus the bundled ones, sorted by id. A file
+/// shadows a bundled baseline or one in a later directory with the same id.
+///
+/// Files that cannot be read or parsed are skipped and returned alongside,
+/// so one broken baseline doesn't stop every command.
+pub fn available_baselines(dirs: &[PathBuf]) -> (Vec<Baseline>, Vec<(PathBuf, ConfigError)>) {
+    let mut baselines: Vec<Baseline> = Vec::new();
+    let mut errors = Vec::new();
+    for dir in dirs {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            continue;
+        };
+        let mut paths: Vec<PathBuf> = entries
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| p.extension().is_some_and(|ext| ext == "toml"))
+            .collect();
+        paths.sort();
+        for path in paths {
+            let Some(id) = path.file_stem().and_then(|s| s.to_str()) else {
+                continue;
+            };
+            if baselines.iter().any(|b| b.id == id) {
+                continue;
+            }
+            let parsed = std::fs::read_to_string(&path)
+                .map_err(ConfigError::from)
+                .and_then(|content| {
+                    Baseline::parse(id, BaselineSource::File(path.clone()), content)
+                });
+            match parsed {
+                Ok(baseline) => baselines.push(baseline),
+                Err(e) => errors.push((path, e)),
+            }
+        }
+    }
+    for (id, content) in BUNDLED {
+        if !baselines.iter().any(|b| b.id == *id) {
+            match Baseline::parse(id, BaselineSource::Bundled, content.to_string()) {
+                Ok(baseline) => baselines.push(baseline),
+                Err(e) => errors.push((PathBuf::from(format!("bundled:{}", id)), e)),
+            }
+        }
+    }
+    baselines.sort_by(|a, b| a.id.cmp(&b.id));
+    (baselines, errors)
+}
+
+/// The baseline matching the most DMI fields (ties go to the first by id).
+pub fn select_baseline<'a>(baselines: &'a [Baseline], dmi: &Dmi) -> Option<&'a Baseline> {
+    baselines
+        .iter()
+        .filter_map(|b| b.info.dmi.score(dmi).map(|score| (score, b)))
+        .rev()
+        .max_by_key(|(score, _)| *score)
+        .map(|(_, b)| b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dmi(vendor: &str, product: &str, board: &str) -> Dmi {
+        Dmi {
+            sys_vendor: Some(vendor.to_string()),
+            product_name: Some(product.to_string()),
+            board_name: Some(board.to_string()),
+        }
+    }
+
+    fn baseline(id: &str, content: &str) -> Baseline {
+        Baseline::parse(id, BaselineSource::Bundled, content.to_string()).unwrap()
+    }
+
+    #[test]
+    fn test_select_baseline() {
+        let baselines = vec![
+            baseline(
+                "framework-any",
+                "[baseline.match]\nsys_vendor = \"Framework\"\n",
+            ),
+            baseline(
+                "framework-franmgcp",
+                "[baseline.match]\nsys_vendor = \"framework\"\nboard_name = \"FRANMGCP*\"\n",
+            ),
+            baseline("unmatched", "[settings]\nrefresh_ms = 500\n"),
+        ];
+
+        let laptop = dmi("Framework", "Laptop 13", "FRANMGCP09");
+        assert_eq!(
+            select_baseline(&baselines, &laptop).map(|b| b.id.as_str()),
+            Some("framework-franmgcp")
+        );
+        let other = dmi("Framework", "Laptop 16", "FRANMZCP07");
+        assert_eq!(
+            select_baseline(&baselines, &other).map(|b| b.id.as_str()),
+            Some("framework-any")
+        );
+        assert!(select_baseline(&baselines, &Dmi::default()).is_none());
+    }
+
+    #[test]
+    fn test_stack_selection() {
+        use crate::config::ConfigStack;
+
+        let baselines = vec![baseline(
+            "laptop",
+            "[baseline.match]\nsys_vendor = \"Framework\"\n[controllers]\n\"0000:c1:00.4\" = \"Internal\"\n",
+        )];
+        let user = |content: &str| {
+            vec![ConfigLayer {
+                path: PathBuf::from("/home/me.toml"),
+                value: toml::from_str(content).unwrap(),
+            }]
+        };
+        let laptop = dmi("Framework", "Laptop 13", "FRANMGCP09");
+
+        let stack = ConfigStack::new(user(""), None, &baselines, &laptop).unwrap();
+        assert_eq!(stack.baseline_layers, 1);
+        assert_eq!(stack.layers[0].path, PathBuf::from("bundled:laptop"));
+        let config = crate::config::Config::from_stack(&stack).unwrap();
+        assert_eq!(config.sources, [PathBuf::from("/home/me.toml")]);
+        assert_eq!(
+            config.controllers.get("0000:c1:00.4").map(String::as_str),
+            Some("Internal")
+        );
+
+        let none = user("[settings]\nbaseline = \"none\"\n");
+        assert!(
+            ConfigStack::new(none, None, &baselines, &laptop)
+                .unwrap()
+                .baseline
+                .is_none()
+        );
+        let inherited = user("[baseline]\nname = \"Mine\"\n");
+        assert!(
+            ConfigStack::new(inherited, None, &baselines, &laptop)
+                .unwrap()
+                .baseline
+                .is_none()
+        );
+        let named = user("[settings]\nbaseline = \"laptop\"\n");
+        assert!(
+            ConfigStack::new(named, None, &baselines, &Dmi::default())
+                .unwrap()
+                .baseline
+                .is_some()
+        );
+        let unknown = user("[settings]\nbaseline = \"desktop\"\n");
+        assert!(ConfigStack::new(unknown, None, &baselines, &laptop).is_err());
+    }
+
+    #[test]
+    fn test_malformed_baseline_skipped() {
+        let dir = std::env::temp_dir().join(format!("usbbw-baselines-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("broken.toml"), "[baseline\n").unwrap();
+        std::fs::write(
+            dir.join("desk.toml"),
+            "[baseline.match]\nsys_vendor = \"Dell\"\n",
+        )
+        .unwrap();
+
+        let (baselines, errors) = available_baselines(std::slice::from_ref(&dir));
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(baselines.iter().any(|b| b.id == "desk"));
+        assert!(!baselines.iter().any(|b| b.id == "broken"));
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].0, dir.join("broken.toml"));
+    }
+
+    #[test]
+    fn test_bundled_baselines_parse() {
+        let (baselines, errors) = available_baselines(&[]);
+        assert!(errors.is_empty(), "{:?}", errors);
+        assert!(!baselines.is_empty());
+        for baseline in &baselines {
+            assert!(baseline.info.dmi.sys_vendor.is_some(), "{}", baseline.id);
+            let layers = baseline.layer().unwrap();
+            crate::config::Config::from_layers(&layers).unwrap();
+        }
+    }
+}
@@ -0,0 +1,622 @@
+//! Config diagnostics: where values come from, validation, and why a device
+//! has the label it has (`usbbw config show|validate|explain`).
+
+use super::label::LabelTarget;
+use super::loader::{Config, ConfigLayer, PhysicalPortLabel};
+use crate::model::{PhysicalLocation, UsbDevice, UsbTopology};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Column at which source annotations start in `config show`.
+const SOURCE_COLUMN: usize = 44;
+
+/// Severity of a validation finding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// The config is wrong: the entry is ignored or never matches.
+    Error,
+    /// Probably unintended, but harmless.
+    Warning,
+}
+
+/// A problem found by [`validate`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+    /// How serious it is.
+    pub severity: Severity,
+    /// What is wrong.
+    pub message: String,
+    /// File that set the offending value, if known.
+    pub source: Option<std::path::PathBuf>,
+}
+
+impl Finding {
+    fn new(severity: Severity, message: String, source: Option<&Path>) -> Self {
+        Self {
+            severity,
+            message,
+            source: source.map(Path::to_path_buf),
+        }
+    }
+}
+
+/// Labels just written to `written` that another file overrides, with the
+/// overriding file. Physical port entries are additive and never shadowed.
+pub fn shadowed_labels<'a>(
+    layers: &[ConfigLayer],
+    written: &Path,
+    targets: impl IntoIterator<Item = &'a LabelTarget>,
+) -> Vec<(LabelTarget, PathBuf)> {
+    targets
+        .into_iter()
+        .filter(|target| !matches!(target, LabelTarget::PhysicalPort(_)))
+        .filter_map(|target| {
+            let key = [target.table().to_string(), target.key()];
+            source_of(layers, &key)
+                .filter(|source| *source != written)
+                .map(|source| (target.clone(), source.to_path_buf()))
+        })
+        .collect()
+}
+
+/// File that set the value at `key`, the last layer to set it.
+///
+/// Arrays are concatenated across layers, so an index into an array is
+/// attributed to the layer that contributed that element.
+pub fn source_of<'a>(layers: &'a [ConfigLayer], key: &[String]) -> Option<&'a Path> {
+    let mut source = None;
+    let mut array_offset = 0;
+    for layer in layers {
+        let mut value = Some(&layer.value);
+        for segment in key {
+            value = match value {
+                Some(toml::Value::Table(table)) => table.get(segment),
+                Some(toml::Value::Array(items)) => {
+                    let index: usize = segment.parse().ok()?;
+                    if index < array_offset + items.len() {
+                        return Some(&layer.path);
+                    }
+                    array_offset += items.len();
+                    None
+                }
+                _ => None,
+            };
+        }
+        if value.is_some() {
+            source = Some(layer.path.as_path());
+        }
+    }
+    source
+}
+
+/// Effective config after inheritance, each value annotated with the file
+/// it came from.
+pub fn render_effective(layers: &[ConfigLayer]) -> String {
+    let mut root = Node::Table(Vec::new());
+    for (index, layer) in layers.iter().enumerate() {
+        merge_node(&mut root, &layer.value, index);
+    }
+
+    let mut out = String::from("# Effective configuration (unlisted settings use defaults)\n");
+    if layers.is_empty() {
+        out.push_str("# No config file loaded\n");
+    } else {
+        out.push_str("# Sources, lowest priority first:\n");
+        for (index, layer) in layers.iter().enumerate() {
+            out.push_str(&format!("#   [{}] {}\n", index + 1, layer.path.display()));
+        }
+    }
+    if let Node::Table(entries) = &root {
+        render_table(&mut out, "", entries);
+    }
+    out
+}
+
+/// A merged value and the layers it came from.
+enum Node {
+    Table(Vec<(String, Node)>),
+    /// Array of tables (`[[name]]`), concatenated across layers.
+    Tables(Vec<Node>),
+    Value(toml::Value, Vec<usize>),
+}
+
+fn is_table_array(items: &[toml::Value]) -> bool {
+    items.iter().all(toml::Value::is_table)
+}
+
+fn node_from(value: &toml::Value, layer: usize) -> Node {
+    match value {
+        toml::Value::Table(table) => Node::Table(
+            table
+                .iter()
+                .map(|(k, v)| (k.clone(), node_from(v, layer)))
+                .collect(),
+        ),
+        toml::Value::Array(items) if !items.is_empty() && is_table_array(items) => {
+            Node::Tables(items.iter().map(|v| node_from(v, layer)).collect())
+        }
+        other => Node::Value(other.clone(), vec![layer]),
+    }
+}
+
+/// Same rules as the loader's merge: tables merge, arrays concatenate,
+/// anything else is replaced.
+fn merge_node(node: &mut Node, value: &toml::Value, layer: usize) {
+    match (&mut *node, value) {
+        (Node::Table(entries), toml::Value::Table(table)) => {
+            for (key, value) in table {
+                match entries.iter_mut().find(|(k, _)| k == key) {
+                    Some((_, child)) => merge_node(child, value, layer),
+                    None => entries.push((key.clone(), node_from(value, layer))),
+                }
+            }
+        }
+        (Node::Tables(items), toml::Value::Array(new)) if is_table_array(new) => {
+            items.extend(new.iter().map(|v| node_from(v, layer)));
+        }
+        (Node::Value(toml::Value::Array(items), layers), toml::Value::Array(new)) => {
+            items.extend(new.iter().cloned());
+            if !layers.contains(&layer) {
+                layers.push(layer);
+            }
+        }
+        _ => *node = node_from(value, layer),
+    }
+}
+
+/// Key as written in TOML: bare if possible, else quoted.
+fn toml_key(key: &str) -> String {
+    let bare = !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if bare {
+        key.to_string()
+    } else {
+        toml::Value::String(key.to_string()).to_string()
+    }
+}
+
+fn render_table(out: &mut String, path: &str, entries: &[(String, Node)]) {
+    for (key, node) in entries {
+        if let Node::Value(value, layers) = node {
+            let line = format!("{} = {}", toml_key(key), value);
+            let sources: Vec<String> = layers.iter().map(|l| format!("[{}]", l + 1)).collect();
+            out.push_str(&format!(
+                "{:<width$} # {}\n",
+                line,
+                sources.join(" "),
+                width = SOURCE_COLUMN
+            ));
+        }
+    }
+    for (key, node) in entries {
+        let child_path = if path.is_empty() {
+            toml_key(key)
+        } else {
+            format!("{}.{}", path, toml_key(key))
+        };
+        match node {
+            Node::Table(children) => {
+                let has_values = children.iter().any(|(_, n)| matches!(n, Node::Value(..)));
+                if has_values || children.is_empty() {
+                    out.push_str(&format!("\n[{}]\n", child_path));
+                }
+                render_table(out, &child_path, children);
+            }
+            Node::Tables(items) => {
+                for item in items {
+                    out.push_str(&format!("\n[[{}]]\n", child_path));
+                    if let Node::Table(children) = item {
+                        render_table(out, &child_path, children);
+                    }
+                }
+            }
+            Node::Value(..) => {}
+        }
+    }
+}
+
+/// Check a config for mistakes.
+///
+/// Reports unknown keys, malformed `[products]` and `[buses]` keys,
+/// `[[label_rules]]` with a bad regex or template, rules and
+/// `[[physical_ports]]` entries that match nothing on `topology` (skipped
+/// without one), and labels used by more than one entry.
+pub fn validate(layers: &[ConfigLayer], topology: Option<&UsbTopology>) -> Vec<Finding> {
+    let mut findings = Vec::new();
+
+    for layer in layers {
+        let mut unknown = Vec::new();
+        let parsed: Result<Config, _> =
+            serde_ignored::deserialize(layer.value.clone(), |path| unknown.push(path.to_string()));
+        if parsed.is_err() {
+            // Reported below for the merged config; a layer may be partial
+            continue;
+        }
+        for key in unknown {
+            findings.push(Finding::new(
+                Severity::Error,
+                format!("unknown key `{}`", key),
+                Some(&layer.path),
+            ));
+        }
+    }
+
+    let config = match Config::from_layers(layers) {
+        Ok(config) => config,
+        Err(e) => {
+            findings.push(Finding::new(Severity::Error, e.to_string(), None));
+            return findings;
+        }
+    };
+    let source = |table: &str, key: &str| source_of(layers, &[table.to_string(), key.to_string()]);
+
+    for key in sorted_keys(&config.products) {
+        if let Some(problem) = product_key_problem(key) {
+            findings.push(Finding::new(
+                Severity::Error,
+                format!("[products] \"{}\": {}", key, problem),
+                source("products", key),
+            ));
+        }
+    }
+    for key in sorted_keys(&config.buses) {
+        if key.parse::<u8>().is_err() && !is_stable_bus_key(key) {
+            findings.push(Finding::new(
+                Severity::Error,
+                format!(
+                    "[buses] \"{}\": expected a bus number or \"<controller>/usb2|usb3\"",
+                    key
+                ),
+                source("buses", key),
+            ));
+        } else if is_stable_bus_key(key)
+            && let Some(topology) = topology
+            && !topology
+                .buses
+                .keys()
+                .any(|&bus_num| topology.bus_keys(bus_num).contains(key))
+        {
+            findings.push(Finding::new(
+                Severity::Warning,
+                format!("[buses] \"{}\": matches no controller on this system", key),
+                source("buses", key),
+            ));
+        }
+    }
+
+    for (index, rule) in config.label_rules.iter().enumerate() {
+        let source = source_of(layers, &["label_rules".to_string(), index.to_string()]);
+        if let Some(problem) = rule.problem() {
+            findings.push(Finding::new(
+                Severity::Error,
+                format!("[[label_rules]] #{}: {}", index + 1, problem),
+                source,
+            ));
+        } else if let Some(topology) = topology
+            && !topology
+                .buses
+                .values()
+                .flat_map(|bus| bus.devices.values())
+                .any(|device| rule.matches(device, topology))
+        {
+            findings.push(Finding::new(
+                Severity::Warning,
+                format!(
+                    "[[label_rules]] #{} ({}) matches no device on this system",
+                    index + 1,
+                    rule.describe()
+                ),
+                source,
+            ));
+        }
+    }
+
+    if let Some(topology) = topology {
+        let locations = port_locations(topology);
+        for (index, port) in config.physical_ports.iter().enumerate() {
+            if !locations.iter().any(|loc| port.matches(loc)) {
+                findings.push(Finding::new(
+                    Severity::Warning,
+                    format!(
+                        "[[physical_ports]] #{} (\"{}\", {}) matches no port on this system",
+                        index + 1,
+                        port.label,
+                        describe_port(port)
+                    ),
+                    source_of(layers, &["physical_ports".to_string(), index.to_string()]),
+                ));
+            }
+        }
+    }
+
+    for (label, users) in label_users(&config) {
+        if users.len() > 1 {
+            findings.push(Finding::new(
+                Severity::Warning,
+                format!("label \"{}\" is used by {}", label, users.join(", ")),
+                None,
+            ));
+        }
+    }
+    findings
+}
+
+fn sorted_keys(map: &std::collections::HashMap<String, String>) -> Vec<&String> {
+    let mut keys: Vec<_> = map.keys().collect();
+    keys.sort();
+    keys
+}
+
+/// Why a `[products]` key can never match, if it can't.
+fn product_key_problem(key: &str) -> Option<&'static str> {
+    let mut parts = key.splitn(3, ':');
+    let vid = parts.next().unwrap_or_default();
+    let Some(pid) = parts.next() else {
+        return Some("expected VID:PID or VID:PID:iSerial");
+    };
+    let serial = parts.next();
+    let is_hex = |s: &str| s.len() == 4 && s.chars().all(|c| c.is_ascii_hexdigit());
+    if !is_hex(vid) || !is_hex(pid) {
+        return Some("VID and PID must be 4 hex digits");
+    }
+    if key[..9].chars().any(|c| c.is_ascii_uppercase()) {
+        return Some("VID:PID must be lowercase hex");
+    }
+    if serial == Some("") {
+        return Some("empty serial");
+    }
+    None
+}
+
+/// Whether a `[buses]` key names a controller and role, e.g.
+/// "0000:c3:00.0/usb2".
+fn is_stable_bus_key(key: &str) -> bool {
+    key.rsplit_once('/')
+        .is_some_and(|(controller, role)| !controller.is_empty() && matches!(role, "usb2" | "usb3"))
+}
+
+/// Every ACPI location on the system: root hub ports and devices.
+fn port_locations(topology: &UsbTopology) -> Vec<&PhysicalLocation> {
+    let ports = topology
+        .buses
+        .values()
+        .flat_map(|bus| bus.ports.iter())
+        .filter_map(|port| port.physical_location.as_ref());
+    let devices = topology
+        .buses
+        .values()
+        .flat_map(|bus| bus.devices.values())
+        .filter_map(|device| device.physical_location.as_ref());
+    ports.chain(devices).collect()
+}
+
+fn describe_port(port: &PhysicalPortLabel) -> String {
+    let fields: Vec<String> = [
+        ("panel", &port.panel),
+        ("vertical_position", &port.vertical_position),
+        ("horizontal_position", &port.horizontal_position),
+    ]
+    .into_iter()
+    .filter_map(|(name, value)| value.as_ref().map(|v| format!("{} = {}", name, v)))
+    .chain(port.dock.map(|d| format!("dock = {}", d)))
+    .collect();
+    if fields.is_empty() {
+        "matches any port".to_string()
+    } else {
+        fields.join(", ")
+    }
+}
+
+/// Entries using each label, e.g. `[products] "0d28:0204"`.
+fn label_users(config: &Config) -> BTreeMap<&str, Vec<String>> {
+    let mut users: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+    for (table, map) in [
+        ("controllers", &config.controllers),
+        ("buses", &config.buses),
+        ("products", &config.products),
+        ("devices", &config.devices),
+    ] {
+        for key in sorted_keys(map) {
+            users
+                .entry(map[key].as_str())
+                .or_default()
+                .push(format!("[{}] \"{}\"", table, key));
+        }
+    }
+    for (index, port) in config.physical_ports.iter().enumerate() {
+        users
+            .entry(port.label.as_str())
+            .or_default()
+            .push(format!("[[physical_ports]] #{}", index + 1));
+    }
+    users
+}
+
+/// Explain which config entry labels `device`, in `Config::device_label`
+/// priority order. `config` must be resolved against the device's topology.
+pub fn explain_label(layers: &[ConfigLayer], config: &Config, device: &UsbDevice) -> String {
+    let location = config.device_location(device);
+    let candidates = config.device_candidates(device);
+
+    let mut out = format!(
+        "Device {} ({}) {}\n",
+        device.path.0,
+        device.vid_pid(),
+        device.display_name()
+    );
+    if let Some(serial) = &device.serial {
+        out.push_str(&format!("  Serial: {}\n", serial));
+    }
+    match &location {
+        Some(loc) => out.push_str(&format!("  Port location: {}\n", loc.display())),
+        None => out.push_str("  Port location: not reported\n"),
+    }
+    out.push('\n');
+
+    let mut winner = None;
+    for (rank, candidate) in candidates.iter().enumerate() {
+        let status = match (&candidate.label, winner.is_some()) {
+            (Some(label), false) => {
+                winner = Some(label.clone());
+                format!("\"{}\"  <- used", label)
+            }
+            (Some(label), true) => format!("\"{}\"  (shadowed)", label),
+            (None, _) => "no entry".to_string(),
+        };
+        out.push_str(&format!(
+            "  {}. {:<40} {}\n",
+            rank + 1,
+            candidate.rule,
+            status
+        ));
+        if candidate.label.is_some() {
+            let source = (!candidate.key.is_empty())
+                .then(|| source_of(layers, &candidate.key))
+                .flatten()
+                .map_or_else(
+                    || "auto-detected defaults".to_string(),
+                    |path| path.display().to_string(),
+                );
+            out.push_str(&format!("     {:<40} from {}\n", "", source));
+        }
+    }
+    if location.is_none() {
+        out.push_str("     [[physical_ports]] not checked: no port location\n");
+    }
+
+    out.push('\n');
+    match winner {
+        Some(label) => out.push_str(&format!("Label: {}\n", label)),
+        None => out.push_str(&format!(
+            "Label: none configured, showing \"{}\"\n",
+            device.display_name()
+        )),
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn layer(path: &str, content: &str) -> ConfigLayer {
+        ConfigLayer {
+            path: PathBuf::from(path),
+            value: toml::from_str(content).unwrap(),
+        }
+    }
+
+    fn layers() -> Vec<ConfigLayer> {
+        vec![
+            layer(
+                "/etc/team.toml",
+                r#"
+                [products]
+                "0d28:0204" = "Probe"
+                "0D28:0205" = "Typo"
+
+                [[physical_ports]]
+                panel = "left"
+                label = "Left"
+                "#,
+            ),
+            layer(
+                "/home/me.toml",
+                r#"
+                [settings]
+                refersh_ms = 500
+
+                [products]
+                "0d28:0204" = "My probe"
+
+                [devices]
+                "1-2" = "Left"
+
+                [[physical_ports]]
+                panel = "back"
+                label = "Back"
+                "#,
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_source_of() {
+        let layers = layers();
+        let key = |parts: &[&str]| parts.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            source_of(&layers, &key(&["products", "0d28:0204"])),
+            Some(Path::new("/home/me.toml"))
+        );
+        assert_eq!(
+            source_of(&layers, &key(&["products", "0D28:0205"])),
+            Some(Path::new("/etc/team.toml"))
+        );
+        assert_eq!(
+            source_of(&layers, &key(&["physical_ports", "1"])),
+            Some(Path::new("/home/me.toml"))
+        );
+        assert_eq!(source_of(&layers, &key(&["products", "nope"])), None);
+    }
+
+    #[test]
+    fn test_shadowed_labels() {
+        let layers = layers();
+        let probe = LabelTarget::Product("0d28:0204".to_string());
+        let typo = LabelTarget::Product("0D28:0205".to_string());
+        let new = LabelTarget::Device("1-3".to_string());
+        let port = LabelTarget::PhysicalPort(PhysicalLocation::default());
+
+        // Written to the team file: the personal config overrides the probe
+        let shadowed = shadowed_labels(
+            &layers,
+            Path::new("/etc/team.toml"),
+            [&probe, &typo, &new, &port],
+        );
+        assert_eq!(
+            shadowed,
+            vec![(probe.clone(), PathBuf::from("/home/me.toml"))]
+        );
+
+        let shadowed = shadowed_labels(&layers, Path::new("/home/me.toml"), [&probe]);
+        assert!(shadowed.is_empty());
+    }
+
+    #[test]
+    fn test_validate() {
+        let findings = validate(&layers(), None);
+        let messages: Vec<&str> = findings.iter().map(|f| f.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            [
+                "unknown key `settings.refersh_ms`",
+                "[products] \"0D28:0205\": VID:PID must be lowercase hex",
+                "label \"Left\" is used by [devices] \"1-2\", [[physical_ports]] #1",
+            ]
+        );
+        assert_eq!(findings[0].source, Some(PathBuf::from("/home/me.toml")));
+    }
+
+    #[test]
+    fn test_render_effective() {
+        let out = render_effective(&layers());
+        assert!(out.contains("#   [2] /home/me.toml\n"));
+        let probe = out
+            .lines()
+            .find(|l| l.starts_with("\"0d28:0204\""))
+            .unwrap();
+        assert!(probe.contains("\"My probe\"") && probe.ends_with("# [2]"));
+        assert_eq!(out.matches("[[physical_ports]]").count(), 2);
+    }
+
+    #[test]
+    fn test_product_key_problem() {
+        assert_eq!(product_key_problem("0d28:0204:ABC"), None);
+        assert!(product_key_problem("0d28").is_some());
+        assert!(product_key_problem("0d28:204").is_some());
+        assert!(product_key_problem("0d28:0204:").is_some());
+    }
+}
@@ -0,0 +1,397 @@
+//! Label targets: what a user-assigned label applies to, and the config
+//! table it is stored in.
+
+use super::loader::{Config, PhysicalPortLabel};
+use crate::model::{PhysicalLocation, UsbDevice};
+
+/// Something that can be labelled.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum LabelTarget {
+    /// A product, keyed by VID:PID:iSerial or VID:PID (`[products]`).
+    Product(String),
+    /// A device path such as "3-1.2" (`[devices]`).
+    Device(String),
+    /// A controller, keyed by PCI address (`[controllers]`).
+    Controller(String),
+    /// A bus and its `[buses]` key (see [`Config::bus_target`]).
+    Bus {
+        /// Bus number on the current topology.
+        bus_num: u8,
+        /// Key the label is stored under.
+        key: String,
+    },
+    /// A physical port, matched by ACPI location (`[[physical_ports]]`).
+    PhysicalPort(PhysicalLocation),
+}
+
+impl LabelTarget {
+    /// Short name of the target kind.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Product(_) => "product",
+            Self::Device(_) => "device path",
+            Self::Controller(_) => "controller",
+            Self::Bus { .. } => "bus",
+            Self::PhysicalPort(_) => "physical port",
+        }
+    }
+
+    /// Config table the label is written to.
+    pub fn table(&self) -> &'static str {
+        match self {
+            Self::Product(_) => "products",
+            Self::Device(_) => "devices",
+            Self::Controller(_) => "controllers",
+            Self::Bus { .. } => "buses",
+            Self::PhysicalPort(_) => "physical_ports",
+        }
+    }
+
+    /// Key within the table (a location description for physical ports).
+    pub fn key(&self) -> String {
+        match self {
+            Self::Product(key)
+            | Self::Device(key)
+            | Self::Controller(key)
+            | Self::Bus { key, .. } => key.clone(),
+            Self::PhysicalPort(loc) => {
+                let mut key = loc.display();
+                if loc.dock {
+                    key = format!("dock {}", key).trim_end().to_string();
+                }
+                key
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for LabelTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.kind(), self.key())
+    }
+}
+
+impl PhysicalPortLabel {
+    /// An entry matching exactly one ACPI location.
+    pub fn for_location(loc: &PhysicalLocation, label: String) -> Self {
+        let field = |v: &str| (!v.is_empty()).then(|| v.to_string());
+        Self {
+            panel: field(&loc.panel),
+            horizontal_position: field(&loc.horizontal_position),
+            vertical_position: field(&loc.vertical_position),
+            dock: loc.dock.then_some(true),
+            label,
+        }
+    }
+
+    /// Does this entry match an ACPI location? Unset fields match anything.
+    pub fn matches(&self, loc: &PhysicalLocation) -> bool {
+        let panel_matches = self.panel.as_ref().map(|p| p == &loc.panel).unwrap_or(true);
+        let h_pos_matches = self
+            .horizontal_position
+            .as_ref()
+            .map(|h| h == &loc.horizontal_position)
+            .unwrap_or(true);
+        let v_pos_matches = self
+            .vertical_position
+            .as_ref()
+            .map(|v| v == &loc.vertical_position)
+            .unwrap_or(true);
+        let dock_matches = self.dock.map(|d| d == loc.dock).unwrap_or(true);
+
+        panel_matches && h_pos_matches && v_pos_matches && dock_matches
+    }
+
+    fn is_for_location(&self, loc: &PhysicalLocation) -> bool {
+        let same = |field: &Option<String>, value: &str| field.as_deref().unwrap_or("") == value;
+        same(&self.panel, &loc.panel)
+            && same(&self.horizontal_position, &loc.horizontal_position)
+            && same(&self.vertical_position, &loc.vertical_position)
+            && self.dock.unwrap_or(false) == loc.dock
+    }
+}
+
+/// A config entry that can label a device, as checked by
+/// [`Config::device_label`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LabelCandidate {
+    /// Rule, e.g. `[products] "0d28:0204"`.
+    pub rule: String,
+    /// Key path of the entry (table keys or an array index), for looking up
+    /// the file it came from. Empty for auto-detected defaults.
+    pub key: Vec<String>,
+    /// Label the entry provides, if it exists.
+    pub label: Option<String>,
+    /// Target that edits this entry (`None` for `[[label_rules]]`).
+    pub target: Option<LabelTarget>,
+}
+
+impl Config {
+    /// Label target for a bus, stored under [`Config::bus_key`].
+    pub fn bus_target(&self, bus_num: u8) -> LabelTarget {
+        LabelTarget::Bus {
+            bus_num,
+            key: self.bus_key(bus_num),
+        }
+    }
+
+    /// Entries that could label a device, in priority order:
+    /// 1. Product with serial (VID:PID:iSerial) - specific device
+    /// 2. Product without serial (VID:PID) - all devices of this type
+    /// 3. Matching `[[label_rules]]`, by priority (as of the last
+    ///    [`Config::resolve_topology`])
+    /// 4. Physical location match (first matching `[[physical_ports]]`)
+    /// 5. Explicit device path label (legacy)
+    /// 6. Auto-detected product name, then port label (as of the last
+    ///    [`Config::apply_defaults_from_topology`])
+    ///
+    /// The first candidate with a label wins.
+    pub fn device_candidates(&self, device: &UsbDevice) -> Vec<LabelCandidate> {
+        self.label_candidates(
+            &device.path.0,
+            device.vendor_id,
+            device.product_id,
+            device.serial.as_deref(),
+            self.device_location(device).as_ref(),
+        )
+    }
+
+    fn label_candidates(
+        &self,
+        path: &str,
+        vendor_id: u16,
+        product_id: u16,
+        serial: Option<&str>,
+        physical_location: Option<&PhysicalLocation>,
+    ) -> Vec<LabelCandidate> {
+        let table_entry = |target: LabelTarget| LabelCandidate {
+            rule: format!("[{}] \"{}\"", target.table(), target.key()),
+            key: vec![target.table().to_string(), target.key()],
+            label: self.label_for(&target),
+            target: Some(target),
+        };
+        let mut candidates = Vec::new();
+
+        if let Some(serial) = serial {
+            let key = format!("{:04x}:{:04x}:{}", vendor_id, product_id, serial);
+            candidates.push(table_entry(LabelTarget::Product(key)));
+        }
+
+        let key = format!("{:04x}:{:04x}", vendor_id, product_id);
+        candidates.push(table_entry(LabelTarget::Product(key.clone())));
+
+        let rule_labels = self.rule_labels(path);
+        for (index, label) in rule_labels {
+            candidates.push(LabelCandidate {
+                rule: format!("[[label_rules]] #{}", index + 1),
+                key: vec!["label_rules".to_string(), index.to_string()],
+                label: Some(label.clone()),
+                target: None,
+            });
+        }
+        if rule_labels.is_empty() && !self.label_rules.is_empty() {
+            candidates.push(LabelCandidate {
+                rule: "[[label_rules]]".to_string(),
+                key: vec!["label_rules".to_string(), "-".to_string()],
+                label: None,
+                target: None,
+            });
+        }
+
+        if let Some(loc) = physical_location {
+            let matched = self.physical_ports.iter().position(|p| p.matches(loc));
+            let index = matched.map_or("-".to_string(), |i| i.to_string());
+            candidates.push(LabelCandidate {
+                rule: format!(
+                    "[[physical_ports]] {}",
+                    LabelTarget::PhysicalPort(loc.clone()).key()
+                ),
+                key: vec!["physical_ports".to_string(), index],
+                label: matched.map(|i| self.physical_ports[i].label.clone()),
+                target: Some(LabelTarget::PhysicalPort(loc.clone())),
+            });
+        }
+
+        candidates.push(table_entry(LabelTarget::Device(path.to_string())));
+
+        if let Some(label) = self.default_product(&key) {
+            candidates.push(LabelCandidate {
+                rule: format!("product name \"{}\"", key),
+                key: Vec::new(),
+                label: Some(label.to_string()),
+                target: None,
+            });
+        }
+        if let Some(loc) = physical_location
+            && let Some(label) = self.default_port_label(loc)
+        {
+            candidates.push(LabelCandidate {
+                rule: format!(
+                    "port label {}",
+                    LabelTarget::PhysicalPort(loc.clone()).key()
+                ),
+                key: Vec::new(),
+                label: Some(label.to_string()),
+                target: None,
+            });
+        }
+        candidates
+    }
+
+    /// Label configured for a target, if any.
+    pub fn label_for(&self, target: &LabelTarget) -> Option<String> {
+        match target {
+            LabelTarget::Product(key) => self.products.get(key).cloned(),
+            LabelTarget::Device(path) => self.devices.get(path).cloned(),
+            LabelTarget::Controller(pci) => self.controller_label(pci),
+            LabelTarget::Bus { bus_num, .. } => self.bus_label(*bus_num),
+            LabelTarget::PhysicalPort(loc) => self.physical_port_label(loc),
+        }
+    }
+
+    /// Set the label for a target in memory, replacing an existing entry.
+    pub fn set_label(&mut self, target: &LabelTarget, label: String) {
+        match target {
+            LabelTarget::Product(key) => {
+                self.products.insert(key.clone(), label);
+            }
+            LabelTarget::Device(path) => {
+                self.devices.insert(path.clone(), label);
+            }
+            LabelTarget::Controller(pci) => {
+                self.controllers.insert(pci.clone(), label);
+            }
+            LabelTarget::Bus { key, .. } => {
+                self.buses.insert(key.clone(), label);
+            }
+            LabelTarget::PhysicalPort(loc) => {
+                match self
+                    .physical_ports
+                    .iter_mut()
+                    .find(|p| p.is_for_location(loc))
+                {
+                    Some(existing) => existing.label = label,
+                    // Exact entries go first so they win over wildcard ones
+                    None => self
+                        .physical_ports
+                        .insert(0, PhysicalPortLabel::for_location(loc, label)),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_physical_port_label() {
+        let loc = PhysicalLocation {
+            panel: "left".to_string(),
+            vertical_position: "upper".to_string(),
+            horizontal_position: "center".to_string(),
+            ..Default::default()
+        };
+        let target = LabelTarget::PhysicalPort(loc.clone());
+        let mut config: Config = toml::from_str(
+            r#"
+            [[physical_ports]]
+            panel = "left"
+            label = "Any left port"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.label_for(&target).as_deref(), Some("Any left port"));
+
+        config.set_label(&target, "Left rear".to_string());
+        config.set_label(&target, "Left rear (USB-C)".to_string());
+        assert_eq!(config.physical_ports.len(), 2);
+        assert_eq!(
+            config.label_for(&target).as_deref(),
+            Some("Left rear (USB-C)")
+        );
+        assert_eq!(target.to_string(), "physical port left upper center");
+    }
+
+    #[test]
+    fn test_set_bus_label() {
+        let mut config = Config::default();
+        let target = config.bus_target(3);
+        assert_eq!(target.key(), "3");
+        config.set_label(&target, "Rear USB-A".to_string());
+        assert_eq!(config.bus_label(3).as_deref(), Some("Rear USB-A"));
+        assert_eq!(target.table(), "buses");
+    }
+
+    #[test]
+    fn test_stable_bus_keys() {
+        use crate::model::{UsbBus, UsbSpeed, UsbTopology};
+
+        // Controller enumerated as buses 5 and 6 instead of the usual 1 and 2
+        let mut topology = UsbTopology::fixture(5, vec![]);
+        for controller in topology.controllers.values_mut() {
+            controller.usb3_bus = Some(6);
+            controller.firmware_path = Some("\\_SB_.PCI0.GP17.XHC1".to_string());
+        }
+        topology.buses.insert(
+            6,
+            UsbBus {
+                speed: UsbSpeed::SuperPlus,
+                ..UsbBus::fixture(6, vec![])
+            },
+        );
+
+        let mut config: Config = toml::from_str(
+            r#"
+            [buses]
+            "5" = "Bus five"
+            "0000:c1:00.4/usb2" = "Front 2.0"
+            '\_SB_.PCI0.GP17.XHC1/usb3' = "Front 3.x"
+            "#,
+        )
+        .unwrap();
+        config.apply_defaults_from_topology(&topology);
+        assert_eq!(config.bus_label(5).as_deref(), Some("Front 2.0"));
+        assert_eq!(config.bus_label(6).as_deref(), Some("Front 3.x"));
+        assert_eq!(config.bus_target(5).key(), "0000:c1:00.4/usb2");
+        assert_eq!(config.bus_target(6).key(), "\\_SB_.PCI0.GP17.XHC1/usb3");
+
+        let mut config = Config::default();
+        config.apply_defaults_from_topology(&topology);
+        assert_eq!(config.bus_label(6).as_deref(), Some("Bus 6"));
+        assert_eq!(config.bus_target(6).key(), "0000:c1:00.4/usb3");
+    }
+
+    #[test]
+    fn test_root_port_location_label() {
+        use crate::model::{PortInfo, UsbDevice, UsbTopology};
+
+        // A root-level device reports no location of its own; its root port does
+        let loc = PhysicalLocation {
+            panel: "left".to_string(),
+            ..Default::default()
+        };
+        let device = UsbDevice::fixture("1-2", 0x0d28, 0x0204);
+        let mut topology = UsbTopology::fixture(1, vec![device.clone()]);
+        topology.buses.get_mut(&1).unwrap().ports = vec![PortInfo {
+            port_num: 2,
+            device_path: Some(device.path.clone()),
+            physical_location: Some(loc.clone()),
+            ..Default::default()
+        }];
+
+        let mut config: Config = toml::from_str(
+            r#"
+            [[physical_ports]]
+            panel = "left"
+            label = "Left port"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.device_label(&device), None);
+        config.resolve_topology(&topology);
+        assert_eq!(config.device_location(&device), Some(loc));
+        assert_eq!(config.device_name(&device), "Left port");
+    }
+}
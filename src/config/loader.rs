@@ -1,8 +1,14 @@
 //! Configuration loading and management.
 
-use crate::model::PhysicalLocation;
+use super::baseline::{
+    Baseline, BaselineInfo, Dmi, available_baselines, baseline_dirs, select_baseline,
+};
+use super::rules::{LabelRule, resolve_rule_labels};
+use crate::model::{
+    DEFAULT_CRITICAL_USAGE_PERCENT, DEFAULT_HIGH_USAGE_PERCENT, PhysicalLocation, Units, UsbDevice,
+};
 use serde::Deserialize;
-use std::collections::{HashMap, HashSet};
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use thiserror::Error;
 
@@ -15,6 +21,10 @@ pub enum ConfigError {
     Toml(#[from] toml::de::Error),
     #[error("Inheritance error: {0}")]
     Inheritance(String),
+    #[error("TOML parse error: {0}")]
+    Document(#[from] toml_edit::TomlError),
+    #[error("Cannot write config: {0}")]
+    Write(String),
 }
 
 /// Application configuration.
@@ -24,14 +34,29 @@ pub struct Config {
     #[serde(default)]
     pub settings: Settings,
 
+    /// Files the config was read from, highest priority (the loaded file)
+    /// first (canonical paths; empty when using defaults).
+    #[serde(skip)]
+    pub sources: Vec<PathBuf>,
+
+    /// Explicitly loaded config file, where label edits go by default
+    /// (the user config when the search locations were merged).
+    #[serde(skip)]
+    pub primary: Option<PathBuf>,
+
     /// Controller labels by PCI address.
     #[serde(default)]
     pub controllers: HashMap<String, String>,
 
-    /// Bus labels by bus number (keys are strings like "1", "2", etc.).
+    /// Bus labels by controller and role ("0000:c3:00.0/usb2"), controller
+    /// firmware path and role, or bus number ("1", "2", etc.).
     #[serde(default)]
     pub buses: HashMap<String, String>,
 
+    /// Stable `[buses]` keys of each bus number on the current topology.
+    #[serde(skip)]
+    bus_keys: HashMap<u8, Vec<String>>,
+
     /// Device labels by path (e.g., "3-1.2").
     #[serde(default)]
     pub devices: HashMap<String, String>,
@@ -44,6 +69,29 @@ pub struct Config {
     #[serde(default)]
     pub products: HashMap<String, String>,
 
+    /// Auto-detected product names by VID:PID, for devices no config entry
+    /// labels (as of the last [`Config::apply_defaults_from_topology`]).
+    #[serde(skip)]
+    default_products: HashMap<String, String>,
+
+    /// Auto-detected labels of ports no `[[physical_ports]]` entry names.
+    #[serde(skip)]
+    default_ports: Vec<PhysicalPortLabel>,
+
+    /// Pattern-based device labels.
+    #[serde(default)]
+    pub label_rules: Vec<LabelRule>,
+
+    /// `[[label_rules]]` matches of each device path on the current
+    /// topology: rule index and rendered label, highest priority first.
+    #[serde(skip)]
+    rule_labels: HashMap<String, Vec<(usize, String)>>,
+
+    /// Root port locations of root-level devices that report none of their
+    /// own, by device path, on the current topology.
+    #[serde(skip)]
+    port_locations: HashMap<String, PhysicalLocation>,
+
     /// Mermaid output settings.
     #[serde(default)]
     pub mermaid: MermaidConfig,
@@ -51,6 +99,22 @@ pub struct Config {
     /// Position label mappings for ACPI physical_location values.
     #[serde(default)]
     pub position_labels: PositionLabels,
+
+    /// Alert rules evaluated on topology changes.
+    #[serde(default)]
+    pub alerts: Vec<AlertRule>,
+
+    /// Expectations checked by `usbbw check`.
+    #[serde(default)]
+    pub expect: ExpectConfig,
+
+    /// TUI colour overrides on top of `settings.theme`.
+    #[serde(default)]
+    pub theme: ThemeColors,
+
+    /// Declaration of the hardware baseline in effect, if any.
+    #[serde(default)]
+    pub baseline: Option<BaselineInfo>,
 }
 
 /// Global settings.
@@ -60,13 +124,33 @@ pub struct Settings {
     #[serde(default = "default_refresh_ms")]
     pub refresh_ms: u64,
 
-    /// Color theme: "dark" or "light".
+    /// Color theme: "dark", "light", "high-contrast" or "monochrome".
     #[serde(default = "default_theme")]
     pub theme: String,
 
     /// Show bandwidth in bits per second (true) or bytes (false).
     #[serde(default = "default_use_bits")]
     pub use_bits: bool,
+
+    /// Unit system for bandwidth; takes precedence over `use_bits`.
+    #[serde(default)]
+    pub units: Option<Units>,
+
+    /// Periodic bandwidth usage (percent) shown as high.
+    #[serde(default = "default_high_usage_percent")]
+    pub high_usage_percent: f64,
+
+    /// Periodic bandwidth usage (percent) shown as critical.
+    #[serde(default = "default_critical_usage_percent")]
+    pub critical_usage_percent: f64,
+
+    /// Capture the mouse in the TUI (disable to keep terminal text selection).
+    #[serde(default = "default_mouse")]
+    pub mouse: bool,
+
+    /// Hardware baseline: "auto" (match by DMI), "none", or a baseline name.
+    #[serde(default = "default_baseline")]
+    pub baseline: String,
 }
 
 impl Default for Settings {
@@ -75,6 +159,11 @@ impl Default for Settings {
             refresh_ms: default_refresh_ms(),
             theme: default_theme(),
             use_bits: default_use_bits(),
+            units: None,
+            high_usage_percent: default_high_usage_percent(),
+            critical_usage_percent: default_critical_usage_percent(),
+            mouse: default_mouse(),
+            baseline: default_baseline(),
         }
     }
 }
@@ -91,6 +180,188 @@ fn default_use_bits() -> bool {
     true
 }
 
+fn default_high_usage_percent() -> f64 {
+    DEFAULT_HIGH_USAGE_PERCENT
+}
+
+fn default_critical_usage_percent() -> f64 {
+    DEFAULT_CRITICAL_USAGE_PERCENT
+}
+
+fn default_mouse() -> bool {
+    true
+}
+
+fn default_baseline() -> String {
+    "auto".to_string()
+}
+
+/// User-defined TUI colours (`[theme]` section).
+///
+/// Values are colour names ("red", "lightblue"), "#rrggbb", or 256-colour
+/// indices ("130"). Unset colours come from the `settings.theme` palette.
+#[derive(Debug, Clone, Deserialize, Default)]
+pub struct ThemeColors {
+    pub accent: Option<String>,
+    pub text: Option<String>,
+    pub muted: Option<String>,
+    pub highlight: Option<String>,
+    pub controller: Option<String>,
+    pub usb4: Option<String>,
+    pub bus: Option<String>,
+    pub usb2: Option<String>,
+    pub usb3: Option<String>,
+    pub secondary: Option<String>,
+    pub ok: Option<String>,
+    pub warn: Option<String>,
+    pub error: Option<String>,
+    pub new: Option<String>,
+    pub selection_bg: Option<String>,
+    pub bar_bg: Option<String>,
+    pub popup_bg: Option<String>,
+}
+
+/// Declarative topology expectations for `usbbw check`.
+#[derive(Debug, Clone, Deserialize, Default)]
+pub struct ExpectConfig {
+    /// Fail if any device is unconfigured.
+    #[serde(default)]
+    pub no_unconfigured: bool,
+
+    /// Required devices.
+    #[serde(default)]
+    pub devices: Vec<ExpectDevice>,
+
+    /// Bus usage limits.
+    #[serde(default)]
+    pub buses: Vec<ExpectBus>,
+}
+
+impl ExpectConfig {
+    /// Check if no expectations are configured.
+    pub fn is_empty(&self) -> bool {
+        !self.no_unconfigured && self.devices.is_empty() && self.buses.is_empty()
+    }
+}
+
+/// A required device. At least one matching device must be present, and
+/// every matching device must satisfy the given constraints.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ExpectDevice {
+    /// VID:PID[:serial] key or key prefix.
+    pub device: String,
+    /// Rule name (default: derived from the constraints).
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Physical port label (from `[[physical_ports]]`) the device must be behind.
+    #[serde(default)]
+    pub port: Option<String>,
+    /// Device path the device must be at or behind (e.g., "3-1").
+    #[serde(default)]
+    pub path: Option<String>,
+    /// Controller PCI address or label.
+    #[serde(default)]
+    pub controller: Option<String>,
+    /// Required speed ("low", "full", "high", "super", "super_plus",
+    /// "super_plus_2", or a short name like "480M").
+    #[serde(default)]
+    pub speed: Option<String>,
+}
+
+/// Maximum periodic bandwidth usage of a bus.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ExpectBus {
+    /// Bus number or stable `[buses]` key.
+    pub bus: BusRef,
+    /// Usage must stay below this percentage.
+    pub max_usage_percent: f64,
+}
+
+/// A bus in alert and expect rules: a bus number, or a `[buses]` key such
+/// as "0000:c3:00.0/usb2" that survives enumeration order changes.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(untagged)]
+pub enum BusRef {
+    /// Bus number.
+    Number(u8),
+    /// Controller and role, or a bus number as a string.
+    Key(String),
+}
+
+impl std::fmt::Display for BusRef {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Number(num) => write!(f, "{}", num),
+            Self::Key(key) => write!(f, "{}", key),
+        }
+    }
+}
+
+/// Alert rule: a condition plus the actions to take when it fires.
+#[derive(Debug, Clone, Deserialize)]
+pub struct AlertRule {
+    /// Rule name (shown in notifications and passed to hooks).
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Condition that triggers the rule.
+    #[serde(flatten)]
+    pub condition: AlertCondition,
+    /// Shell command run with USBBW_* environment variables describing the event.
+    #[serde(default)]
+    pub hook: Option<String>,
+    /// Ring the terminal bell and send an OSC 9 notification (TUI only).
+    #[serde(default)]
+    pub notify: bool,
+}
+
+impl AlertRule {
+    /// Rule name, falling back to the condition type.
+    pub fn display_name(&self) -> String {
+        self.name
+            .clone()
+            .unwrap_or_else(|| self.condition.name().to_string())
+    }
+}
+
+/// Alert conditions, selected by the `when` key.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "when", rename_all = "snake_case")]
+pub enum AlertCondition {
+    /// Periodic bandwidth usage of a bus rises above a percentage.
+    BusUsage {
+        /// Threshold in percent.
+        above_percent: f64,
+        /// Only this bus (all buses if unset).
+        #[serde(default)]
+        bus: Option<BusRef>,
+    },
+    /// A device fails to configure (new or existing device).
+    Unconfigured {
+        /// Only devices matching this VID:PID[:serial] key or key prefix.
+        #[serde(default)]
+        device: Option<String>,
+    },
+    /// The over-current count of a root hub port increases.
+    OverCurrent,
+    /// A specific device disappears.
+    Disappeared {
+        /// VID:PID[:serial] key or key prefix.
+        device: String,
+    },
+}
+
+impl AlertCondition {
+    /// Condition type name as used in the config file.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::BusUsage { .. } => "bus_usage",
+            Self::Unconfigured { .. } => "unconfigured",
+            Self::OverCurrent => "over_current",
+            Self::Disappeared { .. } => "disappeared",
+        }
+    }
+}
+
 /// Physical port label configuration.
 #[derive(Debug, Deserialize)]
 pub struct PhysicalPortLabel {
@@ -106,20 +377,27 @@ pub struct PhysicalPortLabel {
     pub label: String,
 }
 
-/// Mermaid output configuration.
+/// Diagram view filter configuration (`[mermaid]` section).
+///
+/// Applied by every diagram exporter (Mermaid, markdown, DOT, SVG/HTML).
 #[derive(Debug, Deserialize, Default)]
 pub struct MermaidConfig {
-    /// Device paths to hide from diagrams.
+    /// Device paths to hide from diagrams (including everything behind them).
     #[serde(default)]
     pub hide_paths: Vec<String>,
 
-    /// Only show devices matching these vendor IDs.
+    /// Only show devices matching these vendor IDs (hubs leading to a
+    /// matching device are kept).
     #[serde(default)]
     pub filter_vendors: Vec<String>,
 
     /// Collapse hubs with single child.
     #[serde(default)]
     pub collapse_single_child_hubs: bool,
+
+    /// Maximum device depth below the root hub (1 = root port devices only).
+    #[serde(default)]
+    pub max_depth: Option<usize>,
 }
 
 /// Position label mappings for ACPI physical_location values.
@@ -140,29 +418,73 @@ pub struct PositionLabels {
 }
 
 impl Config {
-    /// Load configuration from default locations.
-    /// Search order:
-    /// 1. ./usbbw.toml
-    /// 2. ~/.config/usbbw/config.toml
-    /// 3. /etc/usbbw.toml
+    /// Load configuration from default locations, merged lowest priority
+    /// first:
+    /// 1. /etc/usbbw.toml, then /etc/usbbw/conf.d/*.toml
+    /// 2. ~/.config/usbbw/config.toml, then ~/.config/usbbw/conf.d/*.toml
+    /// 3. ./usbbw.toml, then ./usbbw.d/*.toml
+    /// 4. `USBBW_<SETTING>` environment variables
+    ///
+    /// The hardware baseline matching this machine is applied underneath.
     pub fn load() -> Result<Self, ConfigError> {
-        let paths = Self::config_paths();
+        Self::from_stack(&Self::discover(None)?)
+    }
 
-        for path in paths.into_iter().flatten() {
-            if path.exists() {
-                return Self::load_from_path(&path);
+    /// Collect the config layers: `explicit` (else `$USBBW_CONFIG`, else
+    /// every search location), environment overrides on top and the
+    /// hardware baseline underneath.
+    pub fn discover(explicit: Option<&Path>) -> Result<ConfigStack, ConfigError> {
+        let explicit = explicit.map(Path::to_path_buf).or_else(|| {
+            std::env::var_os("USBBW_CONFIG")
+                .filter(|v| !v.is_empty())
+                .map(PathBuf::from)
+        });
+        let (layers, primary) = match explicit {
+            Some(path) => {
+                let layers = Self::load_layers(&path)?;
+                let primary = layers.last().map(|l| l.path.clone());
+                (layers, primary)
             }
+            None => (search_layers(&Self::config_levels())?, None),
+        };
+        let (baselines, errors) = available_baselines(&baseline_dirs());
+        for (path, e) in errors {
+            eprintln!("Warning: Skipping baseline {}: {}", path.display(), e);
         }
+        let mut stack = ConfigStack::new(
+            layers,
+            env_layer(std::env::vars()),
+            &baselines,
+            &Dmi::read(),
+        )?;
+        stack.primary = primary;
+        Ok(stack)
+    }
 
-        // No config file found - use defaults
-        Ok(Config::default())
+    /// Build a config from a stack of layers.
+    pub fn from_stack(stack: &ConfigStack) -> Result<Self, ConfigError> {
+        let mut config = Self::from_layers(&stack.layers)?;
+        // Label edits never go to the baseline or the environment
+        config.sources = stack.layers[stack.baseline_layers..stack.layers.len() - stack.env_layers]
+            .iter()
+            .rev()
+            .map(|l| l.path.clone())
+            .collect();
+        config.primary = stack.primary.clone();
+        Ok(config)
     }
 
     /// Apply default labels from a USB topology.
     ///
     /// This fills in any missing labels based on the detected topology.
-    /// Existing labels from config files take priority.
+    /// Existing labels from config files take priority. Port and product
+    /// defaults are kept out of the config tables and only label devices
+    /// that no configured entry does.
     pub fn apply_defaults_from_topology(&mut self, topology: &UsbTopology) {
+        self.resolve_topology(topology);
+        self.default_ports.clear();
+        self.default_products.clear();
+
         // Add controller labels for any not already configured
         for controller in topology.controllers.values() {
             self.controllers
@@ -173,9 +495,10 @@ impl Config {
         // Add bus labels for any not already configured
         // Keep labels simple - the app shows speed info alongside the label
         for bus in topology.buses.values() {
-            self.buses
-                .entry(bus.bus_num.to_string())
-                .or_insert_with(|| format!("Bus {}", bus.bus_num));
+            if self.bus_label(bus.bus_num).is_none() {
+                let key = self.bus_key(bus.bus_num);
+                self.buses.insert(key, format!("Bus {}", bus.bus_num));
+            }
         }
 
         // Add physical port labels for any not already configured
@@ -239,7 +562,7 @@ impl Config {
                             format!("{} USB Port", label_parts.join(" "))
                         };
 
-                        self.physical_ports.push(PhysicalPortLabel {
+                        self.default_ports.push(PhysicalPortLabel {
                             panel: if loc.panel.is_empty() {
                                 None
                             } else {
@@ -263,14 +586,28 @@ impl Config {
             }
         }
 
-        // Add product labels for devices not already configured
+        // Add product labels for devices not already configured, leaving
+        // products labelled by [[label_rules]] to the rules
+        let ruled: std::collections::HashSet<String> = topology
+            .buses
+            .values()
+            .flat_map(|bus| bus.devices.values())
+            .filter(|device| self.rule_labels.contains_key(&device.path.0))
+            .map(|device| format!("{:04x}:{:04x}", device.vendor_id, device.product_id))
+            .collect();
         for bus in topology.buses.values() {
             for device in bus.devices.values() {
                 if device.is_hub {
                     continue;
                 }
                 let key = format!("{:04x}:{:04x}", device.vendor_id, device.product_id);
-                self.products.entry(key).or_insert_with(|| {
+                if ruled.contains(&key) {
+                    continue;
+                }
+                if self.products.contains_key(&key) {
+                    continue;
+                }
+                self.default_products.entry(key).or_insert_with(|| {
                     device
                         .product
                         .clone()
@@ -287,63 +624,121 @@ impl Config {
     /// the inherited files are loaded first and merged, with the current file's
     /// values taking priority.
     pub fn load_from_path(path: &Path) -> Result<Self, ConfigError> {
-        let mut seen = HashSet::new();
-        let merged = read_and_flatten_toml(path, &mut seen)?;
-        let config: Config = merged.try_into()?;
+        Self::from_layers(&Self::load_layers(path)?)
+    }
+
+    /// Read a config file and the files it inherits, lowest priority first.
+    pub fn load_layers(path: &Path) -> Result<Vec<ConfigLayer>, ConfigError> {
+        let mut layers = Vec::new();
+        collect_layers(path, &mut Vec::new(), &mut layers)?;
+        Ok(layers)
+    }
+
+    /// Build a config by merging layers in order.
+    pub fn from_layers(layers: &[ConfigLayer]) -> Result<Self, ConfigError> {
+        let mut config: Config = merge_layers(layers).try_into()?;
+        config.sources = layers.iter().rev().map(|l| l.path.clone()).collect();
+        config.primary = config.sources.first().cloned();
         Ok(config)
     }
 
-    /// Get list of possible config paths.
-    fn config_paths() -> Vec<Option<PathBuf>> {
-        vec![
-            std::env::current_dir().ok().map(|p| p.join("usbbw.toml")),
-            dirs::config_dir().map(|p| p.join("usbbw").join("config.toml")),
-            Some(PathBuf::from("/etc/usbbw.toml")),
-        ]
-    }
-
-    /// Get label for a device, checking all sources in priority order:
-    /// 1. Product with serial (VID:PID:iSerial) - specific device
-    /// 2. Product without serial (VID:PID) - all devices of this type
-    /// 3. Physical location match
-    /// 4. Explicit device path label (legacy)
-    pub fn device_label(
-        &self,
-        path: &str,
-        vendor_id: u16,
-        product_id: u16,
-        serial: Option<&str>,
-        physical_location: Option<&PhysicalLocation>,
-    ) -> Option<String> {
-        // Priority 1: Product with serial (VID:PID:iSerial)
-        if let Some(serial) = serial {
-            let key_with_serial = format!("{:04x}:{:04x}:{}", vendor_id, product_id, serial);
-            if let Some(label) = self.products.get(&key_with_serial) {
-                return Some(label.clone());
-            }
-        }
+    /// Per-user config file, used for writing when no config was loaded.
+    pub fn user_config_path() -> PathBuf {
+        dirs::config_dir()
+            .map(|p| p.join("usbbw").join("config.toml"))
+            .unwrap_or_else(|| PathBuf::from("usbbw.toml"))
+    }
 
-        // Priority 2: Product without serial (VID:PID)
-        let product_key = format!("{:04x}:{:04x}", vendor_id, product_id);
-        if let Some(label) = self.products.get(&product_key) {
-            return Some(label.clone());
+    /// File that label edits are written to.
+    ///
+    /// Defaults to the explicitly loaded config file, else the user config:
+    /// never a system-wide file or a project file that may be committed.
+    /// An explicit target must be a file the config was read from, so the
+    /// edit is picked up on the next load.
+    pub fn write_target(&self, explicit: Option<&Path>) -> Result<PathBuf, ConfigError> {
+        let Some(path) = explicit else {
+            return Ok(self.primary.clone().unwrap_or_else(Self::user_config_path));
+        };
+        if self.sources.is_empty() {
+            return Ok(path.to_path_buf());
         }
+        path.canonicalize()
+            .ok()
+            .filter(|canonical| self.sources.contains(canonical))
+            .ok_or_else(|| {
+                ConfigError::Write(format!(
+                    "{} is not part of the loaded config ({})",
+                    path.display(),
+                    self.sources
+                        .iter()
+                        .map(|p| p.display().to_string())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ))
+            })
+    }
 
-        // Priority 3: Physical location match
-        if let Some(loc) = physical_location {
-            for port_label in &self.physical_ports {
-                if Self::matches_physical_location(port_label, loc) {
-                    return Some(port_label.label.clone());
-                }
-            }
+    /// Config search locations, lowest priority first: system, user,
+    /// project.
+    fn config_levels() -> Vec<ConfigLevel> {
+        let mut levels = vec![ConfigLevel {
+            file: PathBuf::from("/etc/usbbw.toml"),
+            drop_ins: PathBuf::from("/etc/usbbw/conf.d"),
+        }];
+        if let Some(dir) = dirs::config_dir().map(|p| p.join("usbbw")) {
+            levels.push(ConfigLevel {
+                file: dir.join("config.toml"),
+                drop_ins: dir.join("conf.d"),
+            });
         }
-
-        // Priority 4: Explicit device path label (legacy)
-        if let Some(label) = self.devices.get(path) {
-            return Some(label.clone());
+        if let Ok(dir) = std::env::current_dir() {
+            levels.push(ConfigLevel {
+                file: dir.join("usbbw.toml"),
+                drop_ins: dir.join("usbbw.d"),
+            });
         }
+        levels
+    }
 
-        None
+    /// Get label for a device: the first candidate with a label (see
+    /// [`Config::device_candidates`]).
+    pub fn device_label(&self, device: &UsbDevice) -> Option<String> {
+        self.device_candidates(device)
+            .into_iter()
+            .find_map(|candidate| candidate.label)
+    }
+
+    /// Physical location of the port a device is plugged into: its own, or
+    /// for root-level devices the root port's (as of the last
+    /// [`Config::resolve_topology`]).
+    pub fn device_location(&self, device: &UsbDevice) -> Option<PhysicalLocation> {
+        device
+            .physical_location
+            .clone()
+            .or_else(|| self.port_locations.get(&device.path.0).cloned())
+    }
+
+    /// Unit system for bandwidth values.
+    pub fn units(&self) -> Units {
+        self.settings
+            .units
+            .unwrap_or_else(|| Units::from_use_bits(self.settings.use_bits))
+    }
+
+    /// Get the display name for a device: its configured label, falling back
+    /// to the product name.
+    pub fn device_name(&self, device: &UsbDevice) -> String {
+        self.device_label(device)
+            .unwrap_or_else(|| device.display_name())
+    }
+
+    /// Get the label of the physical port matching an ACPI location.
+    pub fn physical_port_label(&self, loc: &PhysicalLocation) -> Option<String> {
+        self.physical_ports
+            .iter()
+            .chain(&self.default_ports)
+            .find(|port_label| port_label.matches(loc))
+            .map(|port_label| port_label.label.clone())
     }
 
     /// Get label for a controller.
@@ -351,45 +746,106 @@ impl Config {
         self.controllers.get(pci_address).cloned()
     }
 
+    /// Record the topology-dependent parts of labelling: which stable
+    /// `[buses]` keys identify each bus, so labels keyed by controller
+    /// follow buses across enumeration order changes, which
+    /// `[[label_rules]]` match each device, and root port locations. Call
+    /// again whenever the topology is re-read.
+    pub fn resolve_topology(&mut self, topology: &UsbTopology) {
+        self.bus_keys = topology
+            .buses
+            .keys()
+            .map(|&bus_num| (bus_num, topology.bus_keys(bus_num)))
+            .collect();
+        self.rule_labels = resolve_rule_labels(&self.label_rules, topology);
+        self.port_locations = topology
+            .buses
+            .values()
+            .flat_map(|bus| bus.devices.values())
+            .filter(|device| device.physical_location.is_none())
+            .filter_map(|device| {
+                topology
+                    .device_port_location(device)
+                    .map(|loc| (device.path.0.clone(), loc))
+            })
+            .collect();
+    }
+
+    /// `[[label_rules]]` labels of a device path on the last resolved
+    /// topology (see [`Config::resolve_topology`]).
+    pub(crate) fn rule_labels(&self, path: &str) -> &[(usize, String)] {
+        self.rule_labels.get(path).map_or(&[], Vec::as_slice)
+    }
+
+    /// Auto-detected name of a VID:PID product with no `[products]` entry
+    /// (see [`Config::apply_defaults_from_topology`]).
+    pub(crate) fn default_product(&self, key: &str) -> Option<&str> {
+        self.default_products.get(key).map(String::as_str)
+    }
+
+    /// Auto-detected label of a port with no `[[physical_ports]]` entry
+    /// (see [`Config::apply_defaults_from_topology`]).
+    pub(crate) fn default_port_label(&self, loc: &PhysicalLocation) -> Option<&str> {
+        self.default_ports
+            .iter()
+            .find(|port_label| port_label.matches(loc))
+            .map(|port_label| port_label.label.as_str())
+    }
+
+    /// `[buses]` keys that can label a bus, highest priority first: stable
+    /// keys (see [`Config::resolve_topology`]), then the bus number.
+    fn bus_key_candidates(&self, bus_num: u8) -> Vec<String> {
+        let mut keys = self.bus_keys.get(&bus_num).cloned().unwrap_or_default();
+        keys.push(bus_num.to_string());
+        keys
+    }
+
+    /// Whether `bus` refers to bus `bus_num` (stable keys as of the last
+    /// [`Config::resolve_topology`]).
+    pub fn bus_matches(&self, bus_num: u8, bus: &BusRef) -> bool {
+        match bus {
+            BusRef::Number(num) => *num == bus_num,
+            BusRef::Key(key) => self.bus_key_candidates(bus_num).contains(key),
+        }
+    }
+
     /// Get label for a bus.
     pub fn bus_label(&self, bus_num: u8) -> Option<String> {
-        self.buses.get(&bus_num.to_string()).cloned()
-    }
-
-    /// Check if physical location matches a label config.
-    fn matches_physical_location(label: &PhysicalPortLabel, loc: &PhysicalLocation) -> bool {
-        let panel_matches = label
-            .panel
-            .as_ref()
-            .map(|p| p == &loc.panel)
-            .unwrap_or(true);
-        let h_pos_matches = label
-            .horizontal_position
-            .as_ref()
-            .map(|h| h == &loc.horizontal_position)
-            .unwrap_or(true);
-        let v_pos_matches = label
-            .vertical_position
-            .as_ref()
-            .map(|v| v == &loc.vertical_position)
-            .unwrap_or(true);
-        let dock_matches = label.dock.map(|d| d == loc.dock).unwrap_or(true);
-
-        panel_matches && h_pos_matches && v_pos_matches && dock_matches
-    }
-
-    /// Check if a device path should be hidden in mermaid output.
+        self.bus_key_candidates(bus_num)
+            .iter()
+            .find_map(|key| self.buses.get(key))
+            .cloned()
+    }
+
+    /// `[buses]` key to store a bus label under: the key currently labelling
+    /// the bus, else the controller PCI address and role.
+    pub fn bus_key(&self, bus_num: u8) -> String {
+        let keys = self.bus_key_candidates(bus_num);
+        keys.iter()
+            .find(|key| self.buses.contains_key(*key))
+            .unwrap_or(&keys[0])
+            .clone()
+    }
+
+    /// Check if a device path is hidden in diagrams (it or an upstream hub
+    /// is listed in `hide_paths`).
     pub fn should_hide_path(&self, path: &str) -> bool {
-        self.mermaid.hide_paths.contains(&path.to_string())
+        self.mermaid
+            .hide_paths
+            .iter()
+            .any(|hidden| path == hidden || path.starts_with(&format!("{}.", hidden)))
     }
 
-    /// Check if a vendor should be filtered in mermaid output.
+    /// Check if a vendor passes the diagram vendor filter.
     pub fn should_show_vendor(&self, vendor_id: u16) -> bool {
         if self.mermaid.filter_vendors.is_empty() {
             return true;
         }
         let vendor_str = format!("{:04x}", vendor_id);
-        self.mermaid.filter_vendors.contains(&vendor_str)
+        self.mermaid
+            .filter_vendors
+            .iter()
+            .any(|v| v.trim_start_matches("0x").eq_ignore_ascii_case(&vendor_str))
     }
 }
 
@@ -397,7 +853,178 @@ impl Config {
 // TOML Inheritance Support
 // =============================================================================
 
-/// Read a TOML file and flatten any inheritance.
+/// One config file, with its `inherit` key removed.
+#[derive(Debug, Clone)]
+pub struct ConfigLayer {
+    /// Canonical path of the file.
+    pub path: PathBuf,
+    /// Parsed contents.
+    pub value: toml::Value,
+}
+
+/// Config layers with the hardware baseline applied underneath.
+#[derive(Debug, Clone)]
+pub struct ConfigStack {
+    /// Baseline layers, then the config file and its inherits; lowest
+    /// priority first.
+    pub layers: Vec<ConfigLayer>,
+    /// Baseline applied underneath the config file, if any.
+    pub baseline: Option<Baseline>,
+    /// Number of leading layers that came from the baseline.
+    pub baseline_layers: usize,
+    /// Number of trailing layers that came from environment variables.
+    pub env_layers: usize,
+    /// Explicitly loaded config file (`None` when searching).
+    pub primary: Option<PathBuf>,
+}
+
+impl ConfigStack {
+    /// Put the baseline selected by `settings.baseline` under `layers`
+    /// and the environment overrides on top.
+    ///
+    /// Nothing is added when the config already inherits a baseline, so
+    /// existing `inherit` setups keep working unchanged.
+    pub fn new(
+        mut layers: Vec<ConfigLayer>,
+        env: Option<ConfigLayer>,
+        baselines: &[Baseline],
+        dmi: &Dmi,
+    ) -> Result<Self, ConfigError> {
+        let env_layers = usize::from(env.is_some());
+        layers.extend(env);
+        let merged = merge_layers(&layers);
+        let selection = merged
+            .get("settings")
+            .and_then(|s| s.get("baseline"))
+            .and_then(|b| b.as_str())
+            .unwrap_or("auto");
+        let inherits_baseline = layers.iter().any(|l| l.value.get("baseline").is_some());
+
+        let baseline = match selection {
+            _ if inherits_baseline => None,
+            "none" => None,
+            "auto" => select_baseline(baselines, dmi),
+            name => Some(baselines.iter().find(|b| b.id == name).ok_or_else(|| {
+                ConfigError::Inheritance(format!(
+                    "unknown baseline \"{}\" in settings.baseline",
+                    name
+                ))
+            })?),
+        };
+        let Some(baseline) = baseline else {
+            return Ok(Self {
+                layers,
+                baseline: None,
+                baseline_layers: 0,
+                env_layers,
+                primary: None,
+            });
+        };
+
+        let mut stack = baseline.layer()?;
+        let baseline_layers = stack.len();
+        stack.extend(layers);
+        Ok(Self {
+            layers: stack,
+            baseline: Some(baseline.clone()),
+            baseline_layers,
+            env_layers,
+            primary: None,
+        })
+    }
+}
+
+/// A config search location: a main file plus a drop-in directory whose
+/// `*.toml` files are applied on top of it in name order.
+#[derive(Debug, Clone)]
+pub struct ConfigLevel {
+    pub file: PathBuf,
+    pub drop_ins: PathBuf,
+}
+
+/// Read every file of every level, lowest priority first. A file inherited
+/// from several places is only applied once, at its first (lowest) position.
+pub fn search_layers(levels: &[ConfigLevel]) -> Result<Vec<ConfigLayer>, ConfigError> {
+    let mut layers: Vec<ConfigLayer> = Vec::new();
+    for level in levels {
+        let mut files = Vec::new();
+        if level.file.exists() {
+            files.push(level.file.clone());
+        }
+        if let Ok(entries) = std::fs::read_dir(&level.drop_ins) {
+            let mut drop_ins: Vec<PathBuf> = entries
+                .flatten()
+                .map(|e| e.path())
+                .filter(|p| p.is_file() && p.extension().is_some_and(|ext| ext == "toml"))
+                .collect();
+            drop_ins.sort();
+            files.extend(drop_ins);
+        }
+
+        for file in &files {
+            for layer in Config::load_layers(file)? {
+                if !layers.iter().any(|l| l.path == layer.path) {
+                    layers.push(layer);
+                }
+            }
+        }
+    }
+    Ok(layers)
+}
+
+/// Settings that `USBBW_<NAME>` environment variables override. Other
+/// `USBBW_*` variables (such as those passed to alert hooks) are ignored.
+const ENV_SETTINGS: &[&str] = &[
+    "refresh_ms",
+    "theme",
+    "use_bits",
+    "units",
+    "high_usage_percent",
+    "critical_usage_percent",
+    "mouse",
+    "baseline",
+];
+
+/// Layer holding `[settings]` overrides from `USBBW_*` environment
+/// variables. Values are read as TOML (`true`, `500`) and fall back to
+/// plain strings (`dark`).
+pub fn env_layer(vars: impl IntoIterator<Item = (String, String)>) -> Option<ConfigLayer> {
+    let mut settings = toml::Table::new();
+    for (name, raw) in vars {
+        let Some(key) = name.strip_prefix("USBBW_").map(str::to_lowercase) else {
+            continue;
+        };
+        if !ENV_SETTINGS.contains(&key.as_str()) {
+            continue;
+        }
+        let value = toml::from_str::<toml::Table>(&format!("v = {}", raw))
+            .ok()
+            .and_then(|mut t| t.remove("v"))
+            .unwrap_or(toml::Value::String(raw));
+        settings.insert(key, value);
+    }
+    if settings.is_empty() {
+        return None;
+    }
+    let mut table = toml::Table::new();
+    table.insert("settings".to_string(), toml::Value::Table(settings));
+    Some(ConfigLayer {
+        path: PathBuf::from("environment"),
+        value: toml::Value::Table(table),
+    })
+}
+
+/// Merge layers in order, later ones taking priority.
+pub fn merge_layers(layers: &[ConfigLayer]) -> toml::Value {
+    layers
+        .iter()
+        .map(|layer| layer.value.clone())
+        .reduce(merge_toml_values)
+        .unwrap_or_else(|| toml::Value::Table(toml::Table::new()))
+}
+
+/// Read a TOML file and the files it inherits into `layers`, inherited
+/// files first.
 ///
 /// If the file contains an `inherit` key, the inherited files are loaded first
 /// and merged. The `inherit` key can be:
@@ -405,21 +1032,23 @@ impl Config {
 /// - An array of strings: multiple files to inherit from (applied in order)
 ///
 /// Paths in `inherit` are relative to the directory containing the config file.
-fn read_and_flatten_toml(
+fn collect_layers(
     path: &Path,
-    seen: &mut HashSet<PathBuf>,
-) -> Result<toml::Value, ConfigError> {
+    seen: &mut Vec<PathBuf>,
+    layers: &mut Vec<ConfigLayer>,
+) -> Result<(), ConfigError> {
     // Prevent circular inheritance
     let canonical = path.canonicalize().map_err(|e| {
         ConfigError::Inheritance(format!("cannot resolve {}: {}", path.display(), e))
     })?;
 
-    if !seen.insert(canonical.clone()) {
+    if seen.contains(&canonical) {
         return Err(ConfigError::Inheritance(format!(
             "{} is inherited more than once; circular dependencies are not allowed",
             path.display()
         )));
     }
+    seen.push(canonical.clone());
 
     // Read and parse the file
     let content = std::fs::read_to_string(path)?;
@@ -432,18 +1061,14 @@ fn read_and_flatten_toml(
         None
     };
 
-    let Some(inherit) = inherit else {
-        // No inheritance, return as-is
-        return Ok(doc);
-    };
-
     // Get the directory containing this config file for resolving relative paths
     let base_dir = path.parent().unwrap_or(Path::new("."));
 
     // Collect inherited files
     let inherited_paths: Vec<PathBuf> = match inherit {
-        toml::Value::String(s) => vec![base_dir.join(&s)],
-        toml::Value::Array(arr) => {
+        None => Vec::new(),
+        Some(toml::Value::String(s)) => vec![base_dir.join(&s)],
+        Some(toml::Value::Array(arr)) => {
             let mut paths = Vec::new();
             for item in arr {
                 if let toml::Value::String(s) = item {
@@ -456,30 +1081,22 @@ fn read_and_flatten_toml(
             }
             paths
         }
-        _ => {
+        Some(_) => {
             return Err(ConfigError::Inheritance(
                 "inherit must be a string or array of strings".to_string(),
             ));
         }
     };
 
-    // Load and merge inherited files
-    let mut merged: Option<toml::Value> = None;
+    // Inherited files first, then the current file on top
     for inherited_path in inherited_paths {
-        let inherited = read_and_flatten_toml(&inherited_path, seen)?;
-        merged = Some(match merged {
-            Some(base) => merge_toml_values(base, inherited),
-            None => inherited,
-        });
+        collect_layers(&inherited_path, seen, layers)?;
     }
-
-    // Merge current file on top of inherited
-    let result = match merged {
-        Some(base) => merge_toml_values(base, doc),
-        None => doc,
-    };
-
-    Ok(result)
+    layers.push(ConfigLayer {
+        path: canonical,
+        value: doc,
+    });
+    Ok(())
 }
 
 /// Deep-merge two TOML values.
@@ -487,7 +1104,7 @@ fn read_and_flatten_toml(
 /// - Tables are merged recursively (later values override earlier)
 /// - Arrays are concatenated
 /// - Other values are replaced by the later value
-fn merge_toml_values(base: toml::Value, overlay: toml::Value) -> toml::Value {
+pub(crate) fn merge_toml_values(base: toml::Value, overlay: toml::Value) -> toml::Value {
     match (base, overlay) {
         // Both are tables: merge recursively
         (toml::Value::Table(mut base_table), toml::Value::Table(overlay_table)) => {
@@ -513,26 +1130,46 @@ fn merge_toml_values(base: toml::Value, overlay: toml::Value) -> toml::Value {
 
 /// Generate example configuration content.
 pub fn example_config() -> &'static str {
-    r#"# usbbw configuration file
+    r##"# usbbw configuration file
 # Place in ./usbbw.toml, ~/.config/usbbw/config.toml, or /etc/usbbw.toml
 
 [settings]
 # Refresh interval in milliseconds (for TUI mode)
 refresh_ms = 1000
-# Color theme: "dark" or "light"
+# Color theme: "dark", "light", "high-contrast" or "monochrome" (NO_COLOR forces monochrome)
 theme = "dark"
 # Show bandwidth in bits per second (true) or bytes (false)
 use_bits = true
+# Or pick a unit system: "bits", "bits-iec", "bytes", "bytes-iec", "bus-time"
+# units = "bus-time"
+# Periodic bandwidth usage (percent) shown as high / critical
+high_usage_percent = 80
+critical_usage_percent = 95
+# Mouse support in the TUI (set false to keep the terminal's own text selection)
+mouse = true
+# Hardware baseline applied underneath this file: "auto" (matched by DMI
+# board identity, see `usbbw baselines list`), "none", or a baseline name
+baseline = "auto"
+
+# TUI colour overrides (names, "#rrggbb" or 256-colour indices)
+[theme]
+# accent = "cyan"
+# highlight = "#ffaa00"
+# selection_bg = "236"
 
 # Controller labels (by PCI address)
 [controllers]
 # "0000:c1:00.4" = "AMD Integrated USB"
 # "0000:c3:00.0" = "Thunderbolt USB"
 
-# Bus labels (by bus number, use quoted string keys)
+# Bus labels, keyed by controller PCI address and role ("usb2" or "usb3"),
+# by controller firmware path and role, or by bus number. Bus numbers can
+# change between boots; the controller keys do not.
 [buses]
-# "1" = "Internal USB 2.0"
-# "2" = "Internal USB 3.1"
+# "0000:c1:00.4/usb2" = "Internal USB 2.0"
+# "0000:c1:00.4/usb3" = "Internal USB 3.1"
+# "\\_SB_.PCI0.GP17.XHC0/usb2" = "Internal USB 2.0"
+# "1" = "Bus 1"
 
 # Device path labels
 # Format: "bus-port.port.port" = "label"
@@ -553,7 +1190,23 @@ use_bits = true
 # "0d28:0204" = "DAPLink Debug Probe"
 # "046d:c52b" = "Logitech Unifying Receiver"
 
-# Mermaid diagram output settings
+# Label rules: one label for every matching device, below exact [products]
+# entries. Matchers (all optional, all must match): vendor_id / product_id
+# globs, product / manufacturer regexes, serial_prefix, interface_class /
+# interface_subclass / interface_protocol, parent (hub VID:PID or path glob).
+# Template fields: {vid} {pid} {product} {manufacturer} {serial} {path};
+# {serial:-4} keeps the last 4 characters, {serial:4} the first 4.
+# [[label_rules]]
+# label = "{product} #{serial:-4}"
+# product = "(?i)cmsis-dap"
+# priority = 10
+#
+# [[label_rules]]
+# label = "FTDI cable {serial}"
+# vendor_id = "0403"
+# product_id = "60*"
+
+# Diagram view filters (Mermaid, markdown, DOT, SVG/HTML)
 [mermaid]
 # Device paths to hide from diagrams
 hide_paths = []
@@ -561,7 +1214,40 @@ hide_paths = []
 filter_vendors = []
 # Collapse hubs with single child
 collapse_single_child_hubs = false
-"#
+# Maximum device depth below the root hub (1 = root port devices only)
+# max_depth = 2
+
+# Expectations checked by `usbbw check` (exits non-zero on failure)
+[expect]
+# Fail if any device failed to configure
+no_unconfigured = false
+# Device must be present; port/path/controller/speed are optional constraints
+# [[expect.devices]]
+# device = "0d28:0204:ABC123"
+# port = "Left USB-C Port"
+# controller = "0000:c3:00.0"
+# speed = "high"
+#
+# [[expect.buses]]
+# bus = "0000:c3:00.0/usb2"   # a [buses] key or bus number
+# max_usage_percent = 60
+
+# Alert rules (evaluated by the TUI and `usbbw daemon` on every refresh)
+# Conditions (`when`): bus_usage, unconfigured, over_current, disappeared
+# hook: shell command, run with USBBW_* environment variables
+# notify: terminal bell + OSC 9 desktop notification in the TUI
+# [[alerts]]
+# name = "Bus nearly full"
+# when = "bus_usage"
+# above_percent = 90
+# bus = "0000:c3:00.0/usb3"   # optional: a [buses] key or bus number
+# notify = true
+#
+# [[alerts]]
+# when = "disappeared"
+# device = "0d28:0204:ABC123"
+# hook = "notify-send \"usbbw\" \"$USBBW_MESSAGE\""
+"##
 }
 
 use crate::model::UsbTopology;
@@ -630,13 +1316,14 @@ pub fn generate_config(topology: &UsbTopology) -> String {
     output.push_str(
         "# =============================================================================\n",
     );
-    output.push_str("# Bus Labels (by bus number)\n");
+    output.push_str("# Bus Labels (by controller and role)\n");
     output.push_str(
         "# =============================================================================\n",
     );
     output.push_str(
-        "# Odd numbers are typically USB 2.0, even numbers USB 3.x on xHCI controllers\n\n",
+        "# Keys are \"<controller PCI address>/usb2|usb3\" so labels survive bus numbers\n",
     );
+    output.push_str("# changing between boots; the firmware path of the controller works too.\n\n");
     output.push_str("[buses]\n");
 
     for bus in topology.buses_sorted() {
@@ -645,14 +1332,23 @@ pub fn generate_config(topology: &UsbTopology) -> String {
         } else {
             "USB 2.0"
         };
+        let keys = topology.bus_keys(bus.bus_num);
+        let key = keys
+            .first()
+            .cloned()
+            .unwrap_or_else(|| bus.bus_num.to_string());
         // Use simple labels; speed is shown by the app anyway
         output.push_str(&format!(
-            "\"{}\" = \"Bus {}\"  # {} {}\n",
+            "\"{}\" = \"Bus {}\"  # bus {}, {} {}\n",
+            sanitize_toml_string(&key),
             bus.bus_num,
             bus.bus_num,
             speed_name,
             bus.speed.short_name()
         ));
+        if let Some(alternative) = keys.get(1) {
+            output.push_str(&format!("# or \"{}\"\n", sanitize_toml_string(alternative)));
+        }
     }
     output.push('\n');
 
@@ -781,7 +1477,7 @@ pub fn generate_config(topology: &UsbTopology) -> String {
     }
 
     let mut products: Vec<_> = products.into_iter().collect();
-    products.sort_by(|a, b| a.0.cmp(&b.0));
+    products.sort_by_key(|a| a.0);
 
     for ((vid, pid), name) in &products {
         output.push_str(&format!(
@@ -861,3 +1557,94 @@ fn sanitize_toml_string(s: &str) -> String {
         .replace('\r', "\\r")
         .replace('\t', "\\t")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_search_layers() {
+        let dir = std::env::temp_dir().join(format!("usbbw-levels-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let write = |path: &str, content: &str| {
+            let path = dir.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        };
+        write(
+            "etc/usbbw.toml",
+            "[controllers]\n\"0000:c1:00.4\" = \"Internal\"\n[products]\n\"0d28:0204\" = \"Probe\"\n",
+        );
+        write("etc/conf.d/10-rig.toml", "[settings]\nrefresh_ms = 2000\n");
+        write("etc/conf.d/notes.txt", "ignored");
+        write(
+            "home/conf.d/20-me.toml",
+            "[products]\n\"0d28:0204\" = \"My probe\"\n",
+        );
+        write("project/usbbw.toml", "[settings]\ntheme = \"light\"\n");
+        let level = |file: &str, drop_ins: &str| ConfigLevel {
+            file: dir.join(file),
+            drop_ins: dir.join(drop_ins),
+        };
+        let levels = [
+            level("etc/usbbw.toml", "etc/conf.d"),
+            level("home/config.toml", "home/conf.d"),
+            level("project/usbbw.toml", "project/usbbw.d"),
+        ];
+
+        let layers = search_layers(&levels).unwrap();
+        assert_eq!(layers.len(), 4);
+        let env = env_layer([
+            ("USBBW_REFRESH_MS".to_string(), "250".to_string()),
+            ("USBBW_MOUSE".to_string(), "false".to_string()),
+            (
+                "USBBW_MESSAGE".to_string(),
+                "from an alert hook".to_string(),
+            ),
+        ]);
+        let stack = ConfigStack::new(layers, env, &[], &Dmi::default()).unwrap();
+        let config = Config::from_stack(&stack).unwrap();
+
+        assert_eq!(
+            config.controller_label("0000:c1:00.4").as_deref(),
+            Some("Internal")
+        );
+        assert_eq!(
+            config.products.get("0d28:0204").map(String::as_str),
+            Some("My probe")
+        );
+        assert_eq!(config.settings.theme, "light");
+        assert_eq!(config.settings.refresh_ms, 250);
+        assert!(!config.settings.mouse);
+        assert_eq!(config.sources.len(), 4);
+        // Never the system or project file
+        assert_eq!(
+            config.write_target(None).unwrap(),
+            Config::user_config_path()
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_write_target_in_inherit_chain() {
+        let dir = std::env::temp_dir().join(format!("usbbw-target-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("team.toml"), "[buses]\n\"1\" = \"Internal\"\n").unwrap();
+        std::fs::write(dir.join("me.toml"), "inherit = \"team.toml\"\n").unwrap();
+        std::fs::write(dir.join("other.toml"), "").unwrap();
+
+        let config = Config::load_from_path(&dir.join("me.toml")).unwrap();
+        let me = dir.join("me.toml").canonicalize().unwrap();
+        let team = dir.join("team.toml").canonicalize().unwrap();
+        assert_eq!(config.sources, vec![me.clone(), team.clone()]);
+        assert_eq!(config.write_target(None).unwrap(), me);
+        assert_eq!(
+            config.write_target(Some(&dir.join("team.toml"))).unwrap(),
+            team
+        );
+        assert!(config.write_target(Some(&dir.join("other.toml"))).is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
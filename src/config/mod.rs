@@ -1,8 +1,24 @@
 //! Configuration loading and management.
 
+mod baseline;
+mod inspect;
+mod label;
 mod loader;
+mod rules;
+mod writer;
 
+pub use baseline::{
+    Baseline, BaselineInfo, BaselineSource, Dmi, DmiMatch, available_baselines, baseline_dirs,
+    select_baseline,
+};
+pub use inspect::{
+    Finding, Severity, explain_label, render_effective, shadowed_labels, source_of, validate,
+};
+pub use label::{LabelCandidate, LabelTarget};
 pub use loader::{
-    Config, ConfigError, MermaidConfig, PhysicalPortLabel, PositionLabels, Settings,
-    example_config, generate_config,
+    AlertCondition, AlertRule, BusRef, Config, ConfigError, ConfigLayer, ConfigLevel, ConfigStack,
+    ExpectBus, ExpectConfig, ExpectDevice, MermaidConfig, PhysicalPortLabel, PositionLabels,
+    Settings, ThemeColors, env_layer, example_config, generate_config, merge_layers, search_layers,
 };
+pub use rules::{LabelRule, render_template};
+pub use writer::ConfigEditor;
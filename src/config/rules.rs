@@ -0,0 +1,437 @@
+//! `[[label_rules]]`: labels for every device matching a pattern, such as
+//! all CMSIS-DAP probes or all FTDI cables.
+
+use crate::model::{UsbDevice, UsbTopology};
+use regex::Regex;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// A pattern-based device label. Every matcher that is set must match.
+#[derive(Debug, Clone, Deserialize)]
+pub struct LabelRule {
+    /// Label template, e.g. `"{product} #{serial:-4}"`. Placeholders:
+    /// `vid`, `pid`, `product`, `manufacturer`, `serial`, `path`; `{x:N}`
+    /// keeps the first N characters and `{x:-N}` the last N. A rule whose
+    /// template uses a value the device doesn't report does not apply.
+    pub label: String,
+
+    /// Rules with a higher priority are tried first (default 0; ties go to
+    /// the earlier rule).
+    #[serde(default)]
+    pub priority: i32,
+
+    /// Glob over the vendor ID as 4 lowercase hex digits (e.g. "0403").
+    #[serde(default)]
+    pub vendor_id: Option<String>,
+
+    /// Glob over the product ID as 4 lowercase hex digits (e.g. "60*").
+    #[serde(default)]
+    pub product_id: Option<String>,
+
+    /// Regex searched in the product string.
+    #[serde(default)]
+    pub product: Option<String>,
+
+    /// Regex searched in the manufacturer string.
+    #[serde(default)]
+    pub manufacturer: Option<String>,
+
+    /// Required start of the serial number.
+    #[serde(default)]
+    pub serial_prefix: Option<String>,
+
+    /// Some interface must have this class...
+    #[serde(default)]
+    pub interface_class: Option<u8>,
+
+    /// ...and subclass...
+    #[serde(default)]
+    pub interface_subclass: Option<u8>,
+
+    /// ...and protocol.
+    #[serde(default)]
+    pub interface_protocol: Option<u8>,
+
+    /// Glob over the upstream hub's VID:PID or path (e.g. "2109:*").
+    #[serde(default)]
+    pub parent: Option<String>,
+}
+
+/// A rule with its regexes compiled.
+struct CompiledRule<'a> {
+    rule: &'a LabelRule,
+    product: Option<Regex>,
+    manufacturer: Option<Regex>,
+}
+
+impl LabelRule {
+    fn compile(&self) -> Result<CompiledRule<'_>, String> {
+        let regex = |pattern: &Option<String>, field: &str| {
+            pattern
+                .as_deref()
+                .map(Regex::new)
+                .transpose()
+                .map_err(|e| format!("invalid {} regex: {}", field, e))
+        };
+        check_template(&self.label)?;
+        Ok(CompiledRule {
+            rule: self,
+            product: regex(&self.product, "product")?,
+            manufacturer: regex(&self.manufacturer, "manufacturer")?,
+        })
+    }
+
+    /// Why the rule can never apply (bad regex or template), if it can't.
+    pub fn problem(&self) -> Option<String> {
+        self.compile().err()
+    }
+
+    /// Whether the rule matches a device of `topology`, ignoring whether its
+    /// template can be filled in.
+    pub fn matches(&self, device: &UsbDevice, topology: &UsbTopology) -> bool {
+        let parent = device.path.parent().and_then(|p| topology.get_device(&p));
+        self.compile().is_ok_and(|c| c.matches(device, parent))
+    }
+
+    /// Short description of the matchers, e.g. `vendor_id=0d28 product=/DAP/`.
+    pub fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        let mut push = |key: &str, value: &Option<String>| {
+            if let Some(value) = value {
+                parts.push(format!("{}={}", key, value));
+            }
+        };
+        push("vendor_id", &self.vendor_id);
+        push("product_id", &self.product_id);
+        push(
+            "product",
+            &self.product.as_ref().map(|r| format!("/{}/", r)),
+        );
+        push(
+            "manufacturer",
+            &self.manufacturer.as_ref().map(|r| format!("/{}/", r)),
+        );
+        push("serial_prefix", &self.serial_prefix);
+        let hex = |v: Option<u8>| v.map(|v| format!("0x{:02x}", v));
+        push("interface_class", &hex(self.interface_class));
+        push("interface_subclass", &hex(self.interface_subclass));
+        push("interface_protocol", &hex(self.interface_protocol));
+        push("parent", &self.parent);
+        if parts.is_empty() {
+            "any device".to_string()
+        } else {
+            parts.join(" ")
+        }
+    }
+}
+
+impl CompiledRule<'_> {
+    fn matches(&self, device: &UsbDevice, parent: Option<&UsbDevice>) -> bool {
+        let rule = self.rule;
+        let glob = |pattern: &Option<String>, text: &str| {
+            pattern.as_deref().is_none_or(|p| glob_matches(p, text))
+        };
+        let regex = |re: &Option<Regex>, text: &Option<String>| {
+            re.as_ref()
+                .is_none_or(|re| text.as_deref().is_some_and(|t| re.is_match(t)))
+        };
+        let interface = rule.interface_class.is_none()
+            && rule.interface_subclass.is_none()
+            && rule.interface_protocol.is_none()
+            || device.interfaces.iter().any(|i| {
+                rule.interface_class.is_none_or(|c| c == i.class)
+                    && rule.interface_subclass.is_none_or(|s| s == i.subclass)
+                    && rule.interface_protocol.is_none_or(|p| p == i.protocol)
+            });
+        let parent_matches = rule.parent.as_deref().is_none_or(|pattern| {
+            parent.is_some_and(|p| {
+                glob_matches(pattern, &p.vid_pid()) || glob_matches(pattern, &p.path.0)
+            })
+        });
+
+        glob(&rule.vendor_id, &format!("{:04x}", device.vendor_id))
+            && glob(&rule.product_id, &format!("{:04x}", device.product_id))
+            && regex(&self.product, &device.product)
+            && regex(&self.manufacturer, &device.manufacturer)
+            && rule.serial_prefix.as_deref().is_none_or(|prefix| {
+                device
+                    .serial
+                    .as_deref()
+                    .is_some_and(|s| s.starts_with(prefix))
+            })
+            && interface
+            && parent_matches
+    }
+}
+
+/// `[[label_rules]]` labels for every device of `topology`, by device path:
+/// `(rule index, label)` for each matching rule, highest priority first.
+/// Rules with a bad regex or template are skipped.
+pub fn resolve_rule_labels(
+    rules: &[LabelRule],
+    topology: &UsbTopology,
+) -> HashMap<String, Vec<(usize, String)>> {
+    let mut compiled: Vec<(usize, CompiledRule)> = rules
+        .iter()
+        .enumerate()
+        .filter_map(|(index, rule)| rule.compile().ok().map(|c| (index, c)))
+        .collect();
+    // Stable sort keeps file order among equal priorities
+    compiled.sort_by_key(|(_, c)| std::cmp::Reverse(c.rule.priority));
+
+    let mut labels = HashMap::new();
+    if compiled.is_empty() {
+        return labels;
+    }
+    for bus in topology.buses.values() {
+        for device in bus.devices.values() {
+            let parent = device.path.parent().and_then(|p| topology.get_device(&p));
+            let matched: Vec<(usize, String)> = compiled
+                .iter()
+                .filter(|(_, c)| c.matches(device, parent))
+                .filter_map(|(index, c)| {
+                    render_template(&c.rule.label, device)
+                        .ok()
+                        .flatten()
+                        .map(|label| (*index, label))
+                })
+                .collect();
+            if !matched.is_empty() {
+                labels.insert(device.path.0.clone(), matched);
+            }
+        }
+    }
+    labels
+}
+
+/// Case-insensitive glob match supporting `*` and `?`.
+fn glob_matches(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // Position after the last `*` and the text position it is trying
+    let mut backtrack = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p + 1, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                Some((bp, bt)) => {
+                    p = bp;
+                    t = bt + 1;
+                    backtrack = Some((bp, bt + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// A piece of a parsed label template.
+enum Piece<'a> {
+    Text(&'a str),
+    /// Field name and character range (`Some(4)`: first 4, `Some(-4)`: last 4).
+    Field(&'a str, Option<i64>),
+}
+
+const TEMPLATE_FIELDS: &[&str] = &["vid", "pid", "product", "manufacturer", "serial", "path"];
+
+fn parse_template(template: &str) -> Result<Vec<Piece<'_>>, String> {
+    let mut pieces = Vec::new();
+    let mut rest = template;
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix("{{") {
+            pieces.push(Piece::Text("{"));
+            rest = after;
+        } else if let Some(after) = rest.strip_prefix("}}") {
+            pieces.push(Piece::Text("}"));
+            rest = after;
+        } else if let Some(after) = rest.strip_prefix('{') {
+            let end = after
+                .find('}')
+                .ok_or_else(|| format!("unclosed `{{` in template \"{}\"", template))?;
+            let (name, range) = match after[..end].split_once(':') {
+                Some((name, range)) => {
+                    let range = range.parse::<i64>().map_err(|_| {
+                        format!("bad range `{}` in template \"{}\"", range, template)
+                    })?;
+                    (name, Some(range))
+                }
+                None => (&after[..end], None),
+            };
+            if !TEMPLATE_FIELDS.contains(&name) {
+                return Err(format!(
+                    "unknown field `{}` in template \"{}\" (expected one of {})",
+                    name,
+                    template,
+                    TEMPLATE_FIELDS.join(", ")
+                ));
+            }
+            pieces.push(Piece::Field(name, range));
+            rest = &after[end + 1..];
+        } else if rest.starts_with('}') {
+            return Err(format!("unmatched `}}` in template \"{}\"", template));
+        } else {
+            let end = rest.find(['{', '}']).unwrap_or(rest.len());
+            pieces.push(Piece::Text(&rest[..end]));
+            rest = &rest[end..];
+        }
+    }
+    Ok(pieces)
+}
+
+fn check_template(template: &str) -> Result<(), String> {
+    parse_template(template).map(|_| ())
+}
+
+/// Fill in a label template for a device. `Ok(None)` if the device lacks a
+/// value the template uses.
+pub fn render_template(template: &str, device: &UsbDevice) -> Result<Option<String>, String> {
+    let mut out = String::new();
+    for piece in parse_template(template)? {
+        let (name, range) = match piece {
+            Piece::Text(text) => {
+                out.push_str(text);
+                continue;
+            }
+            Piece::Field(name, range) => (name, range),
+        };
+        let value = match name {
+            "vid" => Some(format!("{:04x}", device.vendor_id)),
+            "pid" => Some(format!("{:04x}", device.product_id)),
+            "product" => device.product.clone(),
+            "manufacturer" => device.manufacturer.clone(),
+            "serial" => device.serial.clone(),
+            _ => Some(device.path.0.clone()),
+        };
+        let Some(value) = value.filter(|v| !v.is_empty()) else {
+            return Ok(None);
+        };
+        let chars: Vec<char> = value.chars().collect();
+        let kept = match range {
+            None => &chars[..],
+            Some(n) if n >= 0 => &chars[..(n as usize).min(chars.len())],
+            Some(n) => &chars[chars.len().saturating_sub(n.unsigned_abs() as usize)..],
+        };
+        out.extend(kept);
+    }
+    Ok(Some(out))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::UsbInterface;
+
+    fn device(path: &str, vid: u16, pid: u16, product: &str, serial: Option<&str>) -> UsbDevice {
+        UsbDevice {
+            manufacturer: Some("ARM".to_string()),
+            product: Some(product.to_string()),
+            serial: serial.map(String::from),
+            interfaces: vec![UsbInterface {
+                number: 0,
+                alt_setting: 0,
+                class: 0xff,
+                subclass: 0x00,
+                protocol: 0x00,
+                driver: None,
+                name: None,
+            }],
+            ..UsbDevice::fixture(path, vid, pid)
+        }
+    }
+
+    fn rule(content: &str) -> LabelRule {
+        toml::from_str(content).unwrap()
+    }
+
+    #[test]
+    fn test_glob_matches() {
+        assert!(glob_matches("0d28", "0d28"));
+        assert!(glob_matches("60*", "6014"));
+        assert!(glob_matches("*:60?4", "0403:6014"));
+        assert!(glob_matches("2109:*", "2109:2817"));
+        assert!(glob_matches("*", ""));
+        assert!(!glob_matches("60*", "1060"));
+        assert!(!glob_matches("1-?", "1-10"));
+    }
+
+    #[test]
+    fn test_render_template() {
+        let probe = device(
+            "1-1.1",
+            0x0d28,
+            0x0204,
+            "DAPLink CMSIS-DAP",
+            Some("0240ABCD1234"),
+        );
+        let render = |t: &str| render_template(t, &probe).unwrap();
+        assert_eq!(render("Probe #{serial:-4}").as_deref(), Some("Probe #1234"));
+        assert_eq!(
+            render("{vid}:{pid} {serial:4}").as_deref(),
+            Some("0d28:0204 0240")
+        );
+        assert_eq!(render("{{{path}}}").as_deref(), Some("{1-1.1}"));
+
+        let no_serial = device("1-2", 0x0d28, 0x0204, "DAPLink", None);
+        assert_eq!(render_template("#{serial}", &no_serial).unwrap(), None);
+        assert!(render_template("{nope}", &probe).is_err());
+        assert!(render_template("{serial:x}", &probe).is_err());
+        assert!(render_template("{serial", &probe).is_err());
+    }
+
+    #[test]
+    fn test_resolve_rule_labels() {
+        let mut hub = device("1-1", 0x2109, 0x2817, "USB2.0 Hub", None);
+        hub.is_hub = true;
+        hub.interfaces[0].class = 0x09;
+        let probe = device(
+            "1-1.1",
+            0x0d28,
+            0x0204,
+            "DAPLink CMSIS-DAP",
+            Some("0240AB12"),
+        );
+        let ftdi = device("1-2", 0x0403, 0x6014, "C232HM-DDHSL-0", Some("FT4X9Y"));
+        let topology = UsbTopology::fixture(1, vec![hub, probe, ftdi]);
+
+        let rules = vec![
+            rule("label = \"FTDI {product}\"\nvendor_id = \"0403\"\nproduct_id = \"60*\""),
+            rule(
+                "label = \"CMSIS-DAP #{serial:-2}\"\nproduct = \"(?i)cmsis-dap\"\nparent = \"2109:*\"",
+            ),
+            rule("label = \"Vendor-specific\"\ninterface_class = 0xff\npriority = -1"),
+            rule(
+                "label = \"Dock probe\"\nproduct = \"DAP\"\nserial_prefix = \"0240\"\npriority = 5",
+            ),
+            rule("label = \"Broken\"\nproduct = \"(\""),
+        ];
+        assert!(rules[4].problem().is_some());
+        assert!(rules[0].problem().is_none());
+
+        let labels = resolve_rule_labels(&rules, &topology);
+        assert_eq!(
+            labels["1-1.1"],
+            [
+                (3, "Dock probe".to_string()),
+                (1, "CMSIS-DAP #12".to_string()),
+                (2, "Vendor-specific".to_string()),
+            ]
+        );
+        assert_eq!(
+            labels["1-2"],
+            [
+                (0, "FTDI C232HM-DDHSL-0".to_string()),
+                (2, "Vendor-specific".to_string()),
+            ]
+        );
+        assert!(!labels.contains_key("1-1"));
+    }
+}
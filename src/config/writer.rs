@@ -0,0 +1,344 @@
+//! Format-preserving config edits.
+//!
+//! Labels are written through a `toml_edit` document, so comments, key order
+//! and table style (standard, inline or dotted) survive, and existing keys are
+//! updated in place instead of duplicated.
+
+use super::label::LabelTarget;
+use super::loader::{Config, ConfigError};
+use crate::model::PhysicalLocation;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use toml_edit::{ArrayOfTables, DocumentMut, InlineTable, Item, Key, Table, TableLike, Value};
+
+/// Header for config files created by the writer.
+const NEW_FILE_HEADER: &str = "# usbbw configuration\n\n";
+
+/// An editable config file.
+#[derive(Debug)]
+pub struct ConfigEditor {
+    path: PathBuf,
+    doc: DocumentMut,
+}
+
+impl ConfigEditor {
+    /// Open a config file for editing; a missing file starts empty.
+    pub fn open(path: &Path) -> Result<Self, ConfigError> {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => NEW_FILE_HEADER.to_string(),
+            Err(e) => return Err(e.into()),
+        };
+        Self::parse(path, &content)
+    }
+
+    /// Editor over `content`, to be saved to `path`.
+    pub fn parse(path: &Path, content: &str) -> Result<Self, ConfigError> {
+        Ok(Self {
+            path: path.to_path_buf(),
+            doc: content.parse()?,
+        })
+    }
+
+    /// File the edits are saved to.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Set the label for a target, updating an existing entry in place.
+    pub fn set_label(&mut self, target: &LabelTarget, label: &str) -> Result<(), ConfigError> {
+        match target {
+            LabelTarget::PhysicalPort(loc) => self.set_physical_port(loc, label),
+            _ => {
+                let table = target.table();
+                let key = target.key();
+                let entries = self
+                    .doc
+                    .entry(table)
+                    .or_insert_with(|| Item::Table(Table::new()));
+                match entries {
+                    Item::Table(t) if !t.contains_key(&key) => {
+                        t.insert_formatted(&quoted_key(&key), toml_edit::value(label));
+                    }
+                    Item::Value(Value::InlineTable(t)) if !t.contains_key(&key) => {
+                        t.insert_formatted(&quoted_key(&key), Value::from(label));
+                        t.fmt();
+                    }
+                    _ => {
+                        let entries = entries
+                            .as_table_like_mut()
+                            .ok_or_else(|| not_a(table, "table"))?;
+                        set_string(entries, &key, label);
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Set a `[[physical_ports]]` label. New exact entries go first so they
+    /// win over wildcard ones.
+    fn set_physical_port(
+        &mut self,
+        loc: &PhysicalLocation,
+        label: &str,
+    ) -> Result<(), ConfigError> {
+        let item = self
+            .doc
+            .entry("physical_ports")
+            .or_insert_with(|| Item::ArrayOfTables(ArrayOfTables::new()));
+
+        match item {
+            Item::ArrayOfTables(ports) => {
+                if let Some(existing) = ports.iter_mut().find(|t| is_for_location(*t, loc)) {
+                    set_string(existing, "label", label);
+                    return Ok(());
+                }
+                let mut entry = Table::new();
+                fill_location(&mut entry, loc, label);
+                let rest: Vec<Table> = ports.iter().cloned().collect();
+                if let Some(position) = rest.first().and_then(Table::position) {
+                    entry.set_position(position);
+                }
+                ports.clear();
+                ports.push(entry);
+                rest.into_iter().for_each(|t| ports.push(t));
+            }
+            Item::Value(Value::Array(ports)) => {
+                let existing = ports
+                    .iter_mut()
+                    .filter_map(Value::as_inline_table_mut)
+                    .find(|t| is_for_location(*t, loc));
+                if let Some(existing) = existing {
+                    set_string(existing, "label", label);
+                    return Ok(());
+                }
+                let mut entry = InlineTable::new();
+                fill_location(&mut entry, loc, label);
+                ports.insert(0, entry);
+                ports.fmt();
+            }
+            _ => return Err(not_a("physical_ports", "array of tables")),
+        }
+        Ok(())
+    }
+
+    /// Check the edited document is still a valid config, then write it
+    /// atomically, keeping the previous version as `<file>.bak`.
+    ///
+    /// Returns the backup path if there was a file to back up.
+    pub fn save(&self) -> Result<Option<PathBuf>, ConfigError> {
+        let content = self.doc.to_string();
+        toml::from_str::<Config>(&content)?;
+
+        // Write through symlinks (e.g. a dotfiles checkout) rather than over them
+        let path = fs::canonicalize(&self.path).unwrap_or_else(|_| self.path.clone());
+        if let Some(dir) = path.parent()
+            && !dir.as_os_str().is_empty()
+        {
+            fs::create_dir_all(dir)?;
+        }
+
+        let backup = if path.exists() {
+            let backup = with_suffix(&path, ".bak");
+            fs::copy(&path, &backup)?;
+            Some(backup)
+        } else {
+            None
+        };
+
+        let tmp = with_suffix(&path, ".tmp");
+        let result = (|| {
+            let mut file = fs::File::create(&tmp)?;
+            file.write_all(content.as_bytes())?;
+            file.sync_all()?;
+            if let Ok(meta) = fs::metadata(&path) {
+                fs::set_permissions(&tmp, meta.permissions())?;
+            }
+            fs::rename(&tmp, &path)
+        })();
+        if let Err(e) = result {
+            let _ = fs::remove_file(&tmp);
+            return Err(e.into());
+        }
+        Ok(backup)
+    }
+}
+
+impl std::fmt::Display for ConfigEditor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.doc)
+    }
+}
+
+fn not_a(key: &str, kind: &str) -> ConfigError {
+    ConfigError::Write(format!("`{}` is not a {}", key, kind))
+}
+
+/// `path` with `suffix` appended to the file name.
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(suffix);
+    path.with_file_name(name)
+}
+
+/// Key written as a quoted string, like the keys in the example config.
+fn quoted_key(key: &str) -> Key {
+    let repr = Value::from(key).to_string();
+    let Some(mut quoted) = Key::parse(repr.trim()).ok().and_then(|mut keys| keys.pop()) else {
+        return Key::new(key);
+    };
+    // Parsed keys carry explicit empty spacing; use the default instead
+    quoted.leaf_decor_mut().clear();
+    quoted.dotted_decor_mut().clear();
+    quoted
+}
+
+/// Set a string value, keeping any comment attached to an existing one.
+fn set_string(table: &mut dyn TableLike, key: &str, value: &str) {
+    match table.get_mut(key).and_then(Item::as_value_mut) {
+        Some(existing) => {
+            let decor = existing.decor().clone();
+            *existing = Value::from(value);
+            *existing.decor_mut() = decor;
+        }
+        None => {
+            table.insert(key, toml_edit::value(value));
+        }
+    }
+}
+
+fn is_for_location(table: &dyn TableLike, loc: &PhysicalLocation) -> bool {
+    let same =
+        |key: &str, value: &str| table.get(key).and_then(Item::as_str).unwrap_or("") == value;
+    same("panel", &loc.panel)
+        && same("horizontal_position", &loc.horizontal_position)
+        && same("vertical_position", &loc.vertical_position)
+        && table.get("dock").and_then(Item::as_bool).unwrap_or(false) == loc.dock
+}
+
+fn fill_location(table: &mut dyn TableLike, loc: &PhysicalLocation, label: &str) {
+    for (key, value) in [
+        ("panel", &loc.panel),
+        ("vertical_position", &loc.vertical_position),
+        ("horizontal_position", &loc.horizontal_position),
+    ] {
+        if !value.is_empty() {
+            table.insert(key, toml_edit::value(value.as_str()));
+        }
+    }
+    if loc.dock {
+        table.insert("dock", toml_edit::value(true));
+    }
+    table.insert("label", toml_edit::value(label));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edit(content: &str, target: &LabelTarget, label: &str) -> String {
+        let mut editor = ConfigEditor::parse(Path::new("usbbw.toml"), content).unwrap();
+        editor.set_label(target, label).unwrap();
+        editor.to_string()
+    }
+
+    #[test]
+    fn test_update_in_place() {
+        let content = "\
+# Devices
+[products]
+# \"[products]\" in a comment
+\"0d28:0204\" = \"OxLink\" # debug probe
+\"046d:082d\" = \"Webcam\"
+
+[settings]
+mouse = false
+";
+        let out = edit(
+            content,
+            &LabelTarget::Product("0d28:0204".into()),
+            "DAPLink",
+        );
+        assert_eq!(
+            out,
+            content.replace("\"OxLink\" # debug", "\"DAPLink\" # debug")
+        );
+
+        let out = edit(content, &Config::default().bus_target(2), "Rear USB-A");
+        assert!(out.starts_with(content));
+        assert!(out.ends_with("\n[buses]\n\"2\" = \"Rear USB-A\"\n"));
+    }
+
+    #[test]
+    fn test_inline_and_dotted_tables() {
+        let out = edit(
+            "controllers = { \"0000:c1:00.4\" = \"AMD\" }\n",
+            &LabelTarget::Controller("0000:c3:00.0".into()),
+            "Thunderbolt",
+        );
+        let config: Config = toml::from_str(&out).unwrap();
+        assert_eq!(config.controllers.len(), 2);
+        assert_eq!(
+            out,
+            "controllers = { \"0000:c1:00.4\" = \"AMD\", \"0000:c3:00.0\" = \"Thunderbolt\" }\n"
+        );
+
+        let out = edit(
+            "devices.\"1-1\" = \"Hub\"\n",
+            &LabelTarget::Device("1-1".into()),
+            "Dock hub",
+        );
+        assert_eq!(out, "devices.\"1-1\" = \"Dock hub\"\n");
+    }
+
+    #[test]
+    fn test_physical_ports_first_wins() {
+        let loc = PhysicalLocation {
+            panel: "left".to_string(),
+            vertical_position: "upper".to_string(),
+            ..Default::default()
+        };
+        let target = LabelTarget::PhysicalPort(loc);
+        let content =
+            "[settings]\nmouse = true\n\n[[physical_ports]]\npanel = \"left\"\nlabel = \"Left\"\n";
+        let out = edit(content, &target, "Left rear");
+        let config: Config = toml::from_str(&out).unwrap();
+        assert_eq!(config.physical_ports.len(), 2);
+        assert_eq!(config.physical_ports[0].label, "Left rear");
+        assert!(out.starts_with("[settings]\nmouse = true\n"));
+
+        let out = edit(&out, &target, "Left rear (USB-C)");
+        let config: Config = toml::from_str(&out).unwrap();
+        assert_eq!(config.physical_ports.len(), 2);
+        assert_eq!(config.physical_ports[0].label, "Left rear (USB-C)");
+    }
+
+    #[test]
+    fn test_save_keeps_backup() {
+        let dir = std::env::temp_dir().join(format!("usbbw-writer-{}", std::process::id()));
+        let path = dir.join("config.toml");
+        let _ = fs::remove_dir_all(&dir);
+
+        let mut editor = ConfigEditor::open(&path).unwrap();
+        editor
+            .set_label(&Config::default().bus_target(1), "Internal")
+            .unwrap();
+        assert_eq!(editor.save().unwrap(), None);
+
+        let mut editor = ConfigEditor::open(&path).unwrap();
+        editor
+            .set_label(&Config::default().bus_target(1), "Internal 2.0")
+            .unwrap();
+        let backup = editor.save().unwrap().unwrap();
+        assert!(fs::read_to_string(backup).unwrap().contains("\"Internal\""));
+        assert!(
+            fs::read_to_string(&path)
+                .unwrap()
+                .contains("\"Internal 2.0\"")
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,7 @@
+//! Persistent history of topology events.
+
+mod store;
+mod time;
+
+pub use store::{HistoryError, HistoryEvent, HistoryFilter, HistoryStore};
+pub use time::{format_timestamp, now_secs, parse_time};
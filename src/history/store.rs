@@ -0,0 +1,302 @@
+//! JSON Lines event store under the XDG state directory.
+
+use crate::model::{ChangeKind, TopologyDiff, UsbTopology, format_bps, key_matches};
+use serde::{Deserialize, Serialize};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// History store errors.
+#[derive(Debug, Error)]
+pub enum HistoryError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("No state directory available; use --history to choose a file")]
+    NoStateDir,
+}
+
+/// A single recorded topology event.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEvent {
+    /// Seconds since the Unix epoch.
+    pub timestamp: u64,
+    /// Event type (e.g., "added", "removed", "unconfigured", "over_current").
+    pub event: String,
+    /// Device config key (VID:PID:iSerial or VID:PID).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+    /// Device label at the time of the event.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Device path (new path, or old path for removals).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// Previous device path (for moves).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub old_path: Option<String>,
+    /// Human-readable details.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub details: Option<String>,
+}
+
+impl HistoryEvent {
+    /// Convert a topology diff into history events.
+    pub fn from_diff(diff: &TopologyDiff, timestamp: u64) -> Vec<Self> {
+        let mut events = Vec::new();
+
+        for change in &diff.changes {
+            let details = match &change.kind {
+                ChangeKind::Moved { from, to } => Some(format!("{} -> {}", from, to)),
+                ChangeKind::SpeedChanged { from, to } => {
+                    Some(format!("{} -> {}", from.short_name(), to.short_name()))
+                }
+                ChangeKind::BandwidthChanged { from_bps, to_bps } => Some(format!(
+                    "{} -> {}",
+                    format_bps(*from_bps),
+                    format_bps(*to_bps)
+                )),
+                _ => None,
+            };
+            let old_path = match change.kind {
+                ChangeKind::Moved { .. } => change.old_path.as_ref().map(|p| p.0.clone()),
+                _ => None,
+            };
+
+            events.push(Self {
+                timestamp,
+                event: change.kind.name().to_string(),
+                key: Some(change.key.clone()),
+                name: Some(change.name.clone()),
+                path: change.path().map(|p| p.0.clone()),
+                old_path,
+                details,
+            });
+        }
+
+        for oc in &diff.over_current {
+            events.push(Self {
+                timestamp,
+                event: "over_current".to_string(),
+                key: None,
+                name: None,
+                path: None,
+                old_path: None,
+                details: Some(format!(
+                    "Bus {} port {} ({}): {} -> {}",
+                    oc.bus_num, oc.port_num, oc.controller, oc.from_count, oc.to_count
+                )),
+            });
+        }
+
+        events
+    }
+}
+
+/// Filter for querying history.
+#[derive(Debug, Clone, Default)]
+pub struct HistoryFilter {
+    /// Device key or key prefix (e.g., "0d28:0204" matches all serials).
+    pub device: Option<String>,
+    /// Device path; also matches devices below it (e.g., "3-1" matches "3-1.2").
+    pub path: Option<String>,
+    /// Only events at or after this timestamp.
+    pub since: Option<u64>,
+    /// Only events at or before this timestamp.
+    pub until: Option<u64>,
+    /// Event type.
+    pub event: Option<String>,
+}
+
+impl HistoryFilter {
+    /// Check if an event passes this filter.
+    pub fn matches(&self, event: &HistoryEvent) -> bool {
+        if let Some(device) = &self.device
+            && !key_matches(event.key.as_deref().unwrap_or(""), device)
+        {
+            return false;
+        }
+
+        if let Some(path) = &self.path {
+            let matches_path = |p: &String| p == path || p.starts_with(&format!("{}.", path));
+            if !event.path.as_ref().is_some_and(matches_path)
+                && !event.old_path.as_ref().is_some_and(matches_path)
+            {
+                return false;
+            }
+        }
+
+        if self.since.is_some_and(|t| event.timestamp < t) {
+            return false;
+        }
+        if self.until.is_some_and(|t| event.timestamp > t) {
+            return false;
+        }
+
+        if let Some(kind) = &self.event
+            && event.event != *kind
+        {
+            return false;
+        }
+
+        true
+    }
+}
+
+/// Append-only history store in JSON Lines format.
+pub struct HistoryStore {
+    path: PathBuf,
+}
+
+impl HistoryStore {
+    /// Create a store backed by a specific file.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Open the default store (`$XDG_STATE_HOME/usbbw/history.jsonl`).
+    pub fn open_default() -> Result<Self, HistoryError> {
+        let dir = dirs::state_dir()
+            .or_else(dirs::data_local_dir)
+            .ok_or(HistoryError::NoStateDir)?;
+        Ok(Self::new(dir.join("usbbw").join("history.jsonl")))
+    }
+
+    /// Path of the history file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Path of the last-seen topology snapshot kept next to the history file.
+    fn snapshot_path(&self) -> PathBuf {
+        self.path.with_extension("topology.json")
+    }
+
+    /// Append events to the store.
+    pub fn append(&self, events: &[HistoryEvent]) -> Result<(), HistoryError> {
+        if events.is_empty() {
+            return Ok(());
+        }
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+
+        let mut buf = String::new();
+        for event in events {
+            buf.push_str(&serde_json::to_string(event)?);
+            buf.push('\n');
+        }
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        file.write_all(buf.as_bytes())?;
+        Ok(())
+    }
+
+    /// Load all events. Malformed lines are skipped.
+    pub fn load(&self) -> Result<Vec<HistoryEvent>, HistoryError> {
+        let content = match std::fs::read_to_string(&self.path) {
+            Ok(c) => c,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+
+        Ok(content
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect())
+    }
+
+    /// Load events matching a filter.
+    pub fn query(&self, filter: &HistoryFilter) -> Result<Vec<HistoryEvent>, HistoryError> {
+        Ok(self
+            .load()?
+            .into_iter()
+            .filter(|e| filter.matches(e))
+            .collect())
+    }
+
+    /// Load the topology recorded by the last daemon run, if any.
+    pub fn load_snapshot(&self) -> Option<UsbTopology> {
+        let content = std::fs::read_to_string(self.snapshot_path()).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    /// Record the current topology so a restarted daemon can catch up.
+    pub fn save_snapshot(&self, topology: &UsbTopology) -> Result<(), HistoryError> {
+        let path = self.snapshot_path();
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let tmp_path = path.with_extension("json.tmp");
+        std::fs::write(&tmp_path, serde_json::to_string(topology)?)?;
+        std::fs::rename(&tmp_path, &path)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(timestamp: u64, kind: &str, key: &str, path: &str) -> HistoryEvent {
+        HistoryEvent {
+            timestamp,
+            event: kind.to_string(),
+            key: Some(key.to_string()),
+            name: None,
+            path: Some(path.to_string()),
+            old_path: None,
+            details: None,
+        }
+    }
+
+    #[test]
+    fn test_filter_device_prefix() {
+        let filter = HistoryFilter {
+            device: Some("0d28:0204".to_string()),
+            ..Default::default()
+        };
+        assert!(filter.matches(&event(1, "added", "0d28:0204:ABC", "3-1")));
+        assert!(filter.matches(&event(1, "added", "0d28:0204", "3-1")));
+        assert!(!filter.matches(&event(1, "added", "0d28:02041", "3-1")));
+    }
+
+    #[test]
+    fn test_filter_path_and_time() {
+        let filter = HistoryFilter {
+            path: Some("3-1".to_string()),
+            since: Some(10),
+            event: Some("removed".to_string()),
+            ..Default::default()
+        };
+        assert!(filter.matches(&event(10, "removed", "x", "3-1.2")));
+        assert!(!filter.matches(&event(10, "removed", "x", "3-10")));
+        assert!(!filter.matches(&event(9, "removed", "x", "3-1")));
+        assert!(!filter.matches(&event(10, "added", "x", "3-1")));
+    }
+
+    #[test]
+    fn test_append_and_load() {
+        let dir = std::env::temp_dir().join(format!("usbbw-history-test-{}", std::process::id()));
+        let store = HistoryStore::new(dir.join("history.jsonl"));
+        store
+            .append(&[
+                event(1, "added", "a", "1-1"),
+                event(2, "removed", "b", "1-2"),
+            ])
+            .unwrap();
+        store.append(&[event(3, "added", "c", "1-3")]).unwrap();
+
+        let events = store.load().unwrap();
+        assert_eq!(events.len(), 3);
+        assert_eq!(events[2].key.as_deref(), Some("c"));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,136 @@
+//! Minimal UTC timestamp formatting and parsing (no external dependency).
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Current time as seconds since the Unix epoch.
+pub fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Format seconds since the Unix epoch as "YYYY-MM-DD HH:MM:SS" (UTC).
+pub fn format_timestamp(secs: u64) -> String {
+    let days = (secs / 86400) as i64;
+    let rem = secs % 86400;
+    let (year, month, day) = civil_from_days(days);
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+        year,
+        month,
+        day,
+        rem / 3600,
+        (rem % 3600) / 60,
+        rem % 60
+    )
+}
+
+/// Parse a time specification into seconds since the Unix epoch.
+///
+/// Accepted forms:
+/// - Relative to `now`: "90s", "30m", "2h", "7d"
+/// - Unix timestamp: "1760000000"
+/// - UTC date/time: "2026-10-18", "2026-10-18T12:30", "2026-10-18 12:30:05"
+pub fn parse_time(s: &str, now: u64) -> Option<u64> {
+    let s = s.trim();
+
+    if let Some(unit) = s.chars().last().filter(|c| c.is_ascii_alphabetic())
+        && let Ok(n) = s[..s.len() - 1].parse::<u64>()
+    {
+        let unit_secs: u64 = match unit {
+            's' => 1,
+            'm' => 60,
+            'h' => 3600,
+            'd' => 86400,
+            'w' => 7 * 86400,
+            _ => return None,
+        };
+        let secs = n.checked_mul(unit_secs)?;
+        return Some(now.saturating_sub(secs));
+    }
+
+    if let Ok(ts) = s.parse::<u64>() {
+        return Some(ts);
+    }
+
+    let (date, time) = match s.split_once(['T', ' ']) {
+        Some((d, t)) => (d, Some(t)),
+        None => (s, None),
+    };
+
+    let mut date_parts = date.split('-').map(|p| p.parse::<i64>().ok());
+    let year = date_parts.next()??;
+    let month = date_parts.next()??;
+    let day = date_parts.next()??;
+    if date_parts.next().is_some() || !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+
+    let mut secs_of_day = 0;
+    if let Some(time) = time {
+        let mut time_parts = time.split(':').map(|p| p.parse::<u64>().ok());
+        let hour = time_parts.next()??;
+        let minute = time_parts.next().unwrap_or(Some(0))?;
+        let second = time_parts.next().unwrap_or(Some(0))?;
+        if hour > 23 || minute > 59 || second > 59 {
+            return None;
+        }
+        secs_of_day = hour * 3600 + minute * 60 + second;
+    }
+
+    let days = days_from_civil(year, month as u32, day as u32);
+    if days < 0 {
+        return None;
+    }
+    Some(days as u64 * 86400 + secs_of_day)
+}
+
+/// Convert days since 1970-01-01 to (year, month, day).
+/// Algorithm from Howard Hinnant's date library.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+/// Convert (year, month, day) to days since 1970-01-01.
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let mp = (month as i64 + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_timestamp() {
+        assert_eq!(format_timestamp(0), "1970-01-01 00:00:00");
+        assert_eq!(format_timestamp(1_700_000_000), "2023-11-14 22:13:20");
+    }
+
+    #[test]
+    fn test_parse_time() {
+        assert_eq!(parse_time("2023-11-14T22:13:20", 0), Some(1_700_000_000));
+        assert_eq!(parse_time("2023-11-14", 0), Some(1_699_920_000));
+        assert_eq!(parse_time("2h", 10_000), Some(2_800));
+        assert_eq!(parse_time("1700000000", 0), Some(1_700_000_000));
+        assert_eq!(parse_time("yesterday", 0), None);
+        assert_eq!(parse_time("99999999999999999999s", 0), None);
+        assert_eq!(parse_time("40000000000000w", 10_000), None);
+        assert_eq!(parse_time("3000000000000000d", 10_000), None);
+    }
+}
@@ -2,7 +2,10 @@
 //!
 //! A library and CLI tool for visualizing USB bandwidth allocation on Linux systems.
 
+pub mod alert;
+pub mod check;
 pub mod config;
+pub mod history;
 pub mod model;
 pub mod output;
 pub mod sysfs;
@@ -1,23 +1,39 @@
 //! USB Bandwidth Visualization Tool - CLI entry point.
 
 use anyhow::Result;
+use clap::builder::{PossibleValuesParser, TypedValueParser};
 use clap::{CommandFactory, Parser, Subcommand};
 use clap_complete::{Shell, generate};
 use crossterm::{
-    event::{self, Event, KeyCode, KeyEventKind, KeyModifiers},
+    event::{
+        self, DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture,
+        Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers,
+    },
     execute,
     terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
 };
 use ratatui::prelude::*;
-use std::io::stdout;
-use std::path::PathBuf;
+use std::io::{Stdout, Write, stdout};
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 
-use usbbw::config::{Config, example_config, generate_config};
-use usbbw::model::{BandwidthPool, format_bandwidth};
-use usbbw::output::{generate_markdown, generate_mermaid};
+use usbbw::alert::{evaluate_alerts, notification_sequence, run_hook};
+use usbbw::check::{CheckResult, generate_junit, run_checks};
+use usbbw::config::{
+    BaselineSource, Config, ConfigEditor, ConfigStack, Dmi, LabelTarget, Severity,
+    available_baselines, baseline_dirs, example_config, explain_label, generate_config,
+    render_effective, shadowed_labels, validate,
+};
+use usbbw::history::{
+    HistoryEvent, HistoryFilter, HistoryStore, format_timestamp, now_secs, parse_time,
+};
+use usbbw::model::{BandwidthPool, ChangeKind, DevicePath, TopologyDiff, Units, diff_topologies};
+use usbbw::output::{
+    Delimiter, generate_device_table, generate_dot, generate_endpoint_table, generate_markdown,
+    generate_mermaid, generate_metrics, generate_svg,
+};
 use usbbw::sysfs::SysfsParser;
-use usbbw::ui::{App, ViewMode, render};
+use usbbw::ui::{App, ScreenLayout, ViewMode, handle_mouse, render};
 
 #[derive(Parser)]
 #[command(name = "usbbw")]
@@ -27,9 +43,27 @@ struct Cli {
     #[command(subcommand)]
     command: Option<Commands>,
 
-    /// Config file path (default: auto-detect)
+    /// Config file path, instead of merging all search locations
+    /// (default: $USBBW_CONFIG)
     #[arg(short, long)]
     config: Option<PathBuf>,
+
+    /// Bandwidth units (overrides `units`/`use_bits` in the config)
+    #[arg(
+        long,
+        global = true,
+        value_parser = PossibleValuesParser::new(Units::NAMES).try_map(|s| s.parse::<Units>())
+    )]
+    units: Option<Units>,
+
+    /// Write the TUI event log to FILE (JSON lines) on quit
+    #[arg(long, value_name = "FILE")]
+    event_log: Option<PathBuf>,
+
+    /// Save TUI labels to FILE: a file the config was read from
+    /// (default: the --config file, else the user config)
+    #[arg(long, value_name = "FILE")]
+    write_config: Option<PathBuf>,
 }
 
 #[derive(Subcommand)]
@@ -50,9 +84,30 @@ enum Commands {
         #[arg(long)]
         markdown: bool,
 
-        /// Generate standalone HTML file (opens in browser)
+        /// Generate standalone offline HTML report with an inline SVG diagram
         #[arg(long)]
         html: bool,
+
+        #[command(flatten)]
+        view: ViewArgs,
+    },
+
+    /// Export topology in another diagram format
+    Export {
+        /// Output format
+        #[arg(short, long, value_enum, default_value_t = ExportFormat::Dot)]
+        format: ExportFormat,
+
+        /// Output file (default: stdout)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// CSV/TSV: one row per device instead of per endpoint
+        #[arg(long)]
+        per_device: bool,
+
+        #[command(flatten)]
+        view: ViewArgs,
     },
 
     /// List all devices
@@ -69,6 +124,112 @@ enum Commands {
     /// Show best buses for new devices
     Recommend,
 
+    /// Save topology snapshot as JSON (for later comparison with `diff`)
+    Snapshot {
+        /// Output file (default: stdout)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Compare two snapshots, or a snapshot against the live system
+    Diff {
+        /// Old snapshot file
+        old: PathBuf,
+
+        /// New snapshot file (default: live system)
+        new: Option<PathBuf>,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Check the topology against the [expect] rules in the config (exits 1 on failure)
+    Check {
+        /// Check a snapshot file instead of the live system
+        #[arg(long)]
+        snapshot: Option<PathBuf>,
+
+        /// Also write a JUnit XML report to this file
+        #[arg(long)]
+        junit: Option<PathBuf>,
+    },
+
+    /// Serve Prometheus metrics over HTTP or write a node_exporter textfile
+    Exporter {
+        /// Address to listen on for HTTP scrapes of /metrics
+        #[arg(long, default_value = "127.0.0.1:9842")]
+        listen: String,
+
+        /// Write metrics to this file instead of serving HTTP (textfile collector)
+        #[arg(long)]
+        textfile: Option<PathBuf>,
+
+        /// Seconds between textfile updates
+        #[arg(long, default_value_t = 15)]
+        interval: u64,
+
+        /// Write the textfile once and exit
+        #[arg(long, requires = "textfile")]
+        once: bool,
+    },
+
+    /// Watch for topology changes and record them in the history store
+    Daemon {
+        /// History file (default: $XDG_STATE_HOME/usbbw/history.jsonl)
+        #[arg(long)]
+        history: Option<PathBuf>,
+
+        /// Polling interval in milliseconds (default: settings.refresh_ms)
+        #[arg(long)]
+        interval_ms: Option<u64>,
+    },
+
+    /// Query recorded topology events
+    History {
+        /// History file (default: $XDG_STATE_HOME/usbbw/history.jsonl)
+        #[arg(long)]
+        history: Option<PathBuf>,
+
+        /// Device key or VID:PID prefix (e.g. "0d28:0204")
+        #[arg(long)]
+        device: Option<String>,
+
+        /// Device path (also matches devices behind it)
+        #[arg(long)]
+        path: Option<String>,
+
+        /// Start time ("2h", "7d", "2026-10-18", "2026-10-18T09:00" UTC, or Unix seconds)
+        #[arg(long)]
+        since: Option<String>,
+
+        /// End time (same formats as --since)
+        #[arg(long)]
+        until: Option<String>,
+
+        /// Event type (added, removed, moved, reenumerated, speed_changed,
+        /// bandwidth_changed, configured, unconfigured, over_current)
+        #[arg(long)]
+        event: Option<String>,
+
+        /// Output as JSON Lines
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Inspect the loaded config: validate it, show where values come from,
+    /// or explain a device's label
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+
+    /// List hardware baselines or print one
+    Baselines {
+        #[command(subcommand)]
+        action: BaselinesAction,
+    },
+
     /// Print blank example config file
     InitConfig,
 
@@ -87,6 +248,99 @@ enum Commands {
     },
 }
 
+/// `usbbw config` actions.
+#[derive(Subcommand)]
+enum ConfigAction {
+    /// Report unknown keys, malformed keys, unmatched physical ports and
+    /// duplicate labels (exits 1 on errors)
+    Validate {
+        /// Check physical ports against a snapshot instead of the live system
+        #[arg(long)]
+        snapshot: Option<PathBuf>,
+    },
+
+    /// Print the merged config with the file each value comes from
+    Show,
+
+    /// Show which config entry produces a device's label
+    Explain {
+        /// Device path (e.g. "3-1.2")
+        path: String,
+
+        /// Look the device up in a snapshot instead of the live system
+        #[arg(long)]
+        snapshot: Option<PathBuf>,
+    },
+}
+
+/// `usbbw baselines` actions.
+#[derive(Subcommand)]
+enum BaselinesAction {
+    /// List available baselines, the DMI values they match and the active one
+    List,
+
+    /// Print a baseline (default: the active one)
+    Show {
+        /// Baseline name (file name without `.toml`)
+        name: Option<String>,
+    },
+}
+
+/// Diagram view filters (override the `[mermaid]` config section).
+#[derive(clap::Args, Debug)]
+struct ViewArgs {
+    /// Hide a device path and everything behind it (repeatable)
+    #[arg(long = "hide", value_name = "PATH")]
+    hide_paths: Vec<String>,
+
+    /// Only show devices from this vendor ID, plus the hubs leading to them (repeatable)
+    #[arg(long = "vendor", value_name = "VID")]
+    filter_vendors: Vec<String>,
+
+    /// Collapse hubs that have a single child
+    #[arg(long)]
+    collapse_hubs: bool,
+
+    /// Maximum device depth below the root hub (1 = root port devices only)
+    #[arg(long, value_name = "N")]
+    max_depth: Option<usize>,
+}
+
+impl ViewArgs {
+    /// Merge command-line filters into the config.
+    fn apply(&self, config: &mut Config) {
+        config
+            .mermaid
+            .hide_paths
+            .extend(self.hide_paths.iter().cloned());
+        config
+            .mermaid
+            .filter_vendors
+            .extend(self.filter_vendors.iter().cloned());
+        if self.collapse_hubs {
+            config.mermaid.collapse_single_child_hubs = true;
+        }
+        if self.max_depth.is_some() {
+            config.mermaid.max_depth = self.max_depth;
+        }
+    }
+}
+
+/// Diagram export formats.
+#[derive(Clone, Copy, Debug, clap::ValueEnum)]
+enum ExportFormat {
+    /// Graphviz DOT (render with `dot -Tsvg`)
+    Dot,
+    /// Mermaid flowchart (markdown code block)
+    Mermaid,
+    /// Standalone SVG (no external resources)
+    Svg,
+    /// Periodic endpoint table, comma-separated
+    Csv,
+    /// Periodic endpoint table, tab-separated
+    Tsv,
+}
+
 fn main() -> Result<()> {
     let cli = Cli::parse();
 
@@ -97,11 +351,86 @@ fn main() -> Result<()> {
         return Ok(());
     }
 
-    // Load config
-    let mut config = match &cli.config {
-        Some(path) => Config::load_from_path(path)?,
-        None => Config::load()?,
-    };
+    // Load config, with the hardware baseline underneath
+    let stack = Config::discover(cli.config.as_deref())?;
+    let mut config = Config::from_stack(&stack)?;
+    if let Some(units) = cli.units {
+        config.settings.units = Some(units);
+    }
+
+    if let Some(Commands::Config { action }) = &cli.command {
+        return run_config_command(action, &stack, config);
+    }
+    if let Some(Commands::Baselines { action }) = &cli.command {
+        return run_baselines_command(action, &stack);
+    }
+
+    // History queries never touch sysfs
+    if let Some(Commands::History {
+        history,
+        device,
+        path,
+        since,
+        until,
+        event,
+        json,
+    }) = &cli.command
+    {
+        let store = open_history(history.as_deref())?;
+        let now = now_secs();
+        let parse = |s: &Option<String>| -> Result<Option<u64>> {
+            s.as_deref()
+                .map(|s| parse_time(s, now).ok_or_else(|| anyhow::anyhow!("invalid time: {}", s)))
+                .transpose()
+        };
+        let filter = HistoryFilter {
+            device: device.clone(),
+            path: path.clone(),
+            since: parse(since)?,
+            until: parse(until)?,
+            event: event.clone(),
+        };
+        print_history(&store.query(&filter)?, *json)?;
+        return Ok(());
+    }
+
+    // Diff only touches sysfs when comparing against the live system
+    if let Some(Commands::Diff { old, new, json }) = &cli.command {
+        let old_topology = load_snapshot(old)?;
+        let new_topology = match new {
+            Some(path) => load_snapshot(path)?,
+            None => SysfsParser::new().parse_topology()?,
+        };
+        config.apply_defaults_from_topology(&new_topology);
+        let mut diff = diff_topologies(&old_topology, &new_topology);
+        label_diff(&mut diff, &old_topology, &new_topology, &config);
+        if *json {
+            println!("{}", serde_json::to_string_pretty(&diff)?);
+        } else {
+            print_diff(&diff, config.units());
+        }
+        return Ok(());
+    }
+
+    if let Some(Commands::Check { snapshot, junit }) = &cli.command {
+        let topology = match snapshot {
+            Some(path) => load_snapshot(path)?,
+            None => SysfsParser::new().parse_topology()?,
+        };
+        config.apply_defaults_from_topology(&topology);
+        if config.expect.is_empty() {
+            anyhow::bail!("No [expect] rules configured");
+        }
+        let results = run_checks(&topology, &config);
+        print_check_results(&results);
+        if let Some(path) = junit {
+            std::fs::write(path, generate_junit(&results))?;
+        }
+        if results.iter().any(|r| !r.passed) {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
 
     // Parse USB topology
     let parser = SysfsParser::new();
@@ -121,7 +450,9 @@ fn main() -> Result<()> {
             output,
             markdown,
             html,
+            view,
         }) => {
+            view.apply(&mut config);
             let content = if html {
                 usbbw::output::generate_html(&topology, &config)
             } else if markdown {
@@ -134,6 +465,33 @@ fn main() -> Result<()> {
                 None => print!("{}", content),
             }
         }
+        Some(Commands::Export {
+            format,
+            output,
+            per_device,
+            view,
+        }) => {
+            if per_device && !matches!(format, ExportFormat::Csv | ExportFormat::Tsv) {
+                anyhow::bail!("--per-device only applies to csv and tsv exports");
+            }
+            view.apply(&mut config);
+            let table = if per_device {
+                generate_device_table
+            } else {
+                generate_endpoint_table
+            };
+            let content = match format {
+                ExportFormat::Dot => generate_dot(&topology, &config),
+                ExportFormat::Mermaid => generate_mermaid(&topology, &config),
+                ExportFormat::Svg => generate_svg(&topology, &config),
+                ExportFormat::Csv => table(&topology, &config, Delimiter::Comma),
+                ExportFormat::Tsv => table(&topology, &config, Delimiter::Tab),
+            };
+            match output {
+                Some(path) => std::fs::write(path, content)?,
+                None => print!("{}", content),
+            }
+        }
         Some(Commands::List {
             periodic_only,
             verbose,
@@ -143,6 +501,22 @@ fn main() -> Result<()> {
         Some(Commands::Recommend) => {
             print_recommendations(&topology, &config);
         }
+        Some(Commands::Snapshot { output }) => {
+            let content = serde_json::to_string_pretty(&topology)? + "\n";
+            match output {
+                Some(path) => std::fs::write(path, content)?,
+                None => print!("{}", content),
+            }
+        }
+        Some(Commands::Exporter {
+            listen,
+            textfile,
+            interval,
+            once,
+        }) => match textfile {
+            Some(path) => run_textfile_exporter(&path, &mut config, interval, once)?,
+            None => run_http_exporter(&listen, &mut config)?,
+        },
         Some(Commands::InitConfig) => {
             print!("{}", example_config());
         }
@@ -160,13 +534,33 @@ fn main() -> Result<()> {
                 None => print!("{}", content),
             }
         }
-        Some(Commands::Completions { .. }) => {
-            // Handled above before loading config/topology
+        Some(Commands::Daemon {
+            history,
+            interval_ms,
+        }) => {
+            let store = open_history(history.as_deref())?;
+            let interval = interval_ms.unwrap_or(config.settings.refresh_ms);
+            run_daemon(topology, &store, &mut config, interval)?;
+        }
+        Some(Commands::Completions { .. })
+        | Some(Commands::Config { .. })
+        | Some(Commands::Baselines { .. })
+        | Some(Commands::Diff { .. })
+        | Some(Commands::Check { .. })
+        | Some(Commands::History { .. }) => {
+            // Handled above before loading topology
             unreachable!()
         }
         None => {
             // Default: run TUI
-            run_tui(topology, config)?;
+            let label_file = config.write_target(cli.write_config.as_deref())?;
+            run_tui(
+                topology,
+                config,
+                cli.event_log,
+                label_file,
+                cli.config.as_deref(),
+            )?;
         }
     }
 
@@ -204,11 +598,11 @@ fn print_summary(topology: &usbbw::UsbTopology, config: &Config) {
         );
         println!(
             "  Periodic BW: {} / {} ({:.1}%)",
-            pool.format_used(),
-            pool.format_max(),
+            pool.format_used(config.units()),
+            pool.format_max(config.units()),
             pool.periodic_usage_percent()
         );
-        println!("  Available:   {}", pool.format_available());
+        println!("  Available:   {}", pool.format_available(config.units()));
         println!("  Devices:     {}", bus.device_count());
         let total_power = bus.total_power_ma();
         if total_power > 0 {
@@ -281,15 +675,7 @@ fn print_report(topology: &usbbw::UsbTopology, config: &Config) {
                 total_power_ma = total_power_ma.saturating_add(device.max_power_ma);
 
                 let indent = "    ".to_string() + &"  ".repeat(device.path.depth());
-                let name = config
-                    .device_label(
-                        &device.path.0,
-                        device.vendor_id,
-                        device.product_id,
-                        device.serial.as_deref(),
-                        device.physical_location.as_ref(),
-                    )
-                    .unwrap_or_else(|| device.display_name());
+                let name = config.device_name(device);
 
                 // Status indicators
                 let status = if !device.is_configured {
@@ -313,7 +699,7 @@ fn print_report(topology: &usbbw::UsbTopology, config: &Config) {
                 let bw = device.periodic_bandwidth_bps();
                 let mut details = Vec::new();
                 if bw > 0 {
-                    details.push(format!("bw:{}", format_bandwidth(bw)));
+                    details.push(format!("bw:{}", config.units().format(bw, bus.speed)));
                 }
                 if device.max_power_ma > 0 {
                     details.push(format!("pwr:{}mA", device.max_power_ma));
@@ -334,7 +720,7 @@ fn print_report(topology: &usbbw::UsbTopology, config: &Config) {
     println!(
         "Total: {} devices, {}, {} mA",
         total_devices,
-        format_bandwidth(total_periodic_bw),
+        config.units().format_rate(total_periodic_bw),
         total_power_ma
     );
     if unconfigured_count > 0 {
@@ -365,15 +751,7 @@ fn print_device_list(
             }
 
             let indent = "  ".repeat(device.path.depth() + 1);
-            let name = config
-                .device_label(
-                    &device.path.0,
-                    device.vendor_id,
-                    device.product_id,
-                    device.serial.as_deref(),
-                    device.physical_location.as_ref(),
-                )
-                .unwrap_or_else(|| device.display_name());
+            let name = config.device_name(device);
 
             // Show port path for root-level devices (direct on bus)
             let port_prefix = if device.path.depth() == 0 {
@@ -394,7 +772,7 @@ fn print_device_list(
             } else {
                 let bw = device.periodic_bandwidth_bps();
                 if bw > 0 {
-                    format!(" [{}]", format_bandwidth(bw))
+                    format!(" [{}]", config.units().format(bw, bus.speed))
                 } else {
                     String::new()
                 }
@@ -437,7 +815,7 @@ fn print_device_list(
                         ep.direction,
                         ep.max_packet_size,
                         ep.interval_str,
-                        format_bandwidth(ep_bw)
+                        config.units().format(ep_bw, bus.speed)
                     );
                 }
             }
@@ -470,7 +848,7 @@ fn print_recommendations(topology: &usbbw::UsbTopology, config: &Config) {
         println!(
             "  {} - {} available ({:.1}% used)",
             label,
-            pool.format_available(),
+            pool.format_available(config.units()),
             pool.periodic_usage_percent()
         );
     }
@@ -484,30 +862,509 @@ fn print_recommendations(topology: &usbbw::UsbTopology, config: &Config) {
         println!(
             "  {} - {} available ({:.1}% used)",
             label,
-            pool.format_available(),
+            pool.format_available(config.units()),
             pool.periodic_usage_percent()
         );
     }
 }
 
-fn run_tui(topology: usbbw::UsbTopology, config: Config) -> Result<()> {
+/// Load a topology snapshot written by `usbbw snapshot`.
+fn load_snapshot(path: &std::path::Path) -> Result<usbbw::UsbTopology> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| anyhow::anyhow!("cannot read snapshot {}: {}", path.display(), e))?;
+    let topology = serde_json::from_str(&content)
+        .map_err(|e| anyhow::anyhow!("invalid snapshot {}: {}", path.display(), e))?;
+    Ok(topology)
+}
+
+/// Replace device display names in a diff with configured labels.
+fn label_diff(
+    diff: &mut TopologyDiff,
+    old: &usbbw::UsbTopology,
+    new: &usbbw::UsbTopology,
+    config: &Config,
+) {
+    for change in &mut diff.changes {
+        let device = change
+            .new_path
+            .as_ref()
+            .and_then(|p| new.get_device(p))
+            .or_else(|| change.old_path.as_ref().and_then(|p| old.get_device(p)));
+        if let Some(device) = device
+            && let Some(label) = config.device_label(device)
+        {
+            change.name = label;
+        }
+    }
+}
+
+fn run_config_command(
+    action: &ConfigAction,
+    stack: &ConfigStack,
+    mut config: Config,
+) -> Result<()> {
+    let layers = &stack.layers;
+    let topology = |snapshot: &Option<PathBuf>| match snapshot {
+        Some(path) => load_snapshot(path),
+        None => Ok(SysfsParser::new().parse_topology()?),
+    };
+
+    match action {
+        ConfigAction::Show => print!("{}", render_effective(layers)),
+        ConfigAction::Validate { snapshot } => {
+            if layers.is_empty() {
+                println!("No config file found; using defaults");
+                return Ok(());
+            }
+            let topology = topology(snapshot).ok();
+            let findings = validate(layers, topology.as_ref());
+            for finding in &findings {
+                let severity = match finding.severity {
+                    Severity::Error => "error",
+                    Severity::Warning => "warning",
+                };
+                match &finding.source {
+                    Some(source) => {
+                        println!("{}: {} ({})", severity, finding.message, source.display())
+                    }
+                    None => println!("{}: {}", severity, finding.message),
+                }
+            }
+            if topology.is_none() {
+                println!("note: no USB topology available, [[physical_ports]] not checked");
+            }
+
+            let errors = findings
+                .iter()
+                .filter(|f| f.severity == Severity::Error)
+                .count();
+            if !findings.is_empty() {
+                println!();
+            }
+            println!(
+                "{} file(s) checked, {} error(s), {} warning(s)",
+                layers.len(),
+                errors,
+                findings.len() - errors
+            );
+            if errors > 0 {
+                std::process::exit(1);
+            }
+        }
+        ConfigAction::Explain { path, snapshot } => {
+            let topology = topology(snapshot)?;
+            // Auto-detected defaults can label devices too
+            config.apply_defaults_from_topology(&topology);
+            let device = topology
+                .get_device(&DevicePath(path.clone()))
+                .ok_or_else(|| anyhow::anyhow!("No device at path {}", path))?;
+            print!("{}", explain_label(layers, &config, device));
+        }
+    }
+    Ok(())
+}
+
+fn run_baselines_command(action: &BaselinesAction, stack: &ConfigStack) -> Result<()> {
+    let (baselines, errors) = available_baselines(&baseline_dirs());
+    // A baseline the config inherits by path takes the place of auto-selection
+    let inherited = stack
+        .layers
+        .iter()
+        .skip(stack.baseline_layers)
+        .find(|layer| layer.value.get("baseline").is_some())
+        .map(|layer| layer.path.clone());
+    let active = stack.baseline.as_ref().map(|b| b.id.as_str());
+
+    match action {
+        BaselinesAction::List => {
+            let dmi = Dmi::read();
+            if dmi.is_empty() {
+                println!("DMI: not available");
+            } else {
+                println!("DMI: {}", dmi);
+            }
+            println!();
+            for baseline in &baselines {
+                let marker = if active == Some(baseline.id.as_str()) {
+                    "*"
+                } else {
+                    " "
+                };
+                let source = match &baseline.source {
+                    BaselineSource::Bundled => "bundled".to_string(),
+                    BaselineSource::File(path) => path.display().to_string(),
+                };
+                println!("{} {:<24} {}", marker, baseline.id, baseline.info.dmi);
+                if let Some(name) = &baseline.info.name {
+                    println!("  {:<24} {}", "", name);
+                }
+                println!("  {:<24} {}", "", source);
+            }
+            // Broken files are listed so the error isn't only a startup warning
+            for (path, e) in &errors {
+                let id = path.file_stem().unwrap_or_default().to_string_lossy();
+                println!("! {:<24} skipped: {}", id, path.display());
+                for line in e.to_string().lines() {
+                    println!("  {:<24} {}", "", line);
+                }
+            }
+            println!();
+            match (&inherited, &stack.baseline) {
+                (Some(path), _) => println!("Active: {} (inherited by the config)", path.display()),
+                (None, Some(baseline)) => println!("Active: {}", baseline.id),
+                (None, None) => println!("Active: none"),
+            }
+        }
+        BaselinesAction::Show { name } => {
+            let content = match (name, &inherited, &stack.baseline) {
+                (Some(name), _, _) => baselines
+                    .iter()
+                    .find(|b| &b.id == name)
+                    .map(|b| b.content.clone())
+                    .ok_or_else(|| anyhow::anyhow!("No baseline named {}", name))?,
+                (None, Some(path), _) => std::fs::read_to_string(path)?,
+                (None, None, Some(baseline)) => baseline.content.clone(),
+                (None, None, None) => {
+                    anyhow::bail!("No baseline is active (DMI: {})", Dmi::read())
+                }
+            };
+            print!("{}", content);
+        }
+    }
+    Ok(())
+}
+
+fn print_check_results(results: &[CheckResult]) {
+    for result in results {
+        let status = if result.passed { "PASS" } else { "FAIL" };
+        println!("[{}] {}: {}", status, result.name, result.message);
+    }
+
+    let failures = results.iter().filter(|r| !r.passed).count();
+    println!();
+    println!(
+        "{} checks, {} passed, {} failed",
+        results.len(),
+        results.len() - failures,
+        failures
+    );
+}
+
+fn print_diff(diff: &TopologyDiff, units: Units) {
+    if diff.is_empty() {
+        println!("No changes");
+        return;
+    }
+
+    for change in &diff.changes {
+        let path = change.path().map(|p| p.0.as_str()).unwrap_or("?");
+        let (marker, details) = match &change.kind {
+            ChangeKind::Added => ("+", format!("at {}", path)),
+            ChangeKind::Removed => ("-", format!("was at {}", path)),
+            ChangeKind::Moved { from, to } => {
+                let old_path = change
+                    .old_path
+                    .as_ref()
+                    .map(|p| p.0.as_str())
+                    .unwrap_or("?");
+                (
+                    "~",
+                    format!("{} -> {} ({} -> {})", old_path, path, from, to),
+                )
+            }
+            ChangeKind::SpeedChanged { from, to } => (
+                "~",
+                format!("{} -> {} at {}", from.short_name(), to.short_name(), path),
+            ),
+            ChangeKind::BandwidthChanged { from_bps, to_bps } => (
+                "~",
+                format!(
+                    "{} -> {} at {}",
+                    units.format_rate(*from_bps),
+                    units.format_rate(*to_bps),
+                    path
+                ),
+            ),
+            ChangeKind::Reenumerated => ("~", format!("re-enumerated at {}", path)),
+            ChangeKind::Configured => ("~", format!("now configured at {}", path)),
+            ChangeKind::Unconfigured => ("!", format!("NOT CONFIGURED at {}", path)),
+        };
+
+        println!(
+            "{} {:<17} {}  {}  {}",
+            marker,
+            change.kind.name(),
+            change.key,
+            change.name,
+            details
+        );
+    }
+    for oc in &diff.over_current {
+        println!(
+            "! {:<17} Bus {} port {}  {} -> {} event(s)",
+            "over_current", oc.bus_num, oc.port_num, oc.from_count, oc.to_count
+        );
+    }
+}
+
+/// Write metrics to a node_exporter textfile, atomically replacing it.
+fn run_textfile_exporter(
+    path: &std::path::Path,
+    config: &mut Config,
+    interval_secs: u64,
+    once: bool,
+) -> Result<()> {
+    let tmp_path = path.with_extension("prom.tmp");
+    loop {
+        let topology = SysfsParser::new().parse_topology()?;
+        config.resolve_topology(&topology);
+        std::fs::write(&tmp_path, generate_metrics(&topology, config))?;
+        std::fs::rename(&tmp_path, path)?;
+        if once {
+            return Ok(());
+        }
+        std::thread::sleep(Duration::from_secs(interval_secs));
+    }
+}
+
+/// Serve metrics over HTTP, re-reading sysfs on every scrape.
+fn run_http_exporter(listen: &str, config: &mut Config) -> Result<()> {
+    use std::io::{BufRead, BufReader, Write};
+
+    let listener = std::net::TcpListener::bind(listen)?;
+    eprintln!("Serving metrics on http://{}/metrics", listen);
+
+    for stream in listener.incoming() {
+        let Ok(mut stream) = stream else {
+            continue;
+        };
+        let _ = stream.set_read_timeout(Some(Duration::from_secs(5)));
+
+        let mut request_line = String::new();
+        if BufReader::new(&stream)
+            .read_line(&mut request_line)
+            .is_err()
+        {
+            continue;
+        }
+        let target = request_line.split_whitespace().nth(1).unwrap_or("/");
+
+        let (status, content_type, body) =
+            if target == "/metrics" || target.starts_with("/metrics?") {
+                match SysfsParser::new().parse_topology() {
+                    Ok(topology) => {
+                        config.resolve_topology(&topology);
+                        (
+                            "200 OK",
+                            "text/plain; version=0.0.4; charset=utf-8",
+                            generate_metrics(&topology, config),
+                        )
+                    }
+                    Err(e) => (
+                        "500 Internal Server Error",
+                        "text/plain; charset=utf-8",
+                        format!("Failed to read USB topology: {}\n", e),
+                    ),
+                }
+            } else if target == "/" {
+                (
+                    "200 OK",
+                    "text/html; charset=utf-8",
+                    "<html><body><a href=\"/metrics\">Metrics</a></body></html>\n".to_string(),
+                )
+            } else {
+                (
+                    "404 Not Found",
+                    "text/plain; charset=utf-8",
+                    "Not found\n".to_string(),
+                )
+            };
+
+        let _ = write!(
+            stream,
+            "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status,
+            content_type,
+            body.len(),
+            body
+        );
+    }
+
+    Ok(())
+}
+
+/// Open the history store at an explicit path or the default location.
+fn open_history(path: Option<&std::path::Path>) -> Result<HistoryStore> {
+    Ok(match path {
+        Some(p) => HistoryStore::new(p),
+        None => HistoryStore::open_default()?,
+    })
+}
+
+/// Poll the topology and append every change to the history store.
+fn run_daemon(
+    topology: usbbw::UsbTopology,
+    store: &HistoryStore,
+    config: &mut Config,
+    interval_ms: u64,
+) -> Result<()> {
+    eprintln!("Recording USB events to {}", store.path().display());
+
+    // Catch up on changes that happened while the daemon was not running
+    let mut previous = store.load_snapshot();
+    let mut current = topology;
+
+    loop {
+        if let Some(prev) = &previous {
+            let mut diff = diff_topologies(prev, &current);
+            for alert in evaluate_alerts(config, prev, &current, &diff) {
+                eprintln!("Alert [{}]: {}", alert.rule, alert.message);
+                if let Err(e) = run_hook(&alert, false) {
+                    eprintln!("Warning: Alert hook failed: {}", e);
+                }
+            }
+            if !diff.is_empty() {
+                label_diff(&mut diff, prev, &current, config);
+                store.append(&HistoryEvent::from_diff(&diff, now_secs()))?;
+                store.save_snapshot(&current)?;
+            }
+        } else {
+            store.save_snapshot(&current)?;
+        }
+        previous = Some(current);
+
+        std::thread::sleep(Duration::from_millis(interval_ms));
+        current = loop {
+            match SysfsParser::new().parse_topology() {
+                Ok(t) => {
+                    config.resolve_topology(&t);
+                    break t;
+                }
+                Err(e) => {
+                    eprintln!("Warning: Failed to read USB topology: {}", e);
+                    std::thread::sleep(Duration::from_millis(interval_ms));
+                }
+            }
+        };
+    }
+}
+
+fn print_history(events: &[HistoryEvent], json: bool) -> Result<()> {
+    if json {
+        for event in events {
+            println!("{}", serde_json::to_string(event)?);
+        }
+        return Ok(());
+    }
+
+    if events.is_empty() {
+        println!("No matching events");
+        return Ok(());
+    }
+
+    for event in events {
+        let mut line = format!("{}  {:<17}", format_timestamp(event.timestamp), event.event);
+        if let Some(key) = &event.key {
+            line.push_str(&format!(" {}", key));
+        }
+        if let Some(name) = &event.name {
+            line.push_str(&format!("  {}", name));
+        }
+        if let Some(path) = &event.path {
+            line.push_str(&format!("  [{}]", path));
+        }
+        if let Some(details) = &event.details {
+            line.push_str(&format!("  {}", details));
+        }
+        println!("{}", line);
+    }
+    Ok(())
+}
+
+fn run_tui(
+    topology: usbbw::UsbTopology,
+    config: Config,
+    event_log: Option<PathBuf>,
+    label_file: PathBuf,
+    config_path: Option<&Path>,
+) -> Result<()> {
     // Initialize terminal
     enable_raw_mode()?;
     let mut stdout = stdout();
-    execute!(stdout, EnterAlternateScreen)?;
+    execute!(stdout, EnterAlternateScreen, EnableBracketedPaste)?;
+    let mouse = config.settings.mouse;
+    if mouse {
+        execute!(stdout, EnableMouseCapture)?;
+    }
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
     let refresh_ms = config.settings.refresh_ms;
     let mut app = App::new(topology, config);
 
+    let result = event_loop(
+        &mut terminal,
+        &mut app,
+        refresh_ms,
+        &label_file,
+        config_path,
+    );
+
+    // Restore terminal
+    let restored = restore_terminal(&mut terminal, mouse);
+
+    // Dump the session's event log, readable with `usbbw history --history FILE`,
+    // even when the loop failed (a write error must not hide the loop's error)
+    if let Some(path) = event_log {
+        match HistoryStore::new(path.clone()).append(&app.event_log) {
+            Ok(()) => eprintln!(
+                "Event log written to {} ({} events)",
+                path.display(),
+                app.event_log.len()
+            ),
+            Err(e) => eprintln!(
+                "Warning: Failed to write event log {}: {}",
+                path.display(),
+                e
+            ),
+        }
+    }
+
+    result.and(restored)
+}
+
+/// Leave raw mode and the alternate screen.
+fn restore_terminal(terminal: &mut Terminal<CrosstermBackend<Stdout>>, mouse: bool) -> Result<()> {
+    disable_raw_mode()?;
+    if mouse {
+        execute!(terminal.backend_mut(), DisableMouseCapture)?;
+    }
+    execute!(
+        terminal.backend_mut(),
+        DisableBracketedPaste,
+        LeaveAlternateScreen
+    )?;
+    Ok(())
+}
+
+/// Draw, handle input and refresh until the user quits.
+fn event_loop(
+    terminal: &mut Terminal<CrosstermBackend<Stdout>>,
+    app: &mut App,
+    refresh_ms: u64,
+    label_file: &Path,
+    config_path: Option<&Path>,
+) -> Result<()> {
     loop {
-        terminal.draw(|f| render(f, &app))?;
+        let size = terminal.size()?;
+        let layout =
+            ScreenLayout::new(Rect::new(0, 0, size.width, size.height), app.show_event_log);
+        app.fit_scroll(layout.list_rows());
+        terminal.draw(|f| render(f, app))?;
 
         // Poll for events with timeout for auto-refresh
         if event::poll(Duration::from_millis(100))?
-            && let Event::Key(key) = event::read()?
-            && key.kind == KeyEventKind::Press
+            && let Some(key) = next_key(app, event::read()?, &layout)
         {
             // Handle edit mode separately
             if app.edit_mode.is_some() {
@@ -518,18 +1375,24 @@ fn run_tui(topology: usbbw::UsbTopology, config: Config) -> Result<()> {
                     KeyCode::Esc => {
                         app.cancel_edit();
                     }
-                    KeyCode::Backspace => {
+                    KeyCode::Tab => app.cycle_edit_target(true),
+                    KeyCode::BackTab => app.cycle_edit_target(false),
+                    _ => {
                         if let Some(edit) = &mut app.edit_mode {
-                            edit.input.pop();
-                            edit.cursor = edit.input.len();
-                        }
-                    }
-                    KeyCode::Char(c) => {
-                        if let Some(edit) = &mut app.edit_mode {
-                            edit.input.push(c);
-                            edit.cursor = edit.input.len();
+                            edit.input.handle_key(key);
                         }
                     }
+                }
+                continue;
+            }
+
+            // Search prompt
+            if app.search_input.is_some() {
+                match key.code {
+                    KeyCode::Enter => app.confirm_search(),
+                    KeyCode::Esc => app.cancel_search(),
+                    KeyCode::Backspace => app.search_pop(),
+                    KeyCode::Char(c) => app.search_push(c),
                     _ => {}
                 }
                 continue;
@@ -543,7 +1406,39 @@ fn run_tui(topology: usbbw::UsbTopology, config: Config) -> Result<()> {
             }
 
             match key.code {
-                KeyCode::Char('q') => break,
+                KeyCode::Char('q') => return Ok(()),
+                // Endpoint drill-down: scroll, or go back to the tree
+                KeyCode::Char('j') | KeyCode::Down if app.view_mode == ViewMode::Endpoints => {
+                    app.scroll_endpoints(1);
+                }
+                KeyCode::Char('k') | KeyCode::Up if app.view_mode == ViewMode::Endpoints => {
+                    app.scroll_endpoints(-1);
+                }
+                KeyCode::Enter | KeyCode::Esc | KeyCode::Backspace
+                    if app.view_mode == ViewMode::Endpoints && !app.show_help =>
+                {
+                    app.close_endpoints();
+                }
+                // Port map: move between ports, or go back to the tree
+                KeyCode::Char('j') | KeyCode::Down if app.view_mode == ViewMode::Ports => {
+                    app.move_port_selection(1);
+                }
+                KeyCode::Char('k') | KeyCode::Up if app.view_mode == ViewMode::Ports => {
+                    app.move_port_selection(-1);
+                }
+                KeyCode::Esc | KeyCode::Backspace
+                    if app.view_mode == ViewMode::Ports && !app.show_help =>
+                {
+                    app.close_ports();
+                }
+                KeyCode::Char('p') => {
+                    app.open_ports();
+                }
+                KeyCode::Enter
+                    if app.view_mode == ViewMode::Tree && app.selected_device.is_some() =>
+                {
+                    app.open_endpoints();
+                }
                 KeyCode::Char('j') | KeyCode::Down => {
                     app.move_selection(1);
                 }
@@ -582,15 +1477,17 @@ fn run_tui(topology: usbbw::UsbTopology, config: Config) -> Result<()> {
                     // Toggle bandwidth bars
                     app.toggle_bandwidth_bars();
                 }
+                KeyCode::Char('l') => {
+                    // Toggle event log pane
+                    app.toggle_event_log();
+                }
                 KeyCode::Char('x') => {
                     // Toggle expand all / collapse all
                     app.toggle_expand_all();
                 }
                 KeyCode::Char('e') => {
-                    // Edit label for selected device
-                    if app.selected_device.is_some() {
-                        app.start_edit();
-                    }
+                    // Edit label for the selection (device, bus, controller or port)
+                    app.start_edit();
                 }
                 KeyCode::Char('m') => {
                     // Mark selected device as seen
@@ -598,32 +1495,54 @@ fn run_tui(topology: usbbw::UsbTopology, config: Config) -> Result<()> {
                         app.mark_seen(&path.0.clone());
                     }
                 }
-                KeyCode::Char('w') => {
+                KeyCode::Char('w') if app.pending_label_count() > 0 => {
                     // Write pending labels to config
-                    if app.pending_label_count() > 0 {
-                        match write_pending_labels(&app) {
-                            Ok(path) => {
-                                let count = app.pending_label_count();
-                                // Merge pending labels into config so they persist in display
-                                for (key, label) in app.pending_labels.drain() {
-                                    app.config.products.insert(key, label);
-                                }
-                                app.set_status(format!(
-                                    "Wrote {} label(s) to {}",
-                                    count,
-                                    path.display()
-                                ));
+                    match write_pending_labels(app, label_file, config_path) {
+                        Ok(shadowed) => {
+                            let count = app.pending_label_count();
+                            // Merge pending labels into config so they persist in display
+                            for (target, label) in std::mem::take(&mut app.pending_labels) {
+                                app.config.set_label(&target, label);
                             }
-                            Err(e) => {
-                                app.set_status(format!("Error writing config: {}", e));
+                            let mut status =
+                                format!("Wrote {} label(s) to {}", count, label_file.display());
+                            if let Some((target, source)) = shadowed.first() {
+                                status.push_str(&format!(
+                                    "; {} label(s) overridden, e.g. {} by {}",
+                                    shadowed.len(),
+                                    target,
+                                    source.display()
+                                ));
                             }
+                            app.set_status(status);
+                        }
+                        Err(e) => {
+                            app.set_status(format!("Error writing config: {}", e));
                         }
                     }
                 }
-                KeyCode::Esc => {
-                    if app.show_help {
-                        app.show_help = false;
-                    }
+                KeyCode::Esc if app.show_help => {
+                    app.show_help = false;
+                }
+                KeyCode::Char('/') => {
+                    app.start_search();
+                }
+                KeyCode::Char('n') => {
+                    app.next_match(true);
+                }
+                KeyCode::Char('N') => {
+                    app.next_match(false);
+                }
+                KeyCode::Char('f') => {
+                    // Limit tree to search matches
+                    app.toggle_filter();
+                }
+                KeyCode::Char('F') => {
+                    // Cycle quick filters: periodic, unconfigured, new, off
+                    app.cycle_quick_filter();
+                }
+                KeyCode::Esc if app.filter_active() => {
+                    app.clear_filters();
                 }
                 KeyCode::PageUp | KeyCode::Char('K') => {
                     app.scroll_details_up();
@@ -642,76 +1561,60 @@ fn run_tui(topology: usbbw::UsbTopology, config: Config) -> Result<()> {
                 app.update_topology(new_topology);
             }
         }
-    }
-
-    // Restore terminal
-    disable_raw_mode()?;
-    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
 
-    Ok(())
-}
-
-/// Write pending labels to the user's config file.
-fn write_pending_labels(app: &App) -> Result<std::path::PathBuf> {
-    use std::fs;
-    use std::io::Write;
-
-    // Determine config path (prefer user config dir)
-    let config_dir = dirs::config_dir()
-        .map(|d| d.join("usbbw"))
-        .unwrap_or_else(|| std::path::PathBuf::from("."));
-    let config_path = config_dir.join("config.toml");
-
-    // Ensure directory exists
-    fs::create_dir_all(&config_dir)?;
-
-    // Read existing config or create new
-    let mut content = if config_path.exists() {
-        fs::read_to_string(&config_path)?
-    } else {
-        String::from("# usbbw configuration\n\n")
-    };
-
-    // Check if [products] section exists
-    let has_products_section = content.contains("[products]");
-
-    if !has_products_section {
-        content.push_str("\n[products]\n");
-    }
-
-    // Append new product labels
-    // Find the end of the [products] section or end of file
-    let insert_pos = if has_products_section {
-        // Find position after [products] line
-        if let Some(pos) = content.find("[products]") {
-            // Find next section or end of file
-            let after_products = &content[pos + 10..];
-            if let Some(next_section) = after_products.find("\n[") {
-                pos + 10 + next_section
+        for alert in app.take_alerts() {
+            if let Err(e) = run_hook(&alert, true) {
+                app.set_status(format!("Alert hook failed: {}", e));
             } else {
-                content.len()
+                app.set_status(format!("Alert: {}", alert.message));
+            }
+            if alert.notify {
+                let backend = terminal.backend_mut();
+                backend.write_all(notification_sequence(&alert).as_bytes())?;
+                Write::flush(backend)?;
             }
-        } else {
-            content.len()
         }
-    } else {
-        content.len()
-    };
-
-    // Build new entries (VID:PID:iSerial or VID:PID)
-    let mut new_entries = String::new();
-    for (product_key, label) in &app.pending_labels {
-        // Escape the label for TOML
-        let escaped = label.replace('\\', "\\\\").replace('"', "\\\"");
-        new_entries.push_str(&format!("\"{}\" = \"{}\"\n", product_key, escaped));
     }
+}
 
-    // Insert at the right position
-    content.insert_str(insert_pos, &new_entries);
-
-    // Write back
-    let mut file = fs::File::create(&config_path)?;
-    file.write_all(content.as_bytes())?;
+/// Turn a terminal event into a key press, applying mouse events to the app.
+///
+/// Clicking a footer hint is reported as its key.
+fn next_key(app: &mut App, event: Event, layout: &ScreenLayout) -> Option<KeyEvent> {
+    match event {
+        Event::Key(key) if key.kind == KeyEventKind::Press => Some(key),
+        Event::Mouse(mouse) => {
+            handle_mouse(app, mouse, layout).map(|code| KeyEvent::new(code, KeyModifiers::NONE))
+        }
+        Event::Paste(text) => {
+            app.paste(&text);
+            None
+        }
+        _ => None,
+    }
+}
 
-    Ok(config_path)
+/// Write the pending labels to `path`, returning those another config file
+/// overrides (re-reading the config as loaded from `config_path`).
+fn write_pending_labels(
+    app: &App,
+    path: &Path,
+    config_path: Option<&Path>,
+) -> Result<Vec<(LabelTarget, PathBuf)>> {
+    let mut editor = ConfigEditor::open(path)?;
+
+    // Sorted for stable output
+    let mut pending: Vec<_> = app.pending_labels.iter().collect();
+    pending.sort_by_key(|(target, _)| (target.table(), target.key()));
+    for (target, label) in pending {
+        editor.set_label(target, label)?;
+    }
+    editor.save()?;
+
+    let stack = Config::discover(config_path)?;
+    Ok(shadowed_labels(
+        &stack.layers,
+        &path.canonicalize()?,
+        app.pending_labels.keys(),
+    ))
 }
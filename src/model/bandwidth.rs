@@ -1,6 +1,15 @@
 //! Bandwidth pool calculations and formatting.
 
+use super::endpoint::Endpoint;
 use super::speed::UsbSpeed;
+use super::topology::UsbDevice;
+use super::units::Units;
+
+/// Default usage percentage above which a pool counts as high usage.
+pub const DEFAULT_HIGH_USAGE_PERCENT: f64 = 80.0;
+
+/// Default usage percentage above which a pool counts as critical.
+pub const DEFAULT_CRITICAL_USAGE_PERCENT: f64 = 95.0;
 
 /// Bandwidth pool for a bus.
 #[derive(Debug, Clone)]
@@ -49,14 +58,19 @@ impl BandwidthPool {
         self.max_periodic_bps.saturating_sub(self.used_periodic_bps)
     }
 
-    /// Check if bandwidth pool is near capacity (>80%).
+    /// Check if periodic usage is above a threshold percentage.
+    pub fn exceeds(&self, percent: f64) -> bool {
+        self.periodic_usage_percent() > percent
+    }
+
+    /// Check if bandwidth pool is near capacity (default threshold, >80%).
     pub fn is_high_usage(&self) -> bool {
-        self.periodic_usage_percent() > 80.0
+        self.exceeds(DEFAULT_HIGH_USAGE_PERCENT)
     }
 
-    /// Check if bandwidth pool is critical (>95%).
+    /// Check if bandwidth pool is critical (default threshold, >95%).
     pub fn is_critical(&self) -> bool {
-        self.periodic_usage_percent() > 95.0
+        self.exceeds(DEFAULT_CRITICAL_USAGE_PERCENT)
     }
 
     /// Add usage to the pool.
@@ -64,45 +78,19 @@ impl BandwidthPool {
         self.used_periodic_bps = self.used_periodic_bps.saturating_add(bps);
     }
 
-    /// Format used bandwidth as string.
-    pub fn format_used(&self) -> String {
-        format_bps(self.used_periodic_bps)
-    }
-
-    /// Format max bandwidth as string.
-    pub fn format_max(&self) -> String {
-        format_bps(self.max_periodic_bps)
-    }
-
-    /// Format available bandwidth as string.
-    pub fn format_available(&self) -> String {
-        format_bps(self.available_periodic_bps())
+    /// Format used bandwidth in the given units.
+    pub fn format_used(&self, units: Units) -> String {
+        units.format(self.used_periodic_bps, self.speed)
     }
-}
 
-/// Format bits per second as human-readable string.
-pub fn format_bps(bps: u64) -> String {
-    if bps >= 1_000_000_000 {
-        format!("{:.2} Gbps", bps as f64 / 1_000_000_000.0)
-    } else if bps >= 1_000_000 {
-        format!("{:.2} Mbps", bps as f64 / 1_000_000.0)
-    } else if bps >= 1_000 {
-        format!("{:.2} Kbps", bps as f64 / 1_000.0)
-    } else {
-        format!("{} bps", bps)
+    /// Format max bandwidth in the given units.
+    pub fn format_max(&self, units: Units) -> String {
+        units.format(self.max_periodic_bps, self.speed)
     }
-}
 
-/// Format bytes as human-readable string.
-pub fn format_bytes(bytes: u64) -> String {
-    if bytes >= 1_073_741_824 {
-        format!("{:.2} GB", bytes as f64 / 1_073_741_824.0)
-    } else if bytes >= 1_048_576 {
-        format!("{:.2} MB", bytes as f64 / 1_048_576.0)
-    } else if bytes >= 1024 {
-        format!("{:.2} KB", bytes as f64 / 1024.0)
-    } else {
-        format!("{} B", bytes)
+    /// Format available bandwidth in the given units.
+    pub fn format_available(&self, units: Units) -> String {
+        units.format(self.available_periodic_bps(), self.speed)
     }
 }
 
@@ -124,9 +112,71 @@ pub fn simple_bar(percent: f64, width: usize) -> String {
     format!("{}{}", "▓".repeat(filled), "░".repeat(empty))
 }
 
+/// Generate a sparkline of the last `width` values, scaled to `max`.
+pub fn sparkline(values: &[f64], max: f64, width: usize) -> String {
+    const LEVELS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    let start = values.len().saturating_sub(width);
+    values[start..]
+        .iter()
+        .map(|v| {
+            let level = if max > 0.0 {
+                ((v / max) * 7.0).round().clamp(0.0, 7.0) as usize
+            } else {
+                0
+            };
+            LEVELS[level]
+        })
+        .collect()
+}
+
+/// A device's periodic endpoints measured against its bus budget.
+#[derive(Debug, Clone, Copy)]
+pub struct EndpointShares {
+    /// Periodic budget of the bus (bps); 0 if the bus is unknown.
+    pub budget_bps: u64,
+    /// Bandwidth of the largest periodic endpoint (bps).
+    pub largest_bps: u64,
+    /// Number of periodic endpoints.
+    pub periodic_count: usize,
+    speed: UsbSpeed,
+}
+
+impl EndpointShares {
+    /// Shares of `device`'s endpoints on a bus running at `bus_speed`.
+    pub fn new(device: &UsbDevice, bus_speed: Option<UsbSpeed>) -> Self {
+        let periodic = device.periodic_endpoints();
+        Self {
+            budget_bps: bus_speed.map_or(0, |s| s.max_periodic_bandwidth_bps()),
+            largest_bps: periodic
+                .iter()
+                .map(|ep| ep.bandwidth_bps(device.speed))
+                .max()
+                .unwrap_or(0),
+            periodic_count: periodic.len(),
+            speed: device.speed,
+        }
+    }
+
+    /// Percentage of the bus budget taken by `bps`.
+    pub fn percent(&self, bps: u64) -> f64 {
+        if self.budget_bps == 0 {
+            return 0.0;
+        }
+        bps as f64 / self.budget_bps as f64 * 100.0
+    }
+
+    /// Whether `endpoint` is the device's largest consumer, worth pointing
+    /// out when it has more than one periodic endpoint.
+    pub fn is_largest(&self, endpoint: &Endpoint) -> bool {
+        let bps = endpoint.bandwidth_bps(self.speed);
+        self.periodic_count > 1 && bps > 0 && bps == self.largest_bps
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::model::{Direction, TransferType};
 
     #[test]
     fn test_bandwidth_pool() {
@@ -144,11 +194,10 @@ mod tests {
     }
 
     #[test]
-    fn test_format_bps() {
-        assert_eq!(format_bps(500), "500 bps");
-        assert_eq!(format_bps(1500), "1.50 Kbps");
-        assert_eq!(format_bps(1_500_000), "1.50 Mbps");
-        assert_eq!(format_bps(1_500_000_000), "1.50 Gbps");
+    fn test_sparkline() {
+        assert_eq!(sparkline(&[0.0, 50.0, 100.0], 100.0, 10), "▁▅█");
+        assert_eq!(sparkline(&[0.0, 50.0, 100.0], 100.0, 2), "▅█");
+        assert_eq!(sparkline(&[3.0], 0.0, 5), "▁");
     }
 
     #[test]
@@ -157,4 +206,43 @@ mod tests {
         assert_eq!(bandwidth_bar(50.0, 10), "[█████░░░░░]");
         assert_eq!(bandwidth_bar(100.0, 10), "[██████████]");
     }
+
+    #[test]
+    fn test_endpoint_shares() {
+        let endpoint = |address, transfer_type, max_packet_size| Endpoint {
+            address,
+            transfer_type,
+            direction: Direction::In,
+            max_packet_size,
+            b_interval: 1,
+            interval_str: "125us".to_string(),
+            interface: 0,
+        };
+        let device = UsbDevice {
+            endpoints: vec![
+                endpoint(0x81, TransferType::Interrupt, 64),
+                endpoint(0x82, TransferType::Isochronous, 1024),
+                endpoint(0x03, TransferType::Bulk, 512),
+            ],
+            ..UsbDevice::fixture("1-1", 0x046d, 0x0825)
+        };
+        let shares = EndpointShares::new(&device, Some(UsbSpeed::High));
+        assert_eq!(shares.budget_bps, 384_000_000);
+        assert_eq!(shares.periodic_count, 2);
+        // 1024 bytes every microframe
+        assert_eq!(shares.largest_bps, 65_536_000);
+        assert!((shares.percent(38_400_000) - 10.0).abs() < 0.01);
+        assert!(shares.is_largest(&device.endpoints[1]));
+        assert!(!shares.is_largest(&device.endpoints[0]));
+        assert!(!shares.is_largest(&device.endpoints[2]));
+
+        // A lone periodic endpoint is not singled out; unknown bus has no budget
+        let single = UsbDevice {
+            endpoints: vec![device.endpoints[1].clone()],
+            ..device
+        };
+        let shares = EndpointShares::new(&single, None);
+        assert!(!shares.is_largest(&single.endpoints[0]));
+        assert_eq!(shares.percent(1_000), 0.0);
+    }
 }
@@ -0,0 +1,294 @@
+//! Physical port map: root ports grouped by their chassis location.
+//!
+//! Locations come from ACPI `_PLD` data exposed as `physical_location` on
+//! root hub ports (or on the device plugged into them). The USB 2.0 and
+//! USB 3.x root ports behind one connector report the same location, so they
+//! are merged into a single [`PhysicalPort`].
+
+use super::topology::{ControllerId, DevicePath, PhysicalLocation, UsbBus, UsbTopology};
+
+/// Side of the chassis a port is on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Side {
+    Back,
+    Left,
+    Right,
+    Front,
+    Top,
+    Bottom,
+    Lid,
+    Dock,
+    Unknown,
+}
+
+impl Side {
+    /// Side for an ACPI location (dock and lid take precedence over the panel).
+    pub fn from_location(loc: &PhysicalLocation) -> Self {
+        if loc.dock {
+            return Self::Dock;
+        }
+        if loc.lid {
+            return Self::Lid;
+        }
+        match loc.panel.as_str() {
+            "left" => Self::Left,
+            "right" => Self::Right,
+            "front" => Self::Front,
+            "back" => Self::Back,
+            "top" => Self::Top,
+            "bottom" => Self::Bottom,
+            _ => Self::Unknown,
+        }
+    }
+
+    /// Human-readable name.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Back => "Back",
+            Self::Left => "Left",
+            Self::Right => "Right",
+            Self::Front => "Front",
+            Self::Top => "Top",
+            Self::Bottom => "Bottom",
+            Self::Lid => "Lid",
+            Self::Dock => "Dock",
+            Self::Unknown => "Unknown side",
+        }
+    }
+}
+
+/// A root hub port backing a physical connector.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RootPort {
+    pub bus_num: u8,
+    pub port_num: u8,
+}
+
+/// A connector on the chassis.
+#[derive(Debug, Clone)]
+pub struct PhysicalPort {
+    /// ACPI location shared by the backing root ports.
+    pub location: PhysicalLocation,
+    /// Controller the root ports belong to.
+    pub controller_id: ControllerId,
+    /// Root ports behind the connector (one per bus).
+    pub root_ports: Vec<RootPort>,
+    /// Devices plugged directly into the root ports.
+    pub devices: Vec<DevicePath>,
+}
+
+impl PhysicalPort {
+    /// Side of the chassis.
+    pub fn side(&self) -> Side {
+        Side::from_location(&self.location)
+    }
+
+    /// Bus numbers of the backing root ports.
+    pub fn bus_nums(&self) -> Vec<u8> {
+        self.root_ports.iter().map(|p| p.bus_num).collect()
+    }
+
+    /// Periodic bandwidth used by everything behind the connector.
+    pub fn periodic_bandwidth_bps(&self, topology: &UsbTopology) -> u64 {
+        self.devices
+            .iter()
+            .filter_map(|root| {
+                let bus = topology.buses.get(&root.bus_num()?)?;
+                Some(subtree_bandwidth_bps(bus, root))
+            })
+            .sum()
+    }
+}
+
+fn subtree_bandwidth_bps(bus: &UsbBus, root: &DevicePath) -> u64 {
+    let prefix = format!("{}.", root.0);
+    bus.devices
+        .values()
+        .filter(|d| d.path == *root || d.path.0.starts_with(&prefix))
+        .map(|d| d.periodic_bandwidth_bps())
+        .sum()
+}
+
+/// Is the location specific enough to place on the chassis?
+fn is_known(loc: &PhysicalLocation) -> bool {
+    loc.dock
+        || loc.lid
+        || [&loc.panel, &loc.vertical_position, &loc.horizontal_position]
+            .iter()
+            .any(|v| !v.is_empty() && v.as_str() != "unknown")
+}
+
+fn position_rank(value: &str) -> u8 {
+    match value {
+        "upper" | "left" => 0,
+        "center" => 1,
+        "lower" | "right" => 2,
+        _ => 3,
+    }
+}
+
+/// Collect the physical ports of a topology, ordered by side and position.
+///
+/// Root ports without a known location are skipped.
+pub fn physical_ports(topology: &UsbTopology) -> Vec<PhysicalPort> {
+    let mut ports: Vec<PhysicalPort> = Vec::new();
+
+    for bus in topology.buses_sorted() {
+        // Root ports from the hub, plus devices whose port wasn't enumerated
+        let mut root_ports: Vec<(u8, Option<PhysicalLocation>)> = bus
+            .ports
+            .iter()
+            .map(|p| (p.port_num, p.physical_location.clone()))
+            .collect();
+        for device in bus.devices.values().filter(|d| d.path.depth() == 0) {
+            let port_num = device.path.port_path().and_then(|p| p.parse::<u8>().ok());
+            if let Some(port_num) = port_num
+                && !root_ports.iter().any(|(n, _)| *n == port_num)
+            {
+                root_ports.push((port_num, None));
+            }
+        }
+        root_ports.sort_by_key(|(n, _)| *n);
+
+        for (port_num, location) in root_ports {
+            let device_path = DevicePath::new(format!("{}-{}", bus.bus_num, port_num));
+            let device = bus.devices.get(&device_path);
+            let Some(location) =
+                location.or_else(|| device.and_then(|d| d.physical_location.clone()))
+            else {
+                continue;
+            };
+            if !is_known(&location) {
+                continue;
+            }
+
+            let root_port = RootPort {
+                bus_num: bus.bus_num,
+                port_num,
+            };
+            // Merge with the peer port of the same connector on the other bus
+            let peer = ports.iter_mut().find(|p| {
+                p.controller_id == bus.controller_id
+                    && p.location == location
+                    && !p.root_ports.iter().any(|r| r.bus_num == bus.bus_num)
+            });
+            let port = match peer {
+                Some(port) => port,
+                None => {
+                    ports.push(PhysicalPort {
+                        location,
+                        controller_id: bus.controller_id.clone(),
+                        root_ports: Vec::new(),
+                        devices: Vec::new(),
+                    });
+                    ports.last_mut().expect("just pushed")
+                }
+            };
+            port.root_ports.push(root_port);
+            if device.is_some() {
+                port.devices.push(device_path);
+            }
+        }
+    }
+
+    ports.sort_by(|a, b| {
+        (
+            a.side(),
+            position_rank(&a.location.vertical_position),
+            position_rank(&a.location.horizontal_position),
+            a.root_ports.first().map(|r| (r.bus_num, r.port_num)),
+        )
+            .cmp(&(
+                b.side(),
+                position_rank(&b.location.vertical_position),
+                position_rank(&b.location.horizontal_position),
+                b.root_ports.first().map(|r| (r.bus_num, r.port_num)),
+            ))
+    });
+    ports
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::PortInfo;
+
+    fn location(panel: &str, vertical: &str) -> PhysicalLocation {
+        PhysicalLocation {
+            panel: panel.to_string(),
+            vertical_position: vertical.to_string(),
+            horizontal_position: "center".to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn bus(bus_num: u8, ports: Vec<(u8, PhysicalLocation)>) -> UsbBus {
+        UsbBus {
+            num_ports: ports.len() as u8,
+            ports: ports
+                .into_iter()
+                .map(|(port_num, loc)| PortInfo {
+                    port_num,
+                    physical_location: Some(loc),
+                    ..Default::default()
+                })
+                .collect(),
+            ..UsbBus::fixture(bus_num, vec![])
+        }
+    }
+
+    #[test]
+    fn test_peer_ports_merged() {
+        let mut topology = UsbTopology::new();
+        topology.buses.insert(
+            3,
+            bus(
+                3,
+                vec![
+                    (1, location("left", "upper")),
+                    (2, location("right", "upper")),
+                    (3, PhysicalLocation::default()),
+                ],
+            ),
+        );
+        topology.buses.insert(
+            4,
+            bus(
+                4,
+                vec![
+                    (1, location("left", "upper")),
+                    (2, location("left", "lower")),
+                ],
+            ),
+        );
+
+        let ports = physical_ports(&topology);
+        let summary: Vec<_> = ports
+            .iter()
+            .map(|p| {
+                (
+                    p.side(),
+                    p.location.vertical_position.as_str(),
+                    p.bus_nums(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (Side::Left, "upper", vec![3, 4]),
+                (Side::Left, "lower", vec![4]),
+                (Side::Right, "upper", vec![3]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_side_from_location() {
+        assert_eq!(Side::from_location(&location("back", "")), Side::Back);
+        let mut docked = location("left", "");
+        docked.dock = true;
+        assert_eq!(Side::from_location(&docked), Side::Dock);
+        assert_eq!(Side::from_location(&location("unknown", "")), Side::Unknown);
+    }
+}
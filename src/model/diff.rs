@@ -0,0 +1,450 @@
+//! Semantic diff between two USB topologies.
+//!
+//! Devices are matched by their config key (VID:PID[:iSerial]) and physical
+//! port (controller + bus role + port path) rather than by sysfs path, so a
+//! shift in bus numbering between boots does not show up as a change.
+
+use super::speed::UsbSpeed;
+use super::topology::{DevicePath, UsbDevice, UsbTopology};
+use serde::Serialize;
+use std::fmt;
+
+/// Bus-number-independent location of a device.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DeviceLocation {
+    /// Controller PCI address (or controller ID if no PCI address is known).
+    pub controller: String,
+    /// Is the device on the USB 3.x bus of its controller?
+    pub superspeed: bool,
+    /// Port path within the bus (e.g., "1.2").
+    pub port_path: String,
+}
+
+impl DeviceLocation {
+    /// Determine the location of a device within a topology.
+    pub fn of(topology: &UsbTopology, device: &UsbDevice) -> Self {
+        let (controller, superspeed) = device
+            .path
+            .bus_num()
+            .map(|n| bus_role(topology, n))
+            .unwrap_or_default();
+
+        Self {
+            controller,
+            superspeed,
+            port_path: device.path.port_path().unwrap_or_default().to_string(),
+        }
+    }
+}
+
+/// Controller identity and USB 3.x role of a bus.
+fn bus_role(topology: &UsbTopology, bus_num: u8) -> (String, bool) {
+    let controller = topology
+        .get_controller_for_bus(bus_num)
+        .map(|c| {
+            if c.pci_address.is_empty() {
+                c.id.0.clone()
+            } else {
+                c.pci_address.clone()
+            }
+        })
+        .unwrap_or_default();
+    let superspeed = topology
+        .buses
+        .get(&bus_num)
+        .map(|b| b.is_superspeed())
+        .unwrap_or(false);
+    (controller, superspeed)
+}
+
+impl fmt::Display for DeviceLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let role = if self.superspeed { "usb3" } else { "usb2" };
+        write!(f, "{}/{}:{}", self.controller, role, self.port_path)
+    }
+}
+
+/// Kind of change detected for a device.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "change", rename_all = "snake_case")]
+pub enum ChangeKind {
+    /// Device present only in the new topology.
+    Added,
+    /// Device present only in the old topology.
+    Removed,
+    /// Device moved to a different physical port.
+    Moved {
+        from: DeviceLocation,
+        to: DeviceLocation,
+    },
+    /// Device disconnected and re-enumerated at the same port.
+    Reenumerated,
+    /// Negotiated speed changed.
+    SpeedChanged { from: UsbSpeed, to: UsbSpeed },
+    /// Reserved periodic bandwidth changed.
+    BandwidthChanged { from_bps: u64, to_bps: u64 },
+    /// Device went from unconfigured to configured.
+    Configured,
+    /// Device went from configured to unconfigured (bandwidth allocation failed).
+    Unconfigured,
+}
+
+impl ChangeKind {
+    /// Short name for display and filtering.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Added => "added",
+            Self::Removed => "removed",
+            Self::Moved { .. } => "moved",
+            Self::Reenumerated => "reenumerated",
+            Self::SpeedChanged { .. } => "speed_changed",
+            Self::BandwidthChanged { .. } => "bandwidth_changed",
+            Self::Configured => "configured",
+            Self::Unconfigured => "unconfigured",
+        }
+    }
+}
+
+/// A single change for a device.
+#[derive(Debug, Clone, Serialize)]
+pub struct DeviceChange {
+    /// Config key (VID:PID:iSerial or VID:PID).
+    pub key: String,
+    /// Display name (label > product > manufacturer > VID:PID).
+    pub name: String,
+    /// Device path in the old topology (if present).
+    pub old_path: Option<DevicePath>,
+    /// Device path in the new topology (if present).
+    pub new_path: Option<DevicePath>,
+    /// What changed.
+    #[serde(flatten)]
+    pub kind: ChangeKind,
+}
+
+impl DeviceChange {
+    /// Most relevant path: the new one if present, otherwise the old one.
+    pub fn path(&self) -> Option<&DevicePath> {
+        self.new_path.as_ref().or(self.old_path.as_ref())
+    }
+}
+
+/// Increase of the over-current counter on a root hub port.
+#[derive(Debug, Clone, Serialize)]
+pub struct OverCurrentChange {
+    /// Controller PCI address (or controller ID).
+    pub controller: String,
+    /// Is the port on the USB 3.x bus of its controller?
+    pub superspeed: bool,
+    /// Bus number in the new topology.
+    pub bus_num: u8,
+    /// Root hub port number.
+    pub port_num: u8,
+    /// Previous over-current count.
+    pub from_count: u32,
+    /// Current over-current count.
+    pub to_count: u32,
+}
+
+/// Result of comparing two topologies.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TopologyDiff {
+    /// All detected changes, ordered by device key.
+    pub changes: Vec<DeviceChange>,
+    /// Root hub ports whose over-current count went up.
+    pub over_current: Vec<OverCurrentChange>,
+}
+
+impl TopologyDiff {
+    /// True if no changes were found.
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty() && self.over_current.is_empty()
+    }
+}
+
+/// Compare two topologies and report per-device changes.
+///
+/// Matching happens in two passes: first on identical config key and
+/// location, then on config key alone (sorted by path) for devices that
+/// moved. Remaining devices are reported as added or removed.
+pub fn diff_topologies(old: &UsbTopology, new: &UsbTopology) -> TopologyDiff {
+    let old_devices = collect_devices(old);
+    let new_devices = collect_devices(new);
+
+    let mut old_matched = vec![false; old_devices.len()];
+    let mut new_matched = vec![false; new_devices.len()];
+    let mut pairs = Vec::new();
+
+    // Pass 1: same key at the same location
+    for (ni, (ndev, nloc)) in new_devices.iter().enumerate() {
+        if let Some(oi) = old_devices
+            .iter()
+            .enumerate()
+            .position(|(oi, (odev, oloc))| {
+                !old_matched[oi] && odev.config_key() == ndev.config_key() && oloc == nloc
+            })
+        {
+            old_matched[oi] = true;
+            new_matched[ni] = true;
+            pairs.push((oi, ni));
+        }
+    }
+
+    // Pass 2: same key, different location
+    for (ni, (ndev, _)) in new_devices.iter().enumerate() {
+        if new_matched[ni] {
+            continue;
+        }
+        if let Some(oi) = old_devices
+            .iter()
+            .enumerate()
+            .position(|(oi, (odev, _))| !old_matched[oi] && odev.config_key() == ndev.config_key())
+        {
+            old_matched[oi] = true;
+            new_matched[ni] = true;
+            pairs.push((oi, ni));
+        }
+    }
+
+    let mut changes = Vec::new();
+
+    for (oi, ni) in pairs {
+        let (odev, oloc) = &old_devices[oi];
+        let (ndev, nloc) = &new_devices[ni];
+        let change = |kind| DeviceChange {
+            key: ndev.config_key(),
+            name: ndev.display_name(),
+            old_path: Some(odev.path.clone()),
+            new_path: Some(ndev.path.clone()),
+            kind,
+        };
+
+        if oloc != nloc {
+            changes.push(change(ChangeKind::Moved {
+                from: oloc.clone(),
+                to: nloc.clone(),
+            }));
+        } else if let (Some(old_ms), Some(new_ms)) =
+            (odev.connected_duration_ms, ndev.connected_duration_ms)
+            && new_ms < old_ms
+        {
+            // Connection time went backwards: the device was reset or replugged
+            changes.push(change(ChangeKind::Reenumerated));
+        }
+        if odev.speed != ndev.speed {
+            changes.push(change(ChangeKind::SpeedChanged {
+                from: odev.speed,
+                to: ndev.speed,
+            }));
+        }
+        if odev.is_configured != ndev.is_configured {
+            changes.push(change(if ndev.is_configured {
+                ChangeKind::Configured
+            } else {
+                ChangeKind::Unconfigured
+            }));
+        }
+        let (old_bw, new_bw) = (odev.periodic_bandwidth_bps(), ndev.periodic_bandwidth_bps());
+        if old_bw != new_bw {
+            changes.push(change(ChangeKind::BandwidthChanged {
+                from_bps: old_bw,
+                to_bps: new_bw,
+            }));
+        }
+    }
+
+    for (oi, (odev, _)) in old_devices.iter().enumerate() {
+        if !old_matched[oi] {
+            changes.push(DeviceChange {
+                key: odev.config_key(),
+                name: odev.display_name(),
+                old_path: Some(odev.path.clone()),
+                new_path: None,
+                kind: ChangeKind::Removed,
+            });
+        }
+    }
+
+    for (ni, (ndev, _)) in new_devices.iter().enumerate() {
+        if !new_matched[ni] {
+            let change = |kind| DeviceChange {
+                key: ndev.config_key(),
+                name: ndev.display_name(),
+                old_path: None,
+                new_path: Some(ndev.path.clone()),
+                kind,
+            };
+            changes.push(change(ChangeKind::Added));
+            // Failing to configure on arrival is as notable as losing it later
+            if !ndev.is_configured {
+                changes.push(change(ChangeKind::Unconfigured));
+            }
+        }
+    }
+
+    changes.sort_by(|a, b| {
+        a.key
+            .cmp(&b.key)
+            .then_with(|| a.path().map(|p| &p.0).cmp(&b.path().map(|p| &p.0)))
+    });
+
+    TopologyDiff {
+        changes,
+        over_current: diff_over_current(old, new),
+    }
+}
+
+/// Find root hub ports whose over-current count increased. Ports of buses
+/// that only appear in `new` have no earlier count to compare against.
+fn diff_over_current(old: &UsbTopology, new: &UsbTopology) -> Vec<OverCurrentChange> {
+    let mut old_counts = Vec::new();
+    for bus in old.buses_sorted() {
+        let role = bus_role(old, bus.bus_num);
+        for port in &bus.ports {
+            old_counts.push((role.clone(), port.port_num, port.over_current_count));
+        }
+    }
+
+    let mut result = Vec::new();
+    for bus in new.buses_sorted() {
+        let role = bus_role(new, bus.bus_num);
+        for port in &bus.ports {
+            let Some(from_count) = old_counts
+                .iter()
+                .find(|(r, n, _)| *r == role && *n == port.port_num)
+                .map(|(_, _, c)| *c)
+            else {
+                continue;
+            };
+            if port.over_current_count > from_count {
+                result.push(OverCurrentChange {
+                    controller: role.0.clone(),
+                    superspeed: role.1,
+                    bus_num: bus.bus_num,
+                    port_num: port.port_num,
+                    from_count,
+                    to_count: port.over_current_count,
+                });
+            }
+        }
+    }
+    result
+}
+
+/// Collect all devices with their locations, sorted by path for stable matching.
+fn collect_devices(topology: &UsbTopology) -> Vec<(&UsbDevice, DeviceLocation)> {
+    let mut devices: Vec<_> = topology
+        .buses
+        .values()
+        .flat_map(|bus| bus.devices.values())
+        .map(|d| (d, DeviceLocation::of(topology, d)))
+        .collect();
+    devices.sort_by(|a, b| a.0.path.0.cmp(&b.0.path.0));
+    devices
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::PortInfo;
+
+    fn make_device(path: &str, vendor_id: u16, serial: Option<&str>) -> UsbDevice {
+        UsbDevice {
+            serial: serial.map(String::from),
+            ..UsbDevice::fixture(path, vendor_id, 0x0001)
+        }
+    }
+
+    #[test]
+    fn test_no_changes_when_bus_number_shifts() {
+        let old = UsbTopology::fixture(3, vec![make_device("3-1", 0x0d28, Some("A"))]);
+        let new = UsbTopology::fixture(5, vec![make_device("5-1", 0x0d28, Some("A"))]);
+        assert!(diff_topologies(&old, &new).is_empty());
+    }
+
+    #[test]
+    fn test_added_removed_moved() {
+        let old = UsbTopology::fixture(
+            3,
+            vec![
+                make_device("3-1", 0x0d28, Some("A")),
+                make_device("3-2", 0x1234, None),
+            ],
+        );
+        let new = UsbTopology::fixture(
+            3,
+            vec![
+                make_device("3-3", 0x0d28, Some("A")),
+                make_device("3-4", 0x5678, None),
+            ],
+        );
+
+        let diff = diff_topologies(&old, &new);
+        let kinds: Vec<_> = diff.changes.iter().map(|c| c.kind.name()).collect();
+        assert_eq!(kinds, vec!["moved", "removed", "added"]);
+        assert_eq!(diff.changes[0].new_path, Some(DevicePath::new("3-3")));
+    }
+
+    #[test]
+    fn test_reenumerated() {
+        let mut dev = make_device("3-1", 0x0d28, None);
+        dev.connected_duration_ms = Some(60_000);
+        let old = UsbTopology::fixture(3, vec![dev.clone()]);
+        dev.connected_duration_ms = Some(500);
+        let new = UsbTopology::fixture(3, vec![dev]);
+
+        let diff = diff_topologies(&old, &new);
+        assert_eq!(diff.changes.len(), 1);
+        assert_eq!(diff.changes[0].kind, ChangeKind::Reenumerated);
+    }
+
+    #[test]
+    fn test_over_current_increase() {
+        let mut old = UsbTopology::fixture(3, vec![]);
+        let mut new = UsbTopology::fixture(5, vec![]);
+        for (topology, bus_num, count) in [(&mut old, 3, 1), (&mut new, 5, 3)] {
+            topology
+                .buses
+                .get_mut(&bus_num)
+                .unwrap()
+                .ports
+                .push(PortInfo {
+                    port_num: 2,
+                    over_current_count: count,
+                    ..Default::default()
+                });
+        }
+
+        let diff = diff_topologies(&old, &new);
+        assert_eq!(diff.over_current.len(), 1);
+        assert_eq!(diff.over_current[0].from_count, 1);
+        assert_eq!(diff.over_current[0].to_count, 3);
+
+        // A bus that just appeared has no earlier count to compare against
+        let diff = diff_topologies(&UsbTopology::new(), &new);
+        assert!(diff.over_current.is_empty());
+    }
+
+    #[test]
+    fn test_added_unconfigured() {
+        let old = UsbTopology::fixture(3, vec![]);
+        let mut dev = make_device("3-1", 0x0d28, None);
+        dev.is_configured = false;
+        let new = UsbTopology::fixture(3, vec![dev]);
+
+        let diff = diff_topologies(&old, &new);
+        let kinds: Vec<_> = diff.changes.iter().map(|c| c.kind.name()).collect();
+        assert_eq!(kinds, vec!["added", "unconfigured"]);
+    }
+
+    #[test]
+    fn test_unconfigured_transition() {
+        let old = UsbTopology::fixture(3, vec![make_device("3-1", 0x0d28, None)]);
+        let mut dev = make_device("3-1", 0x0d28, None);
+        dev.is_configured = false;
+        let new = UsbTopology::fixture(3, vec![dev]);
+
+        let diff = diff_topologies(&old, &new);
+        assert_eq!(diff.changes.len(), 1);
+        assert_eq!(diff.changes[0].kind, ChangeKind::Unconfigured);
+    }
+}
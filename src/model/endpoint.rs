@@ -1,10 +1,11 @@
 //! USB endpoint model with bandwidth calculation.
 
 use super::speed::UsbSpeed;
+use serde::{Deserialize, Serialize};
 use std::fmt;
 
 /// USB transfer types.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum TransferType {
     Control,
     Bulk,
@@ -44,7 +45,7 @@ impl fmt::Display for TransferType {
 }
 
 /// Endpoint direction.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Direction {
     In,
     Out,
@@ -71,7 +72,7 @@ impl fmt::Display for Direction {
 }
 
 /// A USB endpoint with bandwidth-relevant attributes.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Endpoint {
     /// Endpoint address (e.g., 0x81 = IN endpoint 1, 0x02 = OUT endpoint 2).
     pub address: u8,
@@ -86,6 +87,9 @@ pub struct Endpoint {
     pub b_interval: u8,
     /// Human-readable interval string from sysfs (e.g., "4ms", "125us").
     pub interval_str: String,
+    /// Number of the interface this endpoint belongs to.
+    #[serde(default)]
+    pub interface: u8,
 }
 
 impl Endpoint {
@@ -112,19 +116,19 @@ impl Endpoint {
     }
 
     /// Extract base packet size (bits 10:0 of wMaxPacketSize).
-    fn base_packet_size(&self) -> u16 {
+    pub fn base_packet_size(&self) -> u16 {
         self.max_packet_size & 0x07FF
     }
 
     /// Extract multiplier from wMaxPacketSize bits 12:11 (for high-speed).
     /// Returns 1, 2, or 3.
-    fn multiplier(&self) -> u16 {
+    pub fn multiplier(&self) -> u16 {
         let mult_bits = (self.max_packet_size >> 11) & 0x03;
         if mult_bits == 0 { 1 } else { mult_bits + 1 }
     }
 
     /// Calculate polling interval in microseconds.
-    fn interval_us(&self, device_speed: UsbSpeed) -> u64 {
+    pub fn interval_us(&self, device_speed: UsbSpeed) -> u64 {
         match device_speed {
             UsbSpeed::Low | UsbSpeed::Full => {
                 // Full/Low speed: bInterval is in milliseconds (1-255).
@@ -182,6 +186,7 @@ mod tests {
             max_packet_size: 64,
             b_interval: 8,
             interval_str: "8ms".to_string(),
+            interface: 0,
         };
 
         // 64 bytes * 8 bits = 512 bits per transfer
@@ -200,6 +205,7 @@ mod tests {
             max_packet_size: 64,
             b_interval: 4, // 2^(4-1) * 125µs = 1000µs = 1ms
             interval_str: "1ms".to_string(),
+            interface: 0,
         };
 
         // 64 bytes * 8 bits = 512 bits per ms = 512 Kbps
@@ -216,6 +222,7 @@ mod tests {
             max_packet_size: 512,
             b_interval: 0,
             interval_str: "0ms".to_string(),
+            interface: 0,
         };
 
         assert_eq!(ep.bandwidth_bps(UsbSpeed::High), 0);
@@ -0,0 +1,57 @@
+//! USB interface model.
+
+use serde::{Deserialize, Serialize};
+
+/// A USB interface of the active configuration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsbInterface {
+    /// Interface number (bInterfaceNumber).
+    pub number: u8,
+    /// Current alternate setting (bAlternateSetting).
+    pub alt_setting: u8,
+    /// Interface class (bInterfaceClass).
+    pub class: u8,
+    /// Interface subclass (bInterfaceSubClass).
+    pub subclass: u8,
+    /// Interface protocol (bInterfaceProtocol).
+    pub protocol: u8,
+    /// Bound kernel driver, if any.
+    pub driver: Option<String>,
+    /// Interface string descriptor, if any.
+    pub name: Option<String>,
+}
+
+impl UsbInterface {
+    /// Human-readable name of the interface class.
+    pub fn class_name(&self) -> &'static str {
+        class_name(self.class)
+    }
+}
+
+/// Human-readable name of a USB class code.
+pub fn class_name(class: u8) -> &'static str {
+    match class {
+        0x01 => "Audio",
+        0x02 => "CDC",
+        0x03 => "HID",
+        0x05 => "Physical",
+        0x06 => "Image",
+        0x07 => "Printer",
+        0x08 => "Mass Storage",
+        0x09 => "Hub",
+        0x0a => "CDC Data",
+        0x0b => "Smart Card",
+        0x0d => "Content Security",
+        0x0e => "Video",
+        0x0f => "Personal Healthcare",
+        0x10 => "Audio/Video",
+        0x11 => "Billboard",
+        0x12 => "USB-C Bridge",
+        0xdc => "Diagnostic",
+        0xe0 => "Wireless",
+        0xef => "Miscellaneous",
+        0xfe => "Application Specific",
+        0xff => "Vendor Specific",
+        _ => "Unknown",
+    }
+}
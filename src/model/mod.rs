@@ -1,14 +1,26 @@
 //! USB data model types.
 
 pub mod bandwidth;
+pub mod chassis;
+pub mod diff;
 pub mod endpoint;
+pub mod interface;
 pub mod speed;
 pub mod topology;
+pub mod units;
 
-pub use bandwidth::{BandwidthPool, format_bps};
+pub use bandwidth::{
+    BandwidthPool, DEFAULT_CRITICAL_USAGE_PERCENT, DEFAULT_HIGH_USAGE_PERCENT, EndpointShares,
+};
+pub use chassis::{PhysicalPort, RootPort, Side, physical_ports};
+pub use diff::{
+    ChangeKind, DeviceChange, DeviceLocation, OverCurrentChange, TopologyDiff, diff_topologies,
+};
 pub use endpoint::{Direction, Endpoint, TransferType};
+pub use interface::UsbInterface;
 pub use speed::UsbSpeed;
 pub use topology::{
     ControllerId, ControllerType, DevicePath, PhysicalLocation, PortInfo, PortState, UsbBus,
-    UsbController, UsbDevice, UsbTopology, format_bandwidth,
+    UsbController, UsbDevice, UsbTopology, key_matches,
 };
+pub use units::{Units, format_bps};
@@ -1,9 +1,10 @@
 //! USB speed enumeration with bandwidth characteristics.
 
+use serde::{Deserialize, Serialize};
 use std::fmt;
 
 /// USB speed variants with bandwidth characteristics.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum UsbSpeed {
     /// USB 1.0 Low Speed - 1.5 Mbps
     Low,
@@ -33,6 +34,20 @@ impl UsbSpeed {
         }
     }
 
+    /// Parse a user-facing speed name ("high", "super_plus", or a short
+    /// name like "480M"/"10G"), case-insensitively.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().replace(['-', ' '], "_").as_str() {
+            "low" | "1.5m" => Some(Self::Low),
+            "full" | "12m" => Some(Self::Full),
+            "high" | "480m" => Some(Self::High),
+            "super" | "5g" => Some(Self::Super),
+            "super_plus" | "10g" => Some(Self::SuperPlus),
+            "super_plus_2" | "super_plus2" | "20g" => Some(Self::SuperPlus2),
+            _ => None,
+        }
+    }
+
     /// Raw bandwidth in bits per second.
     pub fn raw_bandwidth_bps(&self) -> u64 {
         match self {
@@ -119,6 +134,14 @@ mod tests {
         assert_eq!(UsbSpeed::from_mbps(999), None);
     }
 
+    #[test]
+    fn test_from_name() {
+        assert_eq!(UsbSpeed::from_name("High"), Some(UsbSpeed::High));
+        assert_eq!(UsbSpeed::from_name("480M"), Some(UsbSpeed::High));
+        assert_eq!(UsbSpeed::from_name("super-plus"), Some(UsbSpeed::SuperPlus));
+        assert_eq!(UsbSpeed::from_name("fast"), None);
+    }
+
     #[test]
     fn test_bandwidth() {
         assert_eq!(UsbSpeed::High.raw_bandwidth_bps(), 480_000_000);
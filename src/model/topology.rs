@@ -1,11 +1,13 @@
 //! USB topology data structures.
 
 use super::endpoint::Endpoint;
+use super::interface::UsbInterface;
 use super::speed::UsbSpeed;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 /// Unique device identifier: bus-port.port.port...
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct DevicePath(pub String);
 
 impl DevicePath {
@@ -63,7 +65,7 @@ impl std::fmt::Display for DevicePath {
 }
 
 /// Physical location attributes (ACPI-provided on some systems).
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct PhysicalLocation {
     /// Is this in a dock?
     pub dock: bool,
@@ -78,7 +80,7 @@ pub struct PhysicalLocation {
 }
 
 /// USB port state from sysfs.
-#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
 pub enum PortState {
     /// No device connected.
     #[default]
@@ -122,7 +124,7 @@ impl PortState {
 }
 
 /// USB port health information.
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct PortInfo {
     /// Port number (1-based).
     pub port_num: u8,
@@ -132,6 +134,9 @@ pub struct PortInfo {
     pub over_current_count: u32,
     /// Device connected to this port (if any).
     pub device_path: Option<DevicePath>,
+    /// Physical location of the port connector (ACPI _PLD), if reported.
+    #[serde(default)]
+    pub physical_location: Option<PhysicalLocation>,
 }
 
 impl PhysicalLocation {
@@ -158,7 +163,7 @@ impl PhysicalLocation {
 }
 
 /// A USB device (includes hubs).
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UsbDevice {
     /// Sysfs path identifier (e.g., "3-1.2").
     pub path: DevicePath,
@@ -182,6 +187,9 @@ pub struct UsbDevice {
     pub num_ports: Option<u8>,
     /// All endpoints across all interfaces.
     pub endpoints: Vec<Endpoint>,
+    /// Interfaces of the active configuration.
+    #[serde(default)]
+    pub interfaces: Vec<UsbInterface>,
     /// Physical location info (on supported systems).
     pub physical_location: Option<PhysicalLocation>,
     /// Children device paths (for hubs).
@@ -245,10 +253,101 @@ impl UsbDevice {
     }
 }
 
+#[cfg(test)]
+impl UsbDevice {
+    /// Test fixture: a configured high-speed USB 2.0 device with no strings,
+    /// interfaces or endpoints. Override fields with struct update syntax.
+    pub(crate) fn fixture(path: &str, vendor_id: u16, product_id: u16) -> Self {
+        UsbDevice {
+            path: DevicePath::new(path),
+            speed: UsbSpeed::High,
+            vendor_id,
+            product_id,
+            manufacturer: None,
+            product: None,
+            serial: None,
+            device_class: 0,
+            is_hub: false,
+            num_ports: None,
+            endpoints: vec![],
+            interfaces: vec![],
+            physical_location: None,
+            children: vec![],
+            label: None,
+            usb_version: "2.00".to_string(),
+            num_interfaces: 1,
+            max_power_ma: 100,
+            is_configured: true,
+            connected_duration_ms: None,
+            rx_lanes: None,
+        }
+    }
+}
+
+#[cfg(test)]
+impl UsbBus {
+    /// Test fixture: high-speed USB 2.0 bus `bus_num` of controller
+    /// `0000:c1:00.4`, holding `devices`, with no port information.
+    pub(crate) fn fixture(bus_num: u8, devices: Vec<UsbDevice>) -> Self {
+        UsbBus {
+            bus_num,
+            speed: UsbSpeed::High,
+            version: "2.00".to_string(),
+            num_ports: 4,
+            devices: devices.into_iter().map(|d| (d.path.clone(), d)).collect(),
+            controller_id: ControllerId("0000:c1:00.4".to_string()),
+            ports: vec![],
+        }
+    }
+}
+
+#[cfg(test)]
+impl UsbTopology {
+    /// Test fixture: controller `0000:c1:00.4` whose only bus is the USB 2.0
+    /// [`UsbBus::fixture`] `bus_num`, holding `devices`.
+    pub(crate) fn fixture(bus_num: u8, devices: Vec<UsbDevice>) -> Self {
+        let bus = UsbBus::fixture(bus_num, devices);
+        let mut topology = UsbTopology::new();
+        topology.controllers.insert(
+            bus.controller_id.clone(),
+            UsbController {
+                id: bus.controller_id.clone(),
+                pci_address: bus.controller_id.0.clone(),
+                usb2_bus: Some(bus_num),
+                usb3_bus: None,
+                label: None,
+                controller_type: ControllerType::Usb,
+                firmware_path: None,
+            },
+        );
+        topology.buses.insert(bus_num, bus);
+        topology
+    }
+}
+
+/// Check if a device config key matches a key or key prefix, ignoring case
+/// (e.g., "0d28:0204" matches "0d28:0204:ABC" but not "0d28:02041").
+pub fn key_matches(key: &str, pattern: &str) -> bool {
+    let key = key.to_ascii_lowercase();
+    let pattern = pattern.to_ascii_lowercase();
+    key == pattern || key.starts_with(&format!("{}:", pattern))
+}
+
 /// Controller identifier (derived from PCI path or bus number).
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct ControllerId(pub String);
 
+impl ControllerId {
+    /// Whether this is the "busN" fallback used when no PCI address was
+    /// found; it follows enumeration order, so it does not identify the
+    /// controller.
+    pub fn is_bus_fallback(&self) -> bool {
+        self.0
+            .strip_prefix("bus")
+            .is_some_and(|num| num.parse::<u8>().is_ok())
+    }
+}
+
 impl std::fmt::Display for ControllerId {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.0)
@@ -256,7 +355,7 @@ impl std::fmt::Display for ControllerId {
 }
 
 /// Controller type (USB, USB4/Thunderbolt, etc.)
-#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
 pub enum ControllerType {
     #[default]
     Usb,
@@ -274,7 +373,7 @@ impl std::fmt::Display for ControllerType {
 }
 
 /// An xHCI controller with paired USB 2.0 and USB 3.x buses.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UsbController {
     /// Controller identifier.
     pub id: ControllerId,
@@ -288,6 +387,10 @@ pub struct UsbController {
     pub label: Option<String>,
     /// Controller type (USB or USB4/Thunderbolt).
     pub controller_type: ControllerType,
+    /// ACPI/firmware path of the controller (e.g., `\_SB_.PCI0.GP17.XHC0`),
+    /// stable when PCI addresses shift.
+    #[serde(default)]
+    pub firmware_path: Option<String>,
 }
 
 impl UsbController {
@@ -300,7 +403,7 @@ impl UsbController {
 }
 
 /// A USB bus (root hub).
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UsbBus {
     /// Bus number (1-based).
     pub bus_num: u8,
@@ -346,6 +449,11 @@ impl UsbBus {
         self.speed.is_superspeed()
     }
 
+    /// Role of the bus on its controller: "usb2" or "usb3".
+    pub fn role(&self) -> &'static str {
+        if self.is_superspeed() { "usb3" } else { "usb2" }
+    }
+
     /// Get devices in tree order (depth-first from root ports).
     pub fn devices_tree_order(&self) -> Vec<&UsbDevice> {
         let mut result = Vec::new();
@@ -404,7 +512,7 @@ impl UsbBus {
 }
 
 /// Complete USB topology of the system.
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct UsbTopology {
     /// All controllers.
     pub controllers: HashMap<ControllerId, UsbController>,
@@ -437,6 +545,44 @@ impl UsbTopology {
         self.buses.values().map(|b| b.device_count()).sum()
     }
 
+    /// Config keys identifying a bus independently of enumeration order:
+    /// "<controller PCI address>/<role>" and "<controller firmware path>/<role>".
+    /// Controllers without a PCI address only have the firmware path key.
+    pub fn bus_keys(&self, bus_num: u8) -> Vec<String> {
+        let Some(bus) = self.buses.get(&bus_num) else {
+            return Vec::new();
+        };
+        let Some(controller) = self.controllers.get(&bus.controller_id) else {
+            return Vec::new();
+        };
+        [
+            Some(controller.pci_address.as_str())
+                .filter(|pci| !pci.is_empty() && !controller.id.is_bus_fallback()),
+            controller.firmware_path.as_deref(),
+        ]
+        .into_iter()
+        .flatten()
+        .map(|id| format!("{}/{}", id, bus.role()))
+        .collect()
+    }
+
+    /// Physical location of the port a device is plugged into: its own
+    /// `physical_location`, or the root hub port's for root-level devices.
+    pub fn device_port_location(&self, device: &UsbDevice) -> Option<PhysicalLocation> {
+        if let Some(loc) = &device.physical_location {
+            return Some(loc.clone());
+        }
+        if device.path.depth() != 0 {
+            return None;
+        }
+        let bus = self.buses.get(&device.path.bus_num()?)?;
+        let port_num: u8 = device.path.port_path()?.parse().ok()?;
+        bus.ports
+            .iter()
+            .find(|p| p.port_num == port_num)
+            .and_then(|p| p.physical_location.clone())
+    }
+
     /// Get a device by its path, searching all buses.
     pub fn get_device(&self, path: &DevicePath) -> Option<&UsbDevice> {
         if let Some(bus_num) = path.bus_num()
@@ -476,19 +622,6 @@ impl UsbTopology {
     }
 }
 
-/// Format bandwidth as human-readable string.
-pub fn format_bandwidth(bps: u64) -> String {
-    if bps >= 1_000_000_000 {
-        format!("{:.2} Gbps", bps as f64 / 1_000_000_000.0)
-    } else if bps >= 1_000_000 {
-        format!("{:.2} Mbps", bps as f64 / 1_000_000.0)
-    } else if bps >= 1_000 {
-        format!("{:.2} Kbps", bps as f64 / 1_000.0)
-    } else {
-        format!("{} bps", bps)
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -496,29 +629,19 @@ mod tests {
 
     fn make_test_device(vendor_id: u16, product_id: u16, serial: Option<&str>) -> UsbDevice {
         UsbDevice {
-            path: DevicePath::new("1-1"),
             speed: UsbSpeed::Full,
-            vendor_id,
-            product_id,
-            manufacturer: None,
-            product: None,
             serial: serial.map(String::from),
-            device_class: 0,
-            is_hub: false,
-            num_ports: None,
-            endpoints: vec![],
-            physical_location: None,
-            children: vec![],
-            label: None,
-            usb_version: "2.00".to_string(),
-            num_interfaces: 1,
-            max_power_ma: 100,
-            is_configured: true,
-            connected_duration_ms: None,
-            rx_lanes: None,
+            ..UsbDevice::fixture("1-1", vendor_id, product_id)
         }
     }
 
+    #[test]
+    fn test_key_matches() {
+        assert!(key_matches("0d28:0204:ABC", "0d28:0204"));
+        assert!(key_matches("0d28:0204:ABC", "0D28:0204:abc"));
+        assert!(!key_matches("0d28:02041", "0d28:0204"));
+    }
+
     #[test]
     fn test_device_path_parent() {
         let path = DevicePath::new("3-1.2.3");
@@ -535,14 +658,6 @@ mod tests {
         assert_eq!(DevicePath::new("3-1.2.3").depth(), 2);
     }
 
-    #[test]
-    fn test_format_bandwidth() {
-        assert_eq!(format_bandwidth(500), "500 bps");
-        assert_eq!(format_bandwidth(64_000), "64.00 Kbps");
-        assert_eq!(format_bandwidth(480_000_000), "480.00 Mbps");
-        assert_eq!(format_bandwidth(5_000_000_000), "5.00 Gbps");
-    }
-
     #[test]
     fn test_vid_pid() {
         let device = make_test_device(0x0d28, 0x0204, None);
@@ -570,4 +685,37 @@ mod tests {
         let device = make_test_device(0x0d28, 0x0204, Some(""));
         assert_eq!(device.config_key(), "0d28:0204");
     }
+
+    #[test]
+    fn test_bus_keys_skip_bus_number_fallback() {
+        let mut topology = UsbTopology::new();
+        for (bus_num, pci, firmware) in
+            [(1, "0000:c1:00.4", None), (3, "bus3", Some("\\_SB_.USB0"))]
+        {
+            let id = ControllerId(pci.to_string());
+            topology.controllers.insert(
+                id.clone(),
+                UsbController {
+                    id: id.clone(),
+                    pci_address: pci.to_string(),
+                    usb2_bus: Some(bus_num),
+                    usb3_bus: None,
+                    label: None,
+                    controller_type: ControllerType::Usb,
+                    firmware_path: firmware.map(String::from),
+                },
+            );
+            topology.buses.insert(
+                bus_num,
+                UsbBus {
+                    controller_id: id,
+                    ..UsbBus::fixture(bus_num, vec![])
+                },
+            );
+        }
+        assert_eq!(topology.bus_keys(1), vec!["0000:c1:00.4/usb2"]);
+        assert_eq!(topology.bus_keys(3), vec!["\\_SB_.USB0/usb2"]);
+        assert!(ControllerId("bus12".to_string()).is_bus_fallback());
+        assert!(!ControllerId("0000:c1:00.4".to_string()).is_bus_fallback());
+    }
 }
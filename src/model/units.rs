@@ -0,0 +1,174 @@
+//! Unit formatting for bandwidth values.
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use super::speed::UsbSpeed;
+
+/// Unit system used to display bandwidth.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Units {
+    /// Bits per second with SI prefixes (Kbps, Mbps, Gbps).
+    #[default]
+    Bits,
+    /// Bits per second with IEC prefixes (Kibps, Mibps, Gibps).
+    BitsIec,
+    /// Bytes per second with SI prefixes (KB/s, MB/s, GB/s).
+    Bytes,
+    /// Bytes per second with IEC prefixes (KiB/s, MiB/s, GiB/s).
+    BytesIec,
+    /// Bus time in microseconds per (micro)frame, as the kernel budgets it.
+    BusTime,
+}
+
+impl Units {
+    /// All unit system names, as accepted by `--units` and `[settings] units`.
+    pub const NAMES: &'static [&'static str] =
+        &["bits", "bits-iec", "bytes", "bytes-iec", "bus-time"];
+
+    /// Unit system matching the legacy `use_bits` setting.
+    pub fn from_use_bits(use_bits: bool) -> Self {
+        if use_bits { Self::Bits } else { Self::Bytes }
+    }
+
+    /// Name as accepted by `--units`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Bits => "bits",
+            Self::BitsIec => "bits-iec",
+            Self::Bytes => "bytes",
+            Self::BytesIec => "bytes-iec",
+            Self::BusTime => "bus-time",
+        }
+    }
+
+    /// Format a bandwidth reserved on a bus running at `speed`.
+    ///
+    /// Bus time is the share of each frame (1ms at low/full speed) or
+    /// microframe (125µs at high speed and above) spent on the transfer.
+    pub fn format(&self, bps: u64, speed: UsbSpeed) -> String {
+        match self {
+            Self::BusTime => {
+                let raw = speed.raw_bandwidth_bps();
+                let period = speed.frame_period_us();
+                let us = if raw == 0 {
+                    0.0
+                } else {
+                    bps as f64 * period as f64 / raw as f64
+                };
+                let frame = if period < 1000 { "µframe" } else { "frame" };
+                format!("{:.1} µs/{}", us, frame)
+            }
+            _ => self.format_rate(bps),
+        }
+    }
+
+    /// Format a bandwidth without bus context.
+    ///
+    /// Bus time needs the bus speed, so it falls back to bits per second.
+    pub fn format_rate(&self, bps: u64) -> String {
+        match self {
+            Self::Bits | Self::BusTime => {
+                scaled(bps as f64, 1000.0, &["bps", "Kbps", "Mbps", "Gbps"])
+            }
+            Self::BitsIec => scaled(bps as f64, 1024.0, &["bps", "Kibps", "Mibps", "Gibps"]),
+            Self::Bytes => scaled(bps as f64 / 8.0, 1000.0, &["B/s", "KB/s", "MB/s", "GB/s"]),
+            Self::BytesIec => scaled(
+                bps as f64 / 8.0,
+                1024.0,
+                &["B/s", "KiB/s", "MiB/s", "GiB/s"],
+            ),
+        }
+    }
+}
+
+impl fmt::Display for Units {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Units {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().replace('_', "-").as_str() {
+            "bits" | "bps" => Ok(Self::Bits),
+            "bits-iec" => Ok(Self::BitsIec),
+            "bytes" => Ok(Self::Bytes),
+            "bytes-iec" => Ok(Self::BytesIec),
+            "bus-time" | "us" | "µs" => Ok(Self::BusTime),
+            _ => Err(format!(
+                "unknown units '{}' (expected one of: {})",
+                s,
+                Self::NAMES.join(", ")
+            )),
+        }
+    }
+}
+
+/// Format bits per second with SI prefixes.
+pub fn format_bps(bps: u64) -> String {
+    Units::Bits.format_rate(bps)
+}
+
+fn scaled(value: f64, base: f64, suffixes: &[&str]) -> String {
+    let mut value = value;
+    let mut index = 0;
+    while value >= base && index + 1 < suffixes.len() {
+        value /= base;
+        index += 1;
+    }
+    if index == 0 {
+        format!("{} {}", value.round() as u64, suffixes[0])
+    } else {
+        format!("{:.2} {}", value, suffixes[index])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_bps() {
+        assert_eq!(format_bps(500), "500 bps");
+        assert_eq!(format_bps(1500), "1.50 Kbps");
+        assert_eq!(format_bps(64_000), "64.00 Kbps");
+        assert_eq!(format_bps(1_500_000), "1.50 Mbps");
+        assert_eq!(format_bps(5_000_000_000), "5.00 Gbps");
+    }
+
+    #[test]
+    fn test_unit_systems() {
+        assert_eq!(Units::BitsIec.format_rate(2048), "2.00 Kibps");
+        assert_eq!(Units::Bytes.format_rate(8_000_000), "1.00 MB/s");
+        assert_eq!(Units::BytesIec.format_rate(8 * 1024 * 1024), "1.00 MiB/s");
+        assert_eq!(Units::Bytes.format_rate(800), "100 B/s");
+    }
+
+    #[test]
+    fn test_bus_time() {
+        // The high-speed periodic budget is 80% of a 125µs microframe
+        let max = UsbSpeed::High.max_periodic_bandwidth_bps();
+        assert_eq!(
+            Units::BusTime.format(max, UsbSpeed::High),
+            "100.0 µs/µframe"
+        );
+        let max = UsbSpeed::Full.max_periodic_bandwidth_bps();
+        assert_eq!(Units::BusTime.format(max, UsbSpeed::Full), "900.0 µs/frame");
+        assert_eq!(Units::BusTime.format_rate(1500), "1.50 Kbps");
+    }
+
+    #[test]
+    fn test_parse_units() {
+        for name in Units::NAMES {
+            assert_eq!(name.parse::<Units>().unwrap().name(), *name);
+        }
+        assert_eq!("bus_time".parse::<Units>(), Ok(Units::BusTime));
+        assert!("furlongs".parse::<Units>().is_err());
+    }
+}
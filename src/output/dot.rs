@@ -0,0 +1,189 @@
+//! Graphviz DOT diagram generation.
+
+use super::view::{ViewNode, bus_view};
+use crate::config::Config;
+use crate::model::{BandwidthPool, UsbBus, UsbTopology};
+
+/// Generate a Graphviz DOT digraph of the USB topology.
+///
+/// Each controller becomes a cluster containing one cluster per bus. Device
+/// fill colours follow reserved bandwidth, bus fill colours follow periodic
+/// usage, and edges are coloured and weighted by configured power draw.
+pub fn generate_dot(topology: &UsbTopology, config: &Config) -> String {
+    let mut output = String::new();
+
+    output.push_str("digraph usb {\n");
+    output.push_str("    // USB Topology Diagram\n");
+    output.push_str("    // Generated by usbbw\n");
+    output.push_str("    graph [rankdir=LR, fontname=\"Helvetica\", fontsize=11];\n");
+    output.push_str(
+        "    node [shape=box, style=\"rounded,filled\", fontname=\"Helvetica\", fontsize=10];\n",
+    );
+    output.push_str("    edge [color=\"#6b7280\"];\n\n");
+
+    for controller in topology.controllers_sorted() {
+        let ctrl_id = format!("ctrl_{}", sanitize_id(&controller.id.0));
+        let ctrl_label = config
+            .controller_label(&controller.pci_address)
+            .unwrap_or_else(|| format!("Controller\n{}", controller.pci_address));
+
+        output.push_str(&format!("    subgraph cluster_{} {{\n", ctrl_id));
+        output.push_str(&format!("        label=\"{}\";\n", escape(&ctrl_label)));
+        output.push_str("        style=\"rounded\";\n");
+        output.push_str("        color=\"#1e3a8a\";\n");
+        output.push_str(&format!(
+            "        {} [label=\"{}\", fillcolor=\"#1e40af\", color=\"#1e3a8a\", fontcolor=\"#ffffff\"];\n",
+            ctrl_id,
+            escape(&ctrl_label)
+        ));
+
+        for bus_num in [controller.usb2_bus, controller.usb3_bus]
+            .into_iter()
+            .flatten()
+        {
+            if let Some(bus) = topology.buses.get(&bus_num) {
+                add_bus_dot(&mut output, bus, config, &ctrl_id);
+            }
+        }
+
+        output.push_str("    }\n\n");
+    }
+
+    output.push_str("}\n");
+    output
+}
+
+fn add_bus_dot(output: &mut String, bus: &UsbBus, config: &Config, parent_id: &str) {
+    let bus_id = format!("bus{}", bus.bus_num);
+    let pool = BandwidthPool::with_usage(bus.speed, bus.periodic_bandwidth_used_bps());
+
+    let label = config
+        .bus_label(bus.bus_num)
+        .unwrap_or_else(|| format!("Bus {}", bus.bus_num));
+    let bus_label = format!(
+        "{}\n{} ({:.1}%)",
+        label,
+        bus.speed.short_name(),
+        pool.periodic_usage_percent()
+    );
+
+    // Bus colour follows usage; USB 2.0/3.x colours match the Mermaid classes
+    let (fill, stroke) = if pool.exceeds(config.settings.critical_usage_percent) {
+        ("#dc2626", "#991b1b")
+    } else if pool.exceeds(config.settings.high_usage_percent) {
+        ("#d97706", "#92400e")
+    } else if bus.is_superspeed() {
+        ("#c2410c", "#9a3412")
+    } else {
+        ("#166534", "#14532d")
+    };
+
+    output.push_str(&format!("        subgraph cluster_{} {{\n", bus_id));
+    output.push_str(&format!("            label=\"{}\";\n", escape(&label)));
+    output.push_str("            style=\"dashed\";\n");
+    output.push_str(&format!("            color=\"{}\";\n", stroke));
+    output.push_str(&format!(
+        "            {} [label=\"{}\", fillcolor=\"{}\", color=\"{}\", fontcolor=\"#ffffff\"];\n",
+        bus_id,
+        escape(&bus_label),
+        fill,
+        stroke
+    ));
+
+    for node in bus_view(bus, config) {
+        add_device_dot(output, &node, bus, config, &bus_id);
+    }
+
+    output.push_str("        }\n");
+    output.push_str(&format!("        {} -> {};\n", parent_id, bus_id));
+}
+
+fn add_device_dot(
+    output: &mut String,
+    node: &ViewNode,
+    bus: &UsbBus,
+    config: &Config,
+    parent_id: &str,
+) {
+    let device = node.device;
+    let dev_id = format!("dev_{}", sanitize_id(&device.path.0));
+    let label = config.device_name(device);
+
+    let bandwidth = device.periodic_bandwidth_bps();
+    let mut node_label = format!("{}\n{}", label, device.path.0);
+    if bandwidth > 0 {
+        node_label.push_str(&format!(
+            "\n{}",
+            config.units().format(bandwidth, bus.speed)
+        ));
+    }
+    for line in node.annotations(config) {
+        node_label.push_str(&format!("\n{}", line));
+    }
+
+    // Fill by reserved bandwidth (hubs keep the hub colour)
+    let (shape, fill, stroke, font) = if device.is_hub {
+        ("box3d", "#7c3aed", "#5b21b6", "#ffffff")
+    } else if bandwidth > 10_000_000 {
+        ("box", "#dc2626", "#991b1b", "#ffffff")
+    } else if bandwidth > 1_000_000 {
+        ("box", "#fcd34d", "#b45309", "#000000")
+    } else {
+        ("box", "#fef3c7", "#b45309", "#000000")
+    };
+
+    output.push_str(&format!(
+        "            {} [label=\"{}\", shape={}, fillcolor=\"{}\", color=\"{}\", fontcolor=\"{}\"];\n",
+        dev_id,
+        escape(&node_label),
+        shape,
+        fill,
+        stroke,
+        font
+    ));
+
+    // Edge colour and width by configured power draw
+    let (edge_color, penwidth) = match device.max_power_ma {
+        0..=100 => ("#6b7280", 1.0),
+        101..=500 => ("#d97706", 1.5),
+        _ => ("#dc2626", 2.5),
+    };
+    output.push_str(&format!(
+        "            {} -> {} [color=\"{}\", penwidth={:.1}, label=\"{} mA\", fontsize=8];\n",
+        parent_id, dev_id, edge_color, penwidth, device.max_power_ma
+    ));
+
+    for child in &node.children {
+        add_device_dot(output, child, bus, config, &dev_id);
+    }
+}
+
+/// Sanitize string for use in a DOT node ID (callers add a letter prefix,
+/// since IDs may not start with a digit).
+fn sanitize_id(s: &str) -> String {
+    s.replace(['-', '.', ':'], "_")
+}
+
+/// Escape a DOT string literal; newlines become centered line breaks.
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(escape("a \"b\"\nc"), "a \\\"b\\\"\\nc");
+    }
+
+    #[test]
+    fn test_empty_topology() {
+        let dot = generate_dot(&UsbTopology::new(), &Config::default());
+        assert!(dot.starts_with("digraph usb {\n"));
+        assert!(dot.ends_with("}\n"));
+    }
+}
@@ -1,37 +1,8 @@
 //! Mermaid diagram generation.
 
+use super::view::{ViewNode, bus_view, visible_devices};
 use crate::config::Config;
-use crate::model::{BandwidthPool, UsbBus, UsbDevice, UsbTopology, format_bandwidth};
-
-/// Generate a standalone HTML file with embedded Mermaid diagram.
-pub fn generate_html(topology: &UsbTopology, config: &Config) -> String {
-    let mermaid_code = generate_mermaid_raw(topology, config);
-
-    format!(
-        r#"<!DOCTYPE html>
-<html>
-<head>
-  <meta charset="utf-8">
-  <title>USB Topology</title>
-  <script src="https://cdn.jsdelivr.net/npm/mermaid/dist/mermaid.min.js"></script>
-  <style>
-    body {{ font-family: system-ui, sans-serif; margin: 2em; }}
-    h1 {{ color: #333; }}
-    .mermaid {{ background: white; }}
-  </style>
-</head>
-<body>
-  <h1>USB Topology</h1>
-  <pre class="mermaid">
-{}
-  </pre>
-  <script>mermaid.initialize({{startOnLoad:true, theme:'default', flowchart:{{curve:'basis'}}}});</script>
-</body>
-</html>
-"#,
-        mermaid_code
-    )
-}
+use crate::model::{BandwidthPool, UsbBus, UsbTopology};
 
 /// Generate a Mermaid flowchart diagram of USB topology.
 pub fn generate_mermaid(topology: &UsbTopology, config: &Config) -> String {
@@ -140,8 +111,8 @@ pub fn generate_markdown(topology: &UsbTopology, config: &Config) -> String {
             label,
             bus_type,
             bus.speed.short_name(),
-            pool.format_used(),
-            pool.format_max(),
+            pool.format_used(config.units()),
+            pool.format_max(config.units()),
             pool.periodic_usage_percent(),
             power,
             bus.device_count()
@@ -150,29 +121,23 @@ pub fn generate_markdown(topology: &UsbTopology, config: &Config) -> String {
 
     output.push('\n');
 
-    // High bandwidth devices
+    // High bandwidth devices (among those shown in the diagram)
     let high_bw_devices: Vec<_> = topology
         .buses
         .values()
         .flat_map(|bus| {
-            bus.devices.values().filter_map(|dev| {
-                let bw = dev.periodic_bandwidth_bps();
-                if bw > 1_000_000 {
-                    // > 1 Mbps
-                    let label = config
-                        .device_label(
-                            &dev.path.0,
-                            dev.vendor_id,
-                            dev.product_id,
-                            dev.serial.as_deref(),
-                            dev.physical_location.as_ref(),
-                        )
-                        .unwrap_or_else(|| dev.display_name());
-                    Some((label, dev.path.0.clone(), bw))
-                } else {
-                    None
-                }
-            })
+            visible_devices(&bus_view(bus, config))
+                .into_iter()
+                .filter_map(|dev| {
+                    let bw = dev.periodic_bandwidth_bps();
+                    if bw > 1_000_000 {
+                        // > 1 Mbps
+                        Some((config.device_name(dev), dev.path.0.clone(), bw, bus.speed))
+                    } else {
+                        None
+                    }
+                })
+                .collect::<Vec<_>>()
         })
         .collect();
 
@@ -183,14 +148,14 @@ pub fn generate_markdown(topology: &UsbTopology, config: &Config) -> String {
         output.push_str("|--------|------|----------|\n");
 
         let mut sorted = high_bw_devices;
-        sorted.sort_by(|a, b| b.2.cmp(&a.2));
+        sorted.sort_by_key(|b| std::cmp::Reverse(b.2));
 
-        for (label, path, bw) in sorted {
+        for (label, path, bw, speed) in sorted {
             output.push_str(&format!(
                 "| {} | `{}` | {} |\n",
                 label,
                 path,
-                format_bandwidth(bw)
+                config.units().format(bw, speed)
             ));
         }
         output.push('\n');
@@ -223,7 +188,7 @@ pub fn generate_markdown(topology: &UsbTopology, config: &Config) -> String {
             i + 1,
             label,
             bus_type,
-            pool.format_available()
+            pool.format_available(config.units())
         ));
     }
 
@@ -298,49 +263,33 @@ fn add_bus_mermaid(
     let bus_class = if is_superspeed { "usb3bus" } else { "usb2bus" };
     output.push_str(&format!("    class {} {}\n", bus_id, bus_class));
 
-    // Add root-level devices
-    for device in bus.devices_tree_order() {
-        if device.path.depth() == 0 {
-            add_device_mermaid(output, device, bus, config, &bus_id);
-        }
+    // Add root-level devices (after view filtering)
+    for node in bus_view(bus, config) {
+        add_device_mermaid(output, &node, bus, config, &bus_id);
     }
 }
 
 fn add_device_mermaid(
     output: &mut String,
-    device: &UsbDevice,
+    node: &ViewNode,
     bus: &UsbBus,
     config: &Config,
     parent_id: &str,
 ) {
-    // Check if device should be hidden
-    if config.should_hide_path(&device.path.0) {
-        return;
-    }
-
-    // Check vendor filter
-    if !config.should_show_vendor(device.vendor_id) {
-        return;
-    }
-
+    let device = node.device;
     let dev_id = sanitize_id(&device.path.0);
 
-    let label = config
-        .device_label(
-            &device.path.0,
-            device.vendor_id,
-            device.product_id,
-            device.serial.as_deref(),
-            device.physical_location.as_ref(),
-        )
-        .unwrap_or_else(|| device.display_name());
+    let label = config.device_name(device);
 
     let bandwidth = device.periodic_bandwidth_bps();
-    let bw_str = if bandwidth > 0 {
-        format!("<br/>{}", format_bandwidth(bandwidth))
+    let mut bw_str = if bandwidth > 0 {
+        format!("<br/>{}", config.units().format(bandwidth, bus.speed))
     } else {
         String::new()
     };
+    for line in node.annotations(config) {
+        bw_str.push_str(&format!("<br/><i>{}</i>", line));
+    }
 
     // Use different shapes for hubs vs devices
     let (shape_open, shape_close) = if device.is_hub {
@@ -369,10 +318,8 @@ fn add_device_mermaid(
     output.push_str(&format!("    class {} {}\n", dev_id, class));
 
     // Add children recursively
-    for child_path in &device.children {
-        if let Some(child) = bus.devices.get(child_path) {
-            add_device_mermaid(output, child, bus, config, &dev_id);
-        }
+    for child in &node.children {
+        add_device_mermaid(output, child, bus, config, &dev_id);
     }
 }
 
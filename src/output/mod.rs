@@ -1,5 +1,15 @@
 //! Output formats.
 
+pub mod dot;
 pub mod mermaid;
+pub mod prometheus;
+pub mod svg;
+pub mod table;
+pub mod view;
 
-pub use mermaid::{generate_html, generate_markdown, generate_mermaid};
+pub use dot::generate_dot;
+pub use mermaid::{generate_markdown, generate_mermaid};
+pub use prometheus::generate_metrics;
+pub use svg::{escape_xml, generate_html, generate_svg};
+pub use table::{Delimiter, generate_device_table, generate_endpoint_table};
+pub use view::{ViewNode, bus_view, visible_devices};
@@ -0,0 +1,253 @@
+//! Prometheus/OpenMetrics text exposition format.
+
+use crate::config::Config;
+use crate::model::{PortState, UsbTopology};
+
+/// Generate metrics for the topology in Prometheus text exposition format.
+///
+/// Controller, bus and device labels are resolved through the config so
+/// dashboards can show friendly names next to the raw identifiers.
+pub fn generate_metrics(topology: &UsbTopology, config: &Config) -> String {
+    let mut output = String::new();
+
+    let mut bus_used = Vec::new();
+    let mut bus_max = Vec::new();
+    let mut bus_power = Vec::new();
+    let mut bus_devices = Vec::new();
+    let mut bus_unconfigured = Vec::new();
+    let mut port_over_current = Vec::new();
+    let mut port_problem = Vec::new();
+    let mut device_reserved = Vec::new();
+    let mut device_configured = Vec::new();
+
+    for bus in topology.buses_sorted() {
+        let controller = topology.get_controller_for_bus(bus.bus_num);
+        let pci_address = controller
+            .map(|c| c.pci_address.clone())
+            .unwrap_or_default();
+        let controller_label = config
+            .controller_label(&pci_address)
+            .unwrap_or_else(|| pci_address.clone());
+        let bus_label = config
+            .bus_label(bus.bus_num)
+            .unwrap_or_else(|| format!("Bus {}", bus.bus_num));
+
+        let bus_labels = format_labels(&[
+            ("controller", &pci_address),
+            ("controller_label", &controller_label),
+            ("bus", &bus.bus_num.to_string()),
+            ("bus_label", &bus_label),
+            ("speed", bus.speed.short_name()),
+        ]);
+
+        bus_used.push(format!(
+            "{} {}",
+            bus_labels,
+            bus.periodic_bandwidth_used_bps()
+        ));
+        bus_max.push(format!(
+            "{} {}",
+            bus_labels,
+            bus.max_periodic_bandwidth_bps()
+        ));
+        bus_power.push(format!("{} {}", bus_labels, bus.total_power_ma()));
+        bus_devices.push(format!("{} {}", bus_labels, bus.device_count()));
+        let unconfigured = bus.devices.values().filter(|d| !d.is_configured).count();
+        bus_unconfigured.push(format!("{} {}", bus_labels, unconfigured));
+
+        for port in &bus.ports {
+            let port_labels = format_labels(&[
+                ("controller", &pci_address),
+                ("bus", &bus.bus_num.to_string()),
+                ("bus_label", &bus_label),
+                ("port", &port.port_num.to_string()),
+            ]);
+            port_over_current.push(format!("{} {}", port_labels, port.over_current_count));
+            if port.state.is_problematic() {
+                let state = match port.state {
+                    PortState::Reconnecting => "reconnecting",
+                    PortState::Powered => "powered",
+                    PortState::Disconnected => "disconnected",
+                    _ => "other",
+                };
+                let problem_labels = format_labels(&[
+                    ("controller", &pci_address),
+                    ("bus", &bus.bus_num.to_string()),
+                    ("bus_label", &bus_label),
+                    ("port", &port.port_num.to_string()),
+                    ("state", state),
+                ]);
+                port_problem.push(format!("{} 1", problem_labels));
+            }
+        }
+
+        for device in bus.devices_tree_order() {
+            let label = config.device_name(device);
+            let device_labels = format_labels(&[
+                ("controller", &pci_address),
+                ("bus", &bus.bus_num.to_string()),
+                ("bus_label", &bus_label),
+                ("path", &device.path.0),
+                ("key", &device.config_key()),
+                ("device_label", &label),
+            ]);
+            device_reserved.push(format!(
+                "{} {}",
+                device_labels,
+                device.periodic_bandwidth_bps()
+            ));
+            device_configured.push(format!(
+                "{} {}",
+                device_labels,
+                u8::from(device.is_configured)
+            ));
+        }
+    }
+
+    add_family(
+        &mut output,
+        "usbbw_bus_periodic_used_bps",
+        "gauge",
+        "Periodic bandwidth reserved on the bus in bits per second.",
+        &bus_used,
+    );
+    add_family(
+        &mut output,
+        "usbbw_bus_periodic_max_bps",
+        "gauge",
+        "Maximum periodic bandwidth of the bus in bits per second.",
+        &bus_max,
+    );
+    add_family(
+        &mut output,
+        "usbbw_bus_power_milliamps",
+        "gauge",
+        "Configured power of all devices on the bus in milliamps.",
+        &bus_power,
+    );
+    add_family(
+        &mut output,
+        "usbbw_bus_devices",
+        "gauge",
+        "Number of devices on the bus.",
+        &bus_devices,
+    );
+    add_family(
+        &mut output,
+        "usbbw_bus_unconfigured_devices",
+        "gauge",
+        "Number of devices on the bus that failed to configure.",
+        &bus_unconfigured,
+    );
+    add_family(
+        &mut output,
+        "usbbw_port_over_current_total",
+        "counter",
+        "Over-current events reported by the root hub port.",
+        &port_over_current,
+    );
+    add_family(
+        &mut output,
+        "usbbw_port_problem",
+        "gauge",
+        "Root hub port in a problematic state (1 while the state persists).",
+        &port_problem,
+    );
+    add_family(
+        &mut output,
+        "usbbw_device_periodic_reserved_bps",
+        "gauge",
+        "Periodic bandwidth reserved by the device in bits per second.",
+        &device_reserved,
+    );
+    add_family(
+        &mut output,
+        "usbbw_device_configured",
+        "gauge",
+        "Device has an active configuration (0 if bandwidth allocation failed).",
+        &device_configured,
+    );
+
+    output
+}
+
+/// Append a metric family with HELP/TYPE headers.
+fn add_family(output: &mut String, name: &str, kind: &str, help: &str, samples: &[String]) {
+    output.push_str(&format!("# HELP {} {}\n", name, help));
+    output.push_str(&format!("# TYPE {} {}\n", name, kind));
+    for sample in samples {
+        output.push_str(name);
+        output.push_str(sample);
+        output.push('\n');
+    }
+}
+
+/// Format a label set as `{name="value",...}`.
+fn format_labels(labels: &[(&str, &str)]) -> String {
+    let parts: Vec<String> = labels
+        .iter()
+        .map(|(name, value)| format!("{}=\"{}\"", name, escape_label_value(value)))
+        .collect();
+    format!("{{{}}}", parts.join(","))
+}
+
+/// Escape a label value (backslash, double quote and newline).
+fn escape_label_value(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::UsbDevice;
+
+    /// Controller 0000:c1:00.4 with USB 2.0 bus 1: a configured probe on 1-1
+    /// and a camera on 1-2 that failed to configure.
+    fn make_topology() -> UsbTopology {
+        let devices = vec![
+            UsbDevice::fixture("1-1", 0x0d28, 0x0204),
+            UsbDevice {
+                is_configured: false,
+                ..UsbDevice::fixture("1-2", 0x046d, 0x0825)
+            },
+        ];
+        UsbTopology::fixture(1, devices)
+    }
+
+    #[test]
+    fn test_escape_label_value() {
+        assert_eq!(escape_label_value(r#"a "b" \c"#), r#"a \"b\" \\c"#);
+        assert_eq!(escape_label_value("x\ny"), "x\\ny");
+    }
+
+    #[test]
+    fn test_empty_topology_has_headers() {
+        let output = generate_metrics(&UsbTopology::new(), &Config::default());
+        assert!(output.contains("# TYPE usbbw_bus_periodic_used_bps gauge\n"));
+        assert!(output.contains("# TYPE usbbw_port_over_current_total counter\n"));
+    }
+
+    #[test]
+    fn test_bus_and_device_samples() {
+        let config: Config = toml::from_str(
+            r#"
+            [products]
+            "0d28:0204" = "Probe"
+            "#,
+        )
+        .unwrap();
+        let output = generate_metrics(&make_topology(), &config);
+        let bus = r#"{controller="0000:c1:00.4",controller_label="0000:c1:00.4",bus="1",bus_label="Bus 1",speed="480M"}"#;
+        assert!(output.contains(&format!("usbbw_bus_devices{} 2\n", bus)));
+        assert!(output.contains(&format!("usbbw_bus_unconfigured_devices{} 1\n", bus)));
+
+        // Configured state is its own gauge, so a device keeps one series
+        let probe = r#"{controller="0000:c1:00.4",bus="1",bus_label="Bus 1",path="1-1",key="0d28:0204",device_label="Probe"}"#;
+        assert!(output.contains(&format!("usbbw_device_periodic_reserved_bps{} 0\n", probe)));
+        assert!(output.contains(&format!("usbbw_device_configured{} 1\n", probe)));
+        assert!(output.contains(r#"usbbw_device_configured{controller="0000:c1:00.4",bus="1",bus_label="Bus 1",path="1-2",key="046d:0825",device_label="046d:0825"} 0"#));
+        assert!(!output.contains("configured=\""));
+    }
+}
@@ -0,0 +1,388 @@
+//! Native SVG topology rendering (no JavaScript, no external resources).
+
+use super::view::{ViewNode, bus_view};
+use crate::config::Config;
+use crate::model::{BandwidthPool, UsbBus, UsbTopology};
+
+/// Node box width in pixels.
+const NODE_WIDTH: f64 = 210.0;
+/// Node box height in pixels.
+const NODE_HEIGHT: f64 = 46.0;
+/// Horizontal gap between tree levels.
+const COLUMN_GAP: f64 = 50.0;
+/// Vertical gap between sibling rows.
+const ROW_GAP: f64 = 12.0;
+/// Outer margin.
+const MARGIN: f64 = 16.0;
+/// Characters that fit on one line of a node.
+const MAX_LABEL_CHARS: usize = 30;
+
+/// CSS classes equivalent to the Mermaid classDefs.
+const STYLE: &str = r#"
+    .node rect.box { stroke-width: 1.5; }
+    .node text { font-family: system-ui, sans-serif; font-size: 12px; }
+    .node text.sub { font-size: 10px; }
+    .controller rect.box { fill: #1e40af; stroke: #1e3a8a; }
+    .usb2bus rect.box { fill: #166534; stroke: #14532d; }
+    .usb3bus rect.box { fill: #c2410c; stroke: #9a3412; }
+    .hub rect.box { fill: #7c3aed; stroke: #5b21b6; }
+    .device rect.box { fill: #fef3c7; stroke: #b45309; }
+    .highbw rect.box { fill: #dc2626; stroke: #991b1b; }
+    .controller text, .usb2bus text, .usb3bus text, .hub text, .highbw text { fill: #ffffff; font-weight: bold; }
+    .device text { fill: #000000; }
+    .bar-bg { fill: #ffffff; fill-opacity: 0.25; }
+    .bar-ok { fill: #22c55e; }
+    .bar-high { fill: #facc15; }
+    .bar-critical { fill: #ef4444; }
+    .edge { fill: none; stroke: #6b7280; stroke-width: 1.5; }
+"#;
+
+/// A node in the rendered tree.
+struct Node {
+    class: &'static str,
+    title: String,
+    subtitle: String,
+    /// Bandwidth bar (usage percent and bar class), for buses.
+    bar: Option<(f64, &'static str)>,
+    children: Vec<Node>,
+    x: f64,
+    y: f64,
+}
+
+impl Node {
+    fn new(class: &'static str, title: String, subtitle: String) -> Self {
+        Self {
+            class,
+            title,
+            subtitle,
+            bar: None,
+            children: Vec::new(),
+            x: 0.0,
+            y: 0.0,
+        }
+    }
+}
+
+/// Generate an SVG diagram of the USB topology.
+///
+/// The tree is laid out left to right (controller → bus → hub → device) with
+/// each parent vertically centered on its children.
+pub fn generate_svg(topology: &UsbTopology, config: &Config) -> String {
+    let mut roots = build_tree(topology, config);
+
+    let mut next_row = 0.0;
+    for root in &mut roots {
+        layout(root, 0, &mut next_row);
+    }
+
+    let depth = roots.iter().map(max_depth).max().unwrap_or(0);
+    let width = MARGIN * 2.0 + (depth + 1) as f64 * NODE_WIDTH + depth as f64 * COLUMN_GAP;
+    let height = (MARGIN * 2.0 + next_row - ROW_GAP).max(MARGIN * 2.0);
+
+    let mut output = String::new();
+    output.push_str(&format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w:.0}\" height=\"{h:.0}\" viewBox=\"0 0 {w:.0} {h:.0}\">\n",
+        w = width,
+        h = height
+    ));
+    output.push_str("  <title>USB Topology</title>\n");
+    output.push_str(&format!("  <style>{}  </style>\n", STYLE));
+
+    for root in &roots {
+        render_edges(&mut output, root);
+    }
+    for root in &roots {
+        render_nodes(&mut output, root);
+    }
+
+    output.push_str("</svg>\n");
+    output
+}
+
+/// Build the controller → bus → device tree with resolved labels.
+fn build_tree(topology: &UsbTopology, config: &Config) -> Vec<Node> {
+    let mut roots = Vec::new();
+
+    for controller in topology.controllers_sorted() {
+        let title = config
+            .controller_label(&controller.pci_address)
+            .unwrap_or_else(|| "Controller".to_string());
+        let mut node = Node::new("controller", title, controller.pci_address.clone());
+
+        for bus_num in [controller.usb2_bus, controller.usb3_bus]
+            .into_iter()
+            .flatten()
+        {
+            if let Some(bus) = topology.buses.get(&bus_num) {
+                node.children.push(bus_node(bus, config));
+            }
+        }
+
+        roots.push(node);
+    }
+
+    roots
+}
+
+fn bus_node(bus: &UsbBus, config: &Config) -> Node {
+    let pool = BandwidthPool::with_usage(bus.speed, bus.periodic_bandwidth_used_bps());
+    let percent = pool.periodic_usage_percent();
+    let title = config
+        .bus_label(bus.bus_num)
+        .unwrap_or_else(|| format!("Bus {}", bus.bus_num));
+    let subtitle = format!("{} ({:.1}%)", bus.speed.short_name(), percent);
+    let class = if bus.is_superspeed() {
+        "usb3bus"
+    } else {
+        "usb2bus"
+    };
+    let bar_class = if pool.exceeds(config.settings.critical_usage_percent) {
+        "bar-critical"
+    } else if pool.exceeds(config.settings.high_usage_percent) {
+        "bar-high"
+    } else {
+        "bar-ok"
+    };
+
+    let mut node = Node::new(class, title, subtitle);
+    node.bar = Some((percent, bar_class));
+    node.children = bus_view(bus, config)
+        .iter()
+        .map(|view| device_node(view, bus, config))
+        .collect();
+    node
+}
+
+fn device_node(view: &ViewNode, bus: &UsbBus, config: &Config) -> Node {
+    let device = view.device;
+    let bandwidth = device.periodic_bandwidth_bps();
+    let title = config.device_name(device);
+    let mut subtitle = if bandwidth > 0 {
+        format!(
+            "{} · {}",
+            device.path.0,
+            config.units().format(bandwidth, bus.speed)
+        )
+    } else {
+        device.path.0.clone()
+    };
+    for line in view.annotations(config) {
+        subtitle.push_str(&format!(" · {}", line));
+    }
+    let class = if device.is_hub {
+        "hub"
+    } else if bandwidth > 10_000_000 {
+        "highbw"
+    } else {
+        "device"
+    };
+
+    let mut node = Node::new(class, title, subtitle);
+    node.children = view
+        .children
+        .iter()
+        .map(|child| device_node(child, bus, config))
+        .collect();
+    node
+}
+
+/// Assign positions: leaves take consecutive rows, parents are centered on
+/// their children.
+fn layout(node: &mut Node, depth: usize, next_row: &mut f64) {
+    node.x = MARGIN + depth as f64 * (NODE_WIDTH + COLUMN_GAP);
+
+    if node.children.is_empty() {
+        node.y = MARGIN + *next_row;
+        *next_row += NODE_HEIGHT + ROW_GAP;
+        return;
+    }
+
+    for child in &mut node.children {
+        layout(child, depth + 1, next_row);
+    }
+    let first = node.children.first().map(|c| c.y).unwrap_or_default();
+    let last = node.children.last().map(|c| c.y).unwrap_or_default();
+    node.y = (first + last) / 2.0;
+}
+
+fn max_depth(node: &Node) -> usize {
+    node.children
+        .iter()
+        .map(|c| max_depth(c) + 1)
+        .max()
+        .unwrap_or(0)
+}
+
+fn render_edges(output: &mut String, node: &Node) {
+    for child in &node.children {
+        let (x1, y1) = (node.x + NODE_WIDTH, node.y + NODE_HEIGHT / 2.0);
+        let (x2, y2) = (child.x, child.y + NODE_HEIGHT / 2.0);
+        let mid = (x1 + x2) / 2.0;
+        output.push_str(&format!(
+            "  <path class=\"edge\" d=\"M{:.1},{:.1} C{:.1},{:.1} {:.1},{:.1} {:.1},{:.1}\"/>\n",
+            x1, y1, mid, y1, mid, y2, x2, y2
+        ));
+        render_edges(output, child);
+    }
+}
+
+fn render_nodes(output: &mut String, node: &Node) {
+    output.push_str(&format!(
+        "  <g class=\"node {}\" transform=\"translate({:.1},{:.1})\">\n",
+        node.class, node.x, node.y
+    ));
+    output.push_str(&format!(
+        "    <title>{}\n{}</title>\n",
+        escape_xml(&node.title),
+        escape_xml(&node.subtitle)
+    ));
+    output.push_str(&format!(
+        "    <rect class=\"box\" width=\"{:.0}\" height=\"{:.0}\" rx=\"6\"/>\n",
+        NODE_WIDTH, NODE_HEIGHT
+    ));
+    output.push_str(&format!(
+        "    <text x=\"8\" y=\"18\">{}</text>\n",
+        escape_xml(&truncate(&node.title, MAX_LABEL_CHARS))
+    ));
+    output.push_str(&format!(
+        "    <text class=\"sub\" x=\"8\" y=\"32\">{}</text>\n",
+        escape_xml(&truncate(&node.subtitle, MAX_LABEL_CHARS + 6))
+    ));
+
+    if let Some((percent, bar_class)) = node.bar {
+        let bar_width = NODE_WIDTH - 16.0;
+        let filled = bar_width * percent.clamp(0.0, 100.0) / 100.0;
+        output.push_str(&format!(
+            "    <rect class=\"bar-bg\" x=\"8\" y=\"37\" width=\"{:.1}\" height=\"5\" rx=\"2\"/>\n",
+            bar_width
+        ));
+        output.push_str(&format!(
+            "    <rect class=\"{}\" x=\"8\" y=\"37\" width=\"{:.1}\" height=\"5\" rx=\"2\"/>\n",
+            bar_class, filled
+        ));
+    }
+
+    output.push_str("  </g>\n");
+
+    for child in &node.children {
+        render_nodes(output, child);
+    }
+}
+
+/// Generate a standalone, fully offline HTML report with the SVG inlined.
+pub fn generate_html(topology: &UsbTopology, config: &Config) -> String {
+    let mut rows = String::new();
+    for bus in topology.buses_sorted() {
+        let pool = BandwidthPool::with_usage(bus.speed, bus.periodic_bandwidth_used_bps());
+        let label = config
+            .bus_label(bus.bus_num)
+            .unwrap_or_else(|| format!("Bus {}", bus.bus_num));
+        rows.push_str(&format!(
+            "      <tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{:.1}%</td><td>{} mA</td><td>{}</td></tr>\n",
+            escape_xml(&label),
+            bus.speed.short_name(),
+            pool.format_used(config.units()),
+            pool.format_max(config.units()),
+            pool.periodic_usage_percent(),
+            bus.total_power_ma(),
+            bus.device_count()
+        ));
+    }
+
+    format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+  <meta charset="utf-8">
+  <title>USB Topology</title>
+  <style>
+    body {{ font-family: system-ui, sans-serif; margin: 2em; }}
+    h1 {{ color: #333; }}
+    table {{ border-collapse: collapse; margin-bottom: 2em; }}
+    th, td {{ border: 1px solid #d1d5db; padding: 4px 10px; text-align: left; }}
+    th {{ background: #f3f4f6; }}
+    .diagram {{ overflow-x: auto; }}
+  </style>
+</head>
+<body>
+  <h1>USB Topology</h1>
+  <table>
+    <thead>
+      <tr><th>Bus</th><th>Speed</th><th>BW Used</th><th>BW Max</th><th>Usage</th><th>Power</th><th>Devices</th></tr>
+    </thead>
+    <tbody>
+{}    </tbody>
+  </table>
+  <div class="diagram">
+{}  </div>
+</body>
+</html>
+"#,
+        rows,
+        generate_svg(topology, config)
+    )
+}
+
+/// Truncate to a maximum number of characters, adding an ellipsis.
+fn truncate(s: &str, max: usize) -> String {
+    if s.chars().count() <= max {
+        s.to_string()
+    } else {
+        let mut out: String = s.chars().take(max.saturating_sub(1)).collect();
+        out.push('…');
+        out
+    }
+}
+
+/// Escape text for XML content and attributes (either quote style).
+pub fn escape_xml(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layout_centers_parent() {
+        let mut root = Node::new("hub", "Hub".to_string(), String::new());
+        root.children
+            .push(Node::new("device", "A".to_string(), String::new()));
+        root.children
+            .push(Node::new("device", "B".to_string(), String::new()));
+
+        let mut next_row = 0.0;
+        layout(&mut root, 0, &mut next_row);
+
+        assert_eq!(root.children[0].y, MARGIN);
+        assert_eq!(root.children[1].y, MARGIN + NODE_HEIGHT + ROW_GAP);
+        assert_eq!(root.y, MARGIN + (NODE_HEIGHT + ROW_GAP) / 2.0);
+        assert_eq!(root.children[0].x, MARGIN + NODE_WIDTH + COLUMN_GAP);
+    }
+
+    #[test]
+    fn test_html_is_offline() {
+        let html = generate_html(&UsbTopology::new(), &Config::default());
+        assert!(html.contains("<svg"));
+        assert!(!html.contains("<script"));
+        assert!(!html.contains("src="));
+    }
+
+    #[test]
+    fn test_escape_xml() {
+        assert_eq!(
+            escape_xml(r#"Tom's <"hub"> & co"#),
+            "Tom&apos;s &lt;&quot;hub&quot;&gt; &amp; co"
+        );
+    }
+
+    #[test]
+    fn test_truncate() {
+        assert_eq!(truncate("short", 10), "short");
+        assert_eq!(truncate("abcdefghij", 5), "abcd…");
+    }
+}
@@ -0,0 +1,239 @@
+//! CSV/TSV export of periodic endpoint bandwidth.
+//!
+//! `--hide` and `--vendor` narrow the rows the same way they narrow a
+//! diagram. The diagram layout options (depth limit, hub collapsing) do not
+//! apply: a capacity table lists every device.
+
+use crate::config::Config;
+use crate::model::{UsbBus, UsbDevice, UsbTopology};
+
+/// Field separator for delimited output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Delimiter {
+    /// Comma-separated values, RFC 4180 quoting.
+    Comma,
+    /// Tab-separated values; tabs and newlines in fields become spaces.
+    Tab,
+}
+
+impl Delimiter {
+    fn separator(&self) -> &'static str {
+        match self {
+            Self::Comma => ",",
+            Self::Tab => "\t",
+        }
+    }
+
+    fn field(&self, value: &str) -> String {
+        match self {
+            Self::Comma => {
+                if value.contains([',', '"', '\n', '\r']) {
+                    format!("\"{}\"", value.replace('"', "\"\""))
+                } else {
+                    value.to_string()
+                }
+            }
+            Self::Tab => value.replace(['\t', '\n', '\r'], " "),
+        }
+    }
+
+    fn row(&self, fields: &[String]) -> String {
+        let fields: Vec<String> = fields.iter().map(|f| self.field(f)).collect();
+        format!("{}\n", fields.join(self.separator()))
+    }
+}
+
+const ENDPOINT_COLUMNS: &[&str] = &[
+    "bus",
+    "controller",
+    "path",
+    "config_key",
+    "label",
+    "speed",
+    "endpoint",
+    "type",
+    "direction",
+    "max_packet_size",
+    "mult",
+    "b_interval",
+    "interval",
+    "interval_us",
+    "bps",
+];
+
+const DEVICE_COLUMNS: &[&str] = &[
+    "bus",
+    "controller",
+    "path",
+    "config_key",
+    "label",
+    "speed",
+    "periodic_endpoints",
+    "bps",
+];
+
+/// Generate one row per periodic (interrupt/isochronous) endpoint.
+pub fn generate_endpoint_table(
+    topology: &UsbTopology,
+    config: &Config,
+    delimiter: Delimiter,
+) -> String {
+    let header: Vec<String> = ENDPOINT_COLUMNS.iter().map(|c| c.to_string()).collect();
+    let mut output = delimiter.row(&header);
+
+    for (bus, controller, device) in table_devices(topology, config) {
+        for ep in device.periodic_endpoints() {
+            let mut row = device_fields(bus, &controller, device, config);
+            row.extend([
+                format!("0x{:02x}", ep.address),
+                ep.transfer_type.to_string(),
+                ep.direction.to_string(),
+                ep.base_packet_size().to_string(),
+                ep.multiplier().to_string(),
+                ep.b_interval.to_string(),
+                ep.interval_str.clone(),
+                ep.interval_us(device.speed).to_string(),
+                ep.bandwidth_bps(device.speed).to_string(),
+            ]);
+            output.push_str(&delimiter.row(&row));
+        }
+    }
+
+    output
+}
+
+/// Generate one row per device with periodic endpoints, totalled.
+pub fn generate_device_table(
+    topology: &UsbTopology,
+    config: &Config,
+    delimiter: Delimiter,
+) -> String {
+    let header: Vec<String> = DEVICE_COLUMNS.iter().map(|c| c.to_string()).collect();
+    let mut output = delimiter.row(&header);
+
+    for (bus, controller, device) in table_devices(topology, config) {
+        let endpoints = device.periodic_endpoints();
+        if endpoints.is_empty() {
+            continue;
+        }
+        let mut row = device_fields(bus, &controller, device, config);
+        row.push(endpoints.len().to_string());
+        row.push(device.periodic_bandwidth_bps().to_string());
+        output.push_str(&delimiter.row(&row));
+    }
+
+    output
+}
+
+/// Devices passing the hide and vendor filters in bus order, with their bus
+/// and controller PCI address.
+fn table_devices<'a>(
+    topology: &'a UsbTopology,
+    config: &Config,
+) -> Vec<(&'a UsbBus, String, &'a UsbDevice)> {
+    let mut rows = Vec::new();
+    for bus in topology.buses_sorted() {
+        let controller = topology
+            .get_controller_for_bus(bus.bus_num)
+            .map(|c| c.pci_address.clone())
+            .unwrap_or_default();
+        for device in bus.devices_tree_order() {
+            if !config.should_hide_path(&device.path.0)
+                && config.should_show_vendor(device.vendor_id)
+            {
+                rows.push((bus, controller.clone(), device));
+            }
+        }
+    }
+    rows
+}
+
+fn device_fields(
+    bus: &UsbBus,
+    controller: &str,
+    device: &UsbDevice,
+    config: &Config,
+) -> Vec<String> {
+    vec![
+        bus.bus_num.to_string(),
+        controller.to_string(),
+        device.path.0.clone(),
+        device.config_key(),
+        config.device_name(device),
+        device.speed.short_name().to_string(),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{DevicePath, Direction, Endpoint, TransferType, UsbSpeed};
+
+    /// Bus 1: hub 1-1 (05e3) -> keyboard 1-1.1 (046d) with one interrupt IN
+    /// endpoint at 8 ms full speed.
+    fn make_topology() -> UsbTopology {
+        let hub = UsbDevice {
+            is_hub: true,
+            children: vec![DevicePath::new("1-1.1")],
+            ..UsbDevice::fixture("1-1", 0x05e3, 0x0608)
+        };
+        let keyboard = UsbDevice {
+            speed: UsbSpeed::Full,
+            endpoints: vec![Endpoint {
+                address: 0x81,
+                transfer_type: TransferType::Interrupt,
+                direction: Direction::In,
+                max_packet_size: 64,
+                b_interval: 8,
+                interval_str: "8ms".to_string(),
+                interface: 0,
+            }],
+            ..UsbDevice::fixture("1-1.1", 0x046d, 0xc31c)
+        };
+        UsbTopology::fixture(1, vec![hub, keyboard])
+    }
+
+    #[test]
+    fn test_rows_ignore_diagram_layout() {
+        // Depth limit and hub collapsing would cut the keyboard from a diagram
+        let config: Config = toml::from_str(
+            r#"
+            [mermaid]
+            max_depth = 1
+            collapse_single_child_hubs = true
+            "#,
+        )
+        .unwrap();
+        let csv = generate_endpoint_table(&make_topology(), &config, Delimiter::Comma);
+        let rows: Vec<&str> = csv.lines().skip(1).collect();
+        assert_eq!(
+            rows,
+            vec![
+                "1,0000:c1:00.4,1-1.1,046d:c31c,046d:c31c,12M,0x81,Interrupt,IN,64,1,8,8ms,8000,64000"
+            ]
+        );
+
+        let mut config = config;
+        config.mermaid.filter_vendors = vec!["05e3".to_string()];
+        let csv = generate_device_table(&make_topology(), &config, Delimiter::Comma);
+        assert_eq!(csv.lines().count(), 1);
+    }
+
+    #[test]
+    fn test_csv_quoting() {
+        let row = Delimiter::Comma.row(&["a".into(), "b,c".into(), "say \"hi\"".into()]);
+        assert_eq!(row, "a,\"b,c\",\"say \"\"hi\"\"\"\n");
+        let row = Delimiter::Tab.row(&["a\tb".into(), "c".into()]);
+        assert_eq!(row, "a b\tc\n");
+    }
+
+    #[test]
+    fn test_empty_topology_has_header() {
+        let topology = UsbTopology::new();
+        let csv = generate_endpoint_table(&topology, &Config::default(), Delimiter::Comma);
+        assert_eq!(csv.lines().count(), 1);
+        assert!(csv.starts_with("bus,controller,path,config_key,label,speed,endpoint,"));
+        let tsv = generate_device_table(&topology, &Config::default(), Delimiter::Tab);
+        assert_eq!(tsv, DEVICE_COLUMNS.join("\t") + "\n");
+    }
+}
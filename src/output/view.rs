@@ -0,0 +1,212 @@
+//! Topology view filtering shared by the diagram exporters.
+//!
+//! Applies the `[mermaid]` filters (hidden subtrees, vendor filter, hub
+//! collapsing, depth limit) once, so every exporter renders the same tree.
+
+use crate::config::Config;
+use crate::model::{UsbBus, UsbDevice};
+
+/// A visible device and its visible children.
+#[derive(Debug)]
+pub struct ViewNode<'a> {
+    /// The device shown by this node.
+    pub device: &'a UsbDevice,
+    /// Single-child hubs collapsed into this node (outermost first).
+    pub via_hubs: Vec<&'a UsbDevice>,
+    /// Visible children.
+    pub children: Vec<ViewNode<'a>>,
+    /// Number of devices behind this node cut off by the depth limit.
+    pub truncated: usize,
+}
+
+impl ViewNode<'_> {
+    /// Extra label lines describing collapsed hubs and truncated devices.
+    pub fn annotations(&self, config: &Config) -> Vec<String> {
+        let mut lines = Vec::new();
+        if !self.via_hubs.is_empty() {
+            let hubs: Vec<String> = self
+                .via_hubs
+                .iter()
+                .map(|h| config.device_name(h))
+                .collect();
+            lines.push(format!("via {}", hubs.join(" > ")));
+        }
+        if self.truncated > 0 {
+            lines.push(format!("+{} more", self.truncated));
+        }
+        lines
+    }
+}
+
+/// Build the filtered device tree for a bus (root-port devices first).
+pub fn bus_view<'a>(bus: &'a UsbBus, config: &Config) -> Vec<ViewNode<'a>> {
+    bus.devices_tree_order()
+        .into_iter()
+        .filter(|d| d.path.depth() == 0)
+        .filter_map(|d| build_node(d, bus, config))
+        .collect()
+}
+
+/// Flatten a view into its visible devices (tree order, collapsed hubs omitted).
+pub fn visible_devices<'a>(nodes: &[ViewNode<'a>]) -> Vec<&'a UsbDevice> {
+    let mut devices = Vec::new();
+    for node in nodes {
+        devices.push(node.device);
+        devices.extend(visible_devices(&node.children));
+    }
+    devices
+}
+
+fn build_node<'a>(device: &'a UsbDevice, bus: &'a UsbBus, config: &Config) -> Option<ViewNode<'a>> {
+    if config.should_hide_path(&device.path.0) {
+        return None;
+    }
+
+    let mut truncated = 0;
+    let mut children = Vec::new();
+    if config
+        .mermaid
+        .max_depth
+        .is_some_and(|max| device.path.depth() + 1 >= max)
+    {
+        truncated = count_descendants(device, bus, config);
+    } else {
+        children = device
+            .children
+            .iter()
+            .filter_map(|p| bus.devices.get(p))
+            .filter_map(|child| build_node(child, bus, config))
+            .collect();
+    }
+
+    // Keep non-matching hubs that lead to a matching device, shown or cut off
+    if !config.should_show_vendor(device.vendor_id) && children.is_empty() && truncated == 0 {
+        return None;
+    }
+
+    if config.mermaid.collapse_single_child_hubs
+        && device.is_hub
+        && children.len() == 1
+        && truncated == 0
+    {
+        let mut child = children.pop()?;
+        child.via_hubs.insert(0, device);
+        return Some(child);
+    }
+
+    Some(ViewNode {
+        device,
+        via_hubs: Vec::new(),
+        children,
+        truncated,
+    })
+}
+
+/// Count visible-by-filter devices behind a device (for depth truncation).
+///
+/// Only devices passing the vendor filter are counted; hubs leading to them
+/// are not, matching what an unlimited depth would have shown as matches.
+fn count_descendants(device: &UsbDevice, bus: &UsbBus, config: &Config) -> usize {
+    device
+        .children
+        .iter()
+        .filter_map(|p| bus.devices.get(p))
+        .filter(|child| !config.should_hide_path(&child.path.0))
+        .map(|child| {
+            usize::from(config.should_show_vendor(child.vendor_id))
+                + count_descendants(child, bus, config)
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{ControllerId, DevicePath, UsbSpeed};
+
+    fn make_device(path: &str, vendor_id: u16, children: &[&str]) -> UsbDevice {
+        UsbDevice {
+            is_hub: !children.is_empty(),
+            children: children.iter().map(|c| DevicePath::new(*c)).collect(),
+            ..UsbDevice::fixture(path, vendor_id, 0x0001)
+        }
+    }
+
+    /// Bus 3: hub 3-1 -> hub 3-1.1 -> {3-1.1.1 (0d28), 3-1.1.2 (046d)}; 3-2 (046d).
+    fn make_bus() -> UsbBus {
+        let devices = vec![
+            make_device("3-1", 0x05e3, &["3-1.1"]),
+            make_device("3-1.1", 0x05e3, &["3-1.1.1", "3-1.1.2"]),
+            make_device("3-1.1.1", 0x0d28, &[]),
+            make_device("3-1.1.2", 0x046d, &[]),
+            make_device("3-2", 0x046d, &[]),
+        ];
+        UsbBus {
+            bus_num: 3,
+            speed: UsbSpeed::High,
+            version: "2.00".to_string(),
+            num_ports: 4,
+            devices: devices.into_iter().map(|d| (d.path.clone(), d)).collect(),
+            controller_id: ControllerId("usb3".to_string()),
+            ports: vec![],
+        }
+    }
+
+    fn paths(nodes: &[ViewNode]) -> Vec<String> {
+        visible_devices(nodes)
+            .iter()
+            .map(|d| d.path.0.clone())
+            .collect()
+    }
+
+    #[test]
+    fn test_hide_subtree() {
+        let bus = make_bus();
+        let mut config = Config::default();
+        config.mermaid.hide_paths = vec!["3-1.1".to_string()];
+        assert_eq!(paths(&bus_view(&bus, &config)), vec!["3-1", "3-2"]);
+    }
+
+    #[test]
+    fn test_vendor_filter_keeps_ancestors() {
+        let bus = make_bus();
+        let mut config = Config::default();
+        config.mermaid.filter_vendors = vec!["0D28".to_string()];
+        assert_eq!(
+            paths(&bus_view(&bus, &config)),
+            vec!["3-1", "3-1.1", "3-1.1.1"]
+        );
+    }
+
+    #[test]
+    fn test_collapse_and_depth() {
+        let bus = make_bus();
+        let mut config = Config::default();
+        config.mermaid.collapse_single_child_hubs = true;
+        let view = bus_view(&bus, &config);
+        // 3-1 has a single child hub, which has two children: 3-1 collapses
+        assert_eq!(view[0].device.path.0, "3-1.1");
+        assert_eq!(view[0].via_hubs[0].path.0, "3-1");
+
+        let mut config = Config::default();
+        config.mermaid.max_depth = Some(1);
+        let view = bus_view(&bus, &config);
+        assert_eq!(paths(&view), vec!["3-1", "3-2"]);
+        assert_eq!(view[0].truncated, 3);
+    }
+
+    #[test]
+    fn test_depth_limit_with_vendor_filter() {
+        let bus = make_bus();
+        let mut config = Config::default();
+        config.mermaid.max_depth = Some(1);
+        config.mermaid.filter_vendors = vec!["0d28".to_string()];
+        let view = bus_view(&bus, &config);
+        // Hub 3-1 doesn't match but hides the only matching device
+        assert_eq!(paths(&view), vec!["3-1"]);
+        assert_eq!(view[0].truncated, 1);
+
+        config.mermaid.filter_vendors = vec!["1234".to_string()];
+        assert!(bus_view(&bus, &config).is_empty());
+    }
+}
@@ -1,5 +1,7 @@
 //! Sysfs parsing for USB device information.
 
 mod parser;
+pub mod usb_ids;
 
 pub use parser::{SysfsError, SysfsParser};
+pub use usb_ids::{UsbIdNames, lookup_usb_ids};
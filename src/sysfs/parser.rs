@@ -2,7 +2,7 @@
 
 use crate::model::{
     ControllerId, ControllerType, DevicePath, Direction, Endpoint, PhysicalLocation, PortInfo,
-    PortState, TransferType, UsbBus, UsbController, UsbDevice, UsbSpeed, UsbTopology,
+    PortState, TransferType, UsbBus, UsbController, UsbDevice, UsbInterface, UsbSpeed, UsbTopology,
 };
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
@@ -136,6 +136,7 @@ impl SysfsParser {
                                 } else {
                                     ControllerType::Usb
                                 },
+                                firmware_path: self.get_firmware_path(bus_num),
                             });
 
                         if bus.is_superspeed() {
@@ -252,11 +253,14 @@ impl SysfsParser {
                 None
             };
 
+            let physical_location = self.parse_physical_location(&port_path).ok();
+
             ports.push(PortInfo {
                 port_num,
                 state,
                 over_current_count,
                 device_path,
+                physical_location,
             });
         }
 
@@ -303,11 +307,11 @@ impl SysfsParser {
         // Parse physical location if present
         let physical_location = self.parse_physical_location(&path).ok();
 
-        // Parse endpoints from all interfaces (only for configured devices)
-        let endpoints = if is_configured {
-            self.parse_all_endpoints(&path)?
+        // Parse interfaces and their endpoints (only for configured devices)
+        let (interfaces, endpoints) = if is_configured {
+            self.parse_interfaces(&path)?
         } else {
-            Vec::new()
+            (Vec::new(), Vec::new())
         };
 
         // Parse max power consumption (bMaxPower is like "500mA" or "0mA")
@@ -334,6 +338,7 @@ impl SysfsParser {
             is_hub,
             num_ports,
             endpoints,
+            interfaces,
             physical_location,
             children: Vec::new(),
             label: None,
@@ -346,14 +351,18 @@ impl SysfsParser {
         })
     }
 
-    /// Parse all endpoints from all interfaces of a device.
-    fn parse_all_endpoints(&self, device_path: &Path) -> Result<Vec<Endpoint>, SysfsError> {
+    /// Parse all interfaces of a device and the endpoints they contain.
+    fn parse_interfaces(
+        &self,
+        device_path: &Path,
+    ) -> Result<(Vec<UsbInterface>, Vec<Endpoint>), SysfsError> {
+        let mut interfaces = Vec::new();
         let mut endpoints = Vec::new();
 
         // Find all interface directories (e.g., "3-1.2:1.0")
         let entries = match std::fs::read_dir(device_path) {
             Ok(e) => e,
-            Err(_) => return Ok(endpoints),
+            Err(_) => return Ok((interfaces, endpoints)),
         };
 
         for entry in entries.flatten() {
@@ -361,16 +370,22 @@ impl SysfsParser {
 
             // Interface directories contain ':'
             if name.contains(':') && entry.path().is_dir() {
+                let iface_path = entry.path();
+                let interface = self.parse_interface(&iface_path);
+                let number = interface.as_ref().map(|i| i.number).unwrap_or(0);
+                interfaces.extend(interface);
+
                 // Find endpoint directories within interface
-                if let Ok(iface_entries) = std::fs::read_dir(entry.path()) {
+                if let Ok(iface_entries) = std::fs::read_dir(&iface_path) {
                     for ep_entry in iface_entries.flatten() {
                         let ep_name = ep_entry.file_name().to_string_lossy().to_string();
 
                         // Match ep_XX but not ep_00 (control endpoint)
                         if ep_name.starts_with("ep_")
                             && ep_name != "ep_00"
-                            && let Ok(ep) = self.parse_endpoint(&ep_entry.path())
+                            && let Ok(mut ep) = self.parse_endpoint(&ep_entry.path())
                         {
+                            ep.interface = number;
                             endpoints.push(ep);
                         }
                     }
@@ -378,7 +393,34 @@ impl SysfsParser {
             }
         }
 
-        Ok(endpoints)
+        interfaces.sort_by_key(|i| i.number);
+        endpoints.sort_by_key(|ep| (ep.interface, ep.address));
+        Ok((interfaces, endpoints))
+    }
+
+    /// Parse an interface directory (e.g., "3-1.2:1.0").
+    fn parse_interface(&self, path: &Path) -> Option<UsbInterface> {
+        let driver = std::fs::read_link(path.join("driver"))
+            .ok()
+            .and_then(|link| link.file_name().map(|n| n.to_string_lossy().to_string()));
+        let name = self
+            .read_attr_string(path, "interface")
+            .ok()
+            .map(|s| s.trim().to_string());
+
+        Some(UsbInterface {
+            number: self.read_hex_attr_u8(path, "bInterfaceNumber").ok()?,
+            alt_setting: self.read_attr_u8(path, "bAlternateSetting").unwrap_or(0),
+            class: self.read_hex_attr_u8(path, "bInterfaceClass").unwrap_or(0),
+            subclass: self
+                .read_hex_attr_u8(path, "bInterfaceSubClass")
+                .unwrap_or(0),
+            protocol: self
+                .read_hex_attr_u8(path, "bInterfaceProtocol")
+                .unwrap_or(0),
+            driver,
+            name,
+        })
     }
 
     /// Parse a single endpoint.
@@ -408,6 +450,7 @@ impl SysfsParser {
             max_packet_size,
             b_interval,
             interval_str: interval_str.trim().to_string(),
+            interface: 0,
         })
     }
 
@@ -477,6 +520,16 @@ impl SysfsParser {
         Ok(ControllerId(format!("bus{}", bus_num)))
     }
 
+    /// ACPI path of the controller a bus belongs to (the root hub's parent).
+    fn get_firmware_path(&self, bus_num: u8) -> Option<String> {
+        let path = self
+            .base_path
+            .join(format!("usb{}", bus_num))
+            .join("../firmware_node/path");
+        let content = std::fs::read_to_string(path).ok()?;
+        Some(content.trim().to_string()).filter(|p| !p.is_empty())
+    }
+
     /// Get PCI address for a bus.
     fn get_pci_address(&self, bus_num: u8) -> Option<String> {
         self.get_controller_id(bus_num).ok().map(|id| id.0)
@@ -529,3 +582,151 @@ impl SysfsParser {
             .map_err(|e| SysfsError::Parse("bMaxPower".to_string(), format!("{}", e)))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn write_attrs(dir: &Path, attrs: &[(&str, &str)]) {
+        fs::create_dir_all(dir).unwrap();
+        for (name, value) in attrs {
+            fs::write(dir.join(name), format!("{}\n", value)).unwrap();
+        }
+    }
+
+    fn write_endpoint(iface: &Path, name: &str, attrs: &[(&str, &str)]) {
+        write_attrs(&iface.join(name), attrs);
+    }
+
+    #[test]
+    fn test_parse_interfaces() {
+        let base = std::env::temp_dir().join(format!("usbbw-sysfs-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&base);
+        let device = base.join("1-2");
+        write_attrs(
+            &device,
+            &[
+                ("speed", "480"),
+                ("idVendor", "046d"),
+                ("idProduct", "0825"),
+                ("product", "Webcam C270"),
+                ("bDeviceClass", "ef"),
+                ("bNumInterfaces", " 2"),
+                ("bConfigurationValue", "1"),
+                ("bMaxPower", "500mA"),
+            ],
+        );
+
+        // Video streaming interface in alternate setting 6 with an
+        // isochronous endpoint, bound to uvcvideo
+        let streaming = device.join("1-2:1.1");
+        write_attrs(
+            &streaming,
+            &[
+                ("bInterfaceNumber", "01"),
+                ("bAlternateSetting", " 6"),
+                ("bInterfaceClass", "0e"),
+                ("bInterfaceSubClass", "02"),
+                ("bInterfaceProtocol", "00"),
+            ],
+        );
+        fs::create_dir_all(base.join("drivers/uvcvideo")).unwrap();
+        std::os::unix::fs::symlink(base.join("drivers/uvcvideo"), streaming.join("driver"))
+            .unwrap();
+        write_endpoint(
+            &streaming,
+            "ep_81",
+            &[
+                ("type", "Isoc"),
+                ("direction", "in"),
+                ("bEndpointAddress", "81"),
+                ("bInterval", "01"),
+                ("wMaxPacketSize", "13fc"),
+                ("interval", "125us"),
+            ],
+        );
+
+        // Control interface with an interrupt endpoint and no driver
+        let control = device.join("1-2:1.0");
+        write_attrs(
+            &control,
+            &[
+                ("bInterfaceNumber", "00"),
+                ("bAlternateSetting", " 0"),
+                ("bInterfaceClass", "0e"),
+                ("bInterfaceSubClass", "01"),
+                ("interface", "Camera Control"),
+            ],
+        );
+        write_endpoint(&control, "ep_00", &[("type", "Control")]);
+        write_endpoint(
+            &control,
+            "ep_87",
+            &[
+                ("type", "Interrupt"),
+                ("direction", "in"),
+                ("bEndpointAddress", "87"),
+                ("bInterval", "08"),
+                ("wMaxPacketSize", "0010"),
+                ("interval", "16ms"),
+            ],
+        );
+
+        let parsed = SysfsParser::with_base_path(&base)
+            .parse_device("1-2")
+            .unwrap();
+        let _ = fs::remove_dir_all(&base);
+
+        assert_eq!(parsed.interfaces.len(), 2);
+        let control = &parsed.interfaces[0];
+        assert_eq!((control.number, control.alt_setting), (0, 0));
+        assert_eq!((control.class, control.subclass), (0x0e, 0x01));
+        assert_eq!(control.driver, None);
+        assert_eq!(control.name.as_deref(), Some("Camera Control"));
+        let streaming = &parsed.interfaces[1];
+        assert_eq!((streaming.number, streaming.alt_setting), (1, 6));
+        assert_eq!(streaming.driver.as_deref(), Some("uvcvideo"));
+
+        // ep_00 skipped; endpoints ordered by interface and mapped to it
+        let endpoints: Vec<(u8, u8, TransferType)> = parsed
+            .endpoints
+            .iter()
+            .map(|ep| (ep.interface, ep.address, ep.transfer_type))
+            .collect();
+        assert_eq!(
+            endpoints,
+            vec![
+                (0, 0x87, TransferType::Interrupt),
+                (1, 0x81, TransferType::Isochronous),
+            ]
+        );
+        assert_eq!(parsed.endpoints[1].max_packet_size, 0x13fc);
+        assert_eq!(parsed.max_power_ma, 500);
+    }
+
+    #[test]
+    fn test_unconfigured_device_has_no_interfaces() {
+        let base = std::env::temp_dir().join(format!("usbbw-sysfs-unconf-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&base);
+        let device = base.join("1-3");
+        write_attrs(
+            &device,
+            &[
+                ("speed", "12"),
+                ("idVendor", "0d28"),
+                ("idProduct", "0204"),
+                ("bConfigurationValue", ""),
+            ],
+        );
+        write_attrs(&device.join("1-3:1.0"), &[("bInterfaceNumber", "00")]);
+
+        let parsed = SysfsParser::with_base_path(&base)
+            .parse_device("1-3")
+            .unwrap();
+        let _ = fs::remove_dir_all(&base);
+        assert!(!parsed.is_configured);
+        assert!(parsed.interfaces.is_empty());
+        assert_eq!(parsed.speed, UsbSpeed::Full);
+    }
+}
@@ -0,0 +1,106 @@
+//! Vendor and product names from the system USB ID database (`usb.ids`,
+//! shipped by usbutils/hwdata).
+
+use std::path::Path;
+
+/// Locations of `usb.ids` on common distributions.
+pub const USB_IDS_PATHS: &[&str] = &[
+    "/usr/share/hwdata/usb.ids",
+    "/usr/share/misc/usb.ids",
+    "/usr/share/usb.ids",
+    "/var/lib/usbutils/usb.ids",
+];
+
+/// Names found for a VID:PID.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UsbIdNames {
+    /// Vendor name.
+    pub vendor: Option<String>,
+    /// Product name.
+    pub product: Option<String>,
+}
+
+/// Look up a VID:PID in the first `usb.ids` found on the system.
+pub fn lookup_usb_ids(vendor_id: u16, product_id: u16) -> UsbIdNames {
+    USB_IDS_PATHS
+        .iter()
+        .map(Path::new)
+        .find_map(|path| std::fs::read(path).ok())
+        .map(|bytes| lookup_in(&String::from_utf8_lossy(&bytes), vendor_id, product_id))
+        .unwrap_or_default()
+}
+
+/// Look up a VID:PID in `usb.ids` content.
+///
+/// Vendors are unindented `vvvv  Name` lines, followed by their products as
+/// tab-indented `pppp  Name` lines.
+pub fn lookup_in(content: &str, vendor_id: u16, product_id: u16) -> UsbIdNames {
+    let vendor_hex = format!("{:04x}", vendor_id);
+    let product_hex = format!("{:04x}", product_id);
+    let mut names = UsbIdNames::default();
+
+    for line in content.lines() {
+        if line.starts_with('#') || line.is_empty() {
+            continue;
+        }
+        match (&names.vendor, line.strip_prefix('\t')) {
+            (None, None) => {
+                if let Some((id, name)) = split_entry(line)
+                    && id == vendor_hex
+                {
+                    names.vendor = Some(name.to_string());
+                }
+            }
+            (Some(_), Some(product)) => {
+                if product.starts_with('\t') {
+                    continue; // interface line
+                }
+                if let Some((id, name)) = split_entry(product)
+                    && id == product_hex
+                {
+                    names.product = Some(name.to_string());
+                    break;
+                }
+            }
+            // Next vendor: the product isn't listed
+            (Some(_), None) => break,
+            (None, Some(_)) => {}
+        }
+    }
+    names
+}
+
+fn split_entry(line: &str) -> Option<(String, &str)> {
+    let (id, name) = line.split_once("  ")?;
+    (id.len() == 4 && id.chars().all(|c| c.is_ascii_hexdigit()))
+        .then(|| (id.to_lowercase(), name.trim()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = "\
+# comment
+046d  Logitech, Inc.
+\t0825  Webcam C270
+\t\t00  interface
+\t082d  HD Pro Webcam C920
+0d28  NXP
+\t0204  ARM mbed
+C 00  (Defined at Interface level)
+";
+
+    #[test]
+    fn test_lookup_in() {
+        let names = lookup_in(SAMPLE, 0x046d, 0x082d);
+        assert_eq!(names.vendor.as_deref(), Some("Logitech, Inc."));
+        assert_eq!(names.product.as_deref(), Some("HD Pro Webcam C920"));
+
+        let names = lookup_in(SAMPLE, 0x046d, 0x0204);
+        assert_eq!(names.vendor.as_deref(), Some("Logitech, Inc."));
+        assert_eq!(names.product, None);
+
+        assert_eq!(lookup_in(SAMPLE, 0x1234, 0x0001), UsbIdNames::default());
+    }
+}
@@ -1,11 +1,22 @@
 //! TUI application state.
 
-use crate::config::Config;
+use crate::alert::{Alert, evaluate_alerts};
+use crate::config::{Config, LabelTarget};
+use crate::history::{HistoryEvent, now_secs};
 use crate::model::{
-    BandwidthPool, ControllerType, DevicePath, UsbBus, UsbDevice, UsbTopology, format_bandwidth,
+    BandwidthPool, ControllerType, DevicePath, PhysicalLocation, PhysicalPort, Units, UsbBus,
+    UsbDevice, UsbSpeed, UsbTopology, diff_topologies, physical_ports,
 };
+use crate::sysfs::{UsbIdNames, lookup_usb_ids};
+use crate::ui::input::LineEditor;
+use crate::ui::samples::UsageHistory;
+use crate::ui::search::{QuickFilter, device_matches};
+use crate::ui::theme::Theme;
 use std::collections::{HashMap, HashSet};
 
+/// Maximum number of entries kept in the event log.
+pub const EVENT_LOG_CAPACITY: usize = 1000;
+
 /// View mode for the TUI.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum ViewMode {
@@ -14,19 +25,32 @@ pub enum ViewMode {
     Tree,
     /// Summary view of all buses.
     Summary,
+    /// Interface and endpoint drill-down for the selected device.
+    Endpoints,
+    /// Chassis schematic of the physical ports.
+    Ports,
 }
 
 /// Input mode for editing labels.
 #[derive(Debug, Clone)]
 pub struct EditState {
-    /// Product key being edited (VID:PID:iSerial or VID:PID).
-    pub product_key: String,
-    /// Display name for the edit dialog.
-    pub display_name: String,
-    /// Current input buffer.
-    pub input: String,
-    /// Cursor position in input.
-    pub cursor: usize,
+    /// What can be labelled from the current selection (Tab cycles).
+    pub targets: Vec<LabelTarget>,
+    /// Index of the target being edited.
+    pub target_index: usize,
+    /// Device the edit was started from, marked seen on save.
+    pub device_path: Option<DevicePath>,
+    /// Label being typed.
+    pub input: LineEditor,
+    /// USB ID database names of the device, looked up once per edit.
+    pub usb_ids: UsbIdNames,
+}
+
+impl EditState {
+    /// Target being edited.
+    pub fn target(&self) -> &LabelTarget {
+        &self.targets[self.target_index]
+    }
 }
 
 /// TUI application state.
@@ -35,6 +59,8 @@ pub struct App {
     pub topology: UsbTopology,
     /// Configuration.
     pub config: Config,
+    /// Colour theme.
+    pub theme: Theme,
     /// Current view mode.
     pub view_mode: ViewMode,
     /// Currently selected item index.
@@ -59,29 +85,57 @@ pub struct App {
     pub startup_devices: HashSet<String>,
     /// Device paths discovered during session (in order).
     pub discovery_order: Vec<String>,
+    /// Per-bus usage and device count samples, one per refresh.
+    pub usage_history: UsageHistory,
     /// Devices marked as "seen" (clears NEW indicator).
     pub seen_devices: HashSet<String>,
-    /// Pending label edits (device path -> label).
-    pub pending_labels: HashMap<String, String>,
+    /// Pending label edits, written to the config with `w`.
+    pub pending_labels: HashMap<LabelTarget, String>,
 
     // --- Display options ---
     /// Show inline bandwidth bars in tree view.
     pub show_bandwidth_bars: bool,
     /// Details panel scroll offset.
     pub details_scroll: u16,
+    /// Endpoint drill-down scroll offset.
+    pub endpoints_scroll: u16,
+    /// Selected port in the port map (index into `physical_ports()`).
+    pub ports_selected: usize,
 
     // --- Edit mode ---
     /// Active edit state (if editing a label).
     pub edit_mode: Option<EditState>,
 
+    // --- Search ---
+    /// Search prompt being typed (None when not searching).
+    pub search_input: Option<String>,
+    /// Last search query, used by n/N and the filter.
+    pub search_query: String,
+    /// Limit the tree to search matches and their ancestors.
+    pub filter_enabled: bool,
+    /// Quick filter limiting the tree to devices in a given state.
+    pub quick_filter: Option<QuickFilter>,
+
     // --- Status message ---
     /// Temporary status message to display.
     pub status_message: Option<(String, std::time::Instant)>,
+
+    // --- Alerts ---
+    /// Alerts fired by the last refresh, waiting to be dispatched.
+    pub pending_alerts: Vec<Alert>,
+
+    // --- Event log ---
+    /// Topology changes seen this session (oldest first).
+    pub event_log: Vec<HistoryEvent>,
+    /// Show the event log pane.
+    pub show_event_log: bool,
 }
 
 impl App {
     /// Create a new app with topology and config.
-    pub fn new(topology: UsbTopology, config: Config) -> Self {
+    pub fn new(topology: UsbTopology, mut config: Config) -> Self {
+        config.resolve_topology(&topology);
+
         // Default: expand all controllers
         let mut expanded = HashSet::new();
         for controller in topology.controllers.values() {
@@ -91,9 +145,14 @@ impl App {
         // Capture all device paths present at startup
         let startup_devices: HashSet<String> = topology.all_device_paths().collect();
 
+        let theme = Theme::from_config(&config);
+        let mut usage_history = UsageHistory::default();
+        usage_history.record(&topology);
+
         Self {
             topology,
             config,
+            theme,
             view_mode: ViewMode::Tree,
             selected: 0,
             scroll_offset: 0,
@@ -105,12 +164,22 @@ impl App {
             selected_bus: None,
             startup_devices,
             discovery_order: Vec::new(),
+            usage_history,
             seen_devices: HashSet::new(),
             pending_labels: HashMap::new(),
             show_bandwidth_bars: false,
             details_scroll: 0,
+            endpoints_scroll: 0,
+            ports_selected: 0,
             edit_mode: None,
+            search_input: None,
+            search_query: String::new(),
+            filter_enabled: false,
+            quick_filter: None,
             status_message: None,
+            pending_alerts: Vec::new(),
+            event_log: Vec::new(),
+            show_event_log: false,
         }
     }
 
@@ -122,6 +191,38 @@ impl App {
                 self.discovery_order.push(path);
             }
         }
+        self.config.resolve_topology(&topology);
+        let diff = diff_topologies(&self.topology, &topology);
+        if !self.config.alerts.is_empty() {
+            self.pending_alerts.extend(evaluate_alerts(
+                &self.config,
+                &self.topology,
+                &topology,
+                &diff,
+            ));
+        }
+        if !diff.is_empty() {
+            let mut events = HistoryEvent::from_diff(&diff, now_secs());
+            // Prefer configured labels over product strings
+            for event in &mut events {
+                let device = event
+                    .path
+                    .as_ref()
+                    .map(|p| DevicePath::new(p.as_str()))
+                    .and_then(|p| {
+                        topology
+                            .get_device(&p)
+                            .or_else(|| self.topology.get_device(&p))
+                    });
+                if let Some(device) = device {
+                    event.name = Some(self.device_label(device));
+                }
+            }
+            self.event_log.extend(events);
+            let excess = self.event_log.len().saturating_sub(EVENT_LOG_CAPACITY);
+            self.event_log.drain(..excess);
+        }
+        self.usage_history.record(&topology);
         self.topology = topology;
         self.last_refresh = std::time::Instant::now();
     }
@@ -130,7 +231,9 @@ impl App {
     pub fn is_new_device(&self, path: &str) -> bool {
         !self.startup_devices.contains(path)
             && !self.seen_devices.contains(path)
-            && !self.pending_labels.contains_key(path)
+            && !self
+                .pending_labels
+                .contains_key(&LabelTarget::Device(path.to_string()))
             && !self.config.devices.contains_key(path)
     }
 
@@ -151,11 +254,17 @@ impl App {
         self.seen_devices.insert(path.to_string());
     }
 
-    /// Set a pending label for a device.
-    pub fn set_pending_label(&mut self, path: String, label: String) {
-        self.pending_labels.insert(path.clone(), label);
-        // Also mark as seen
-        self.seen_devices.insert(path);
+    /// Set a pending label.
+    pub fn set_pending_label(&mut self, target: LabelTarget, label: String) {
+        self.pending_labels.insert(target, label);
+    }
+
+    /// Label for a target: pending edit first, then config.
+    pub fn label_for(&self, target: &LabelTarget) -> Option<String> {
+        self.pending_labels
+            .get(target)
+            .cloned()
+            .or_else(|| self.config.label_for(target))
     }
 
     /// Get count of pending labels.
@@ -176,6 +285,11 @@ impl App {
         self.status_message = Some((msg, std::time::Instant::now()));
     }
 
+    /// Take alerts fired since the last call.
+    pub fn take_alerts(&mut self) -> Vec<Alert> {
+        std::mem::take(&mut self.pending_alerts)
+    }
+
     /// Get current status message if not expired.
     pub fn status(&self) -> Option<&str> {
         self.status_message.as_ref().and_then(|(msg, time)| {
@@ -187,39 +301,135 @@ impl App {
         })
     }
 
-    /// Start editing a label for the selected device.
-    pub fn start_edit(&mut self) {
+    /// Things the current selection can be labelled as, most specific first.
+    pub fn label_targets(&self) -> Vec<LabelTarget> {
+        let mut targets = Vec::new();
+
+        if self.view_mode == ViewMode::Ports {
+            let ports = self.physical_ports();
+            if let Some(port) = ports.get(self.ports_selected) {
+                targets.push(LabelTarget::PhysicalPort(port.location.clone()));
+            }
+        }
+
         if let Some(device) = self.get_selected_device() {
-            // Build product key: VID:PID:iSerial or VID:PID
-            let product_key = if let Some(serial) = &device.serial {
-                format!(
-                    "{:04x}:{:04x}:{}",
-                    device.vendor_id, device.product_id, serial
-                )
-            } else {
-                format!("{:04x}:{:04x}", device.vendor_id, device.product_id)
-            };
+            targets.push(LabelTarget::Product(device.config_key()));
+            targets.push(LabelTarget::Device(device.path.0.clone()));
+            if self.view_mode != ViewMode::Ports
+                && let Some(loc) = self.device_port_location(device)
+            {
+                targets.push(LabelTarget::PhysicalPort(loc));
+            }
+            return targets;
+        }
 
-            // Display name for dialog
-            let display_name = if device.serial.is_some() {
-                product_key.clone()
-            } else {
-                format!("{} (no serial)", product_key)
-            };
+        if self.view_mode != ViewMode::Ports {
+            match self.visible_items().get(self.selected) {
+                Some(TreeItem::Controller { pci_address, .. }) => {
+                    targets.push(LabelTarget::Controller(pci_address.clone()));
+                }
+                Some(TreeItem::Bus { bus_num, .. }) => {
+                    targets.push(self.config.bus_target(*bus_num));
+                }
+                _ => {}
+            }
+        }
+        targets
+    }
 
-            // Pre-populate with existing pending label or empty
-            let existing = self
-                .pending_labels
-                .get(&product_key)
-                .cloned()
-                .unwrap_or_default();
-
-            self.edit_mode = Some(EditState {
-                product_key,
-                display_name,
-                input: existing.clone(),
-                cursor: existing.len(),
-            });
+    /// Start editing a label for the current selection.
+    pub fn start_edit(&mut self) {
+        let targets = self.label_targets();
+        if targets.is_empty() {
+            return;
+        }
+        let existing = self.label_for(&targets[0]).unwrap_or_default();
+        let usb_ids = self
+            .get_selected_device()
+            .map(|device| lookup_usb_ids(device.vendor_id, device.product_id))
+            .unwrap_or_default();
+        let input = LineEditor::new(existing)
+            .with_suggestions(self.label_suggestions(&targets[0], &usb_ids));
+        self.edit_mode = Some(EditState {
+            targets,
+            target_index: 0,
+            device_path: self.selected_device.clone(),
+            input,
+            usb_ids,
+        });
+    }
+
+    /// Ready-made labels for a target: device strings and USB ID database
+    /// names (`usb_ids`) for devices, the location for physical ports.
+    pub fn label_suggestions(&self, target: &LabelTarget, usb_ids: &UsbIdNames) -> Vec<String> {
+        let mut suggestions = Vec::new();
+        match target {
+            LabelTarget::Product(_) | LabelTarget::Device(_) => {
+                if let Some(device) = self.get_selected_device() {
+                    suggestions.extend(device.product.clone());
+                    if let (Some(manufacturer), Some(product)) =
+                        (&device.manufacturer, &device.product)
+                        && !product.starts_with(manufacturer.as_str())
+                    {
+                        suggestions.push(format!("{} {}", manufacturer, product));
+                    }
+                    if let Some(product) = &usb_ids.product {
+                        suggestions.push(product.clone());
+                        if let Some(vendor) = &usb_ids.vendor {
+                            suggestions.push(format!("{} {}", vendor, product));
+                        }
+                    }
+                }
+            }
+            LabelTarget::PhysicalPort(loc) => {
+                let display = loc.display();
+                let mut chars = display.chars();
+                if let Some(first) = chars.next() {
+                    suggestions.push(first.to_uppercase().chain(chars).collect());
+                }
+            }
+            LabelTarget::Controller(_) | LabelTarget::Bus { .. } => {}
+        }
+
+        let current = self.label_for(target);
+        let mut unique = Vec::new();
+        for suggestion in suggestions {
+            let suggestion = suggestion.trim().to_string();
+            if !suggestion.is_empty()
+                && current.as_deref() != Some(suggestion.as_str())
+                && !unique.contains(&suggestion)
+            {
+                unique.push(suggestion);
+            }
+        }
+        unique
+    }
+
+    /// Switch the edit to the next (or previous) label target.
+    ///
+    /// Input that still holds the old target's label is replaced with the new
+    /// target's label; typed text is kept.
+    pub fn cycle_edit_target(&mut self, forward: bool) {
+        let Some(edit) = &self.edit_mode else {
+            return;
+        };
+        let len = edit.targets.len();
+        let next = if forward {
+            (edit.target_index + 1) % len
+        } else {
+            (edit.target_index + len - 1) % len
+        };
+        let old_label = self.label_for(edit.target()).unwrap_or_default();
+        let new_label = self.label_for(&edit.targets[next]).unwrap_or_default();
+        let suggestions = self.label_suggestions(&edit.targets[next], &edit.usb_ids);
+
+        if let Some(edit) = &mut self.edit_mode {
+            edit.target_index = next;
+            let typed = edit.input.text() != old_label && edit.input.suggestion_index().is_none();
+            if !typed {
+                edit.input = LineEditor::new(new_label);
+            }
+            edit.input.set_suggestions(suggestions);
         }
     }
 
@@ -233,10 +443,276 @@ impl App {
         if let Some(edit) = self.edit_mode.take()
             && !edit.input.is_empty()
         {
-            self.set_pending_label(edit.product_key, edit.input);
+            let target = edit.target().clone();
+            self.set_status(format!("Label for {} pending (w to write)", target));
+            self.set_pending_label(target, edit.input.into_text());
+            if let Some(path) = edit.device_path {
+                self.seen_devices.insert(path.0);
+            }
         }
     }
 
+    /// Insert pasted text into the open prompt, if any.
+    pub fn paste(&mut self, text: &str) {
+        if let Some(edit) = &mut self.edit_mode {
+            edit.input.insert_str(text);
+        } else if self.search_input.is_some() {
+            for c in text.chars().filter(|c| !c.is_control()) {
+                self.search_push(c);
+            }
+        }
+    }
+
+    /// Start typing a search query.
+    pub fn start_search(&mut self) {
+        self.search_input = Some(String::new());
+    }
+
+    /// Append a character to the search prompt and jump to the first match.
+    pub fn search_push(&mut self, c: char) {
+        if let Some(input) = &mut self.search_input {
+            input.push(c);
+            self.search_query = input.clone();
+            self.jump_to_match(true, true);
+        }
+    }
+
+    /// Remove the last character from the search prompt.
+    pub fn search_pop(&mut self) {
+        if let Some(input) = &mut self.search_input {
+            input.pop();
+            self.search_query = input.clone();
+            self.jump_to_match(true, true);
+        }
+    }
+
+    /// Close the search prompt, keeping the query for n/N and the filter.
+    pub fn confirm_search(&mut self) {
+        self.search_input = None;
+        if !self.search_query.is_empty() {
+            let count = self.search_matches().len();
+            self.set_status(format!("{} match(es) for '{}'", count, self.search_query));
+        }
+        self.clamp_selection();
+    }
+
+    /// Close the search prompt and clear the query.
+    pub fn cancel_search(&mut self) {
+        self.search_input = None;
+        self.search_query.clear();
+        self.filter_enabled = false;
+        self.clamp_selection();
+    }
+
+    /// Jump to the next (or previous) search match, expanding its bus.
+    pub fn next_match(&mut self, forward: bool) {
+        if self.search_query.is_empty() {
+            self.set_status("No search query (press / to search)".to_string());
+            return;
+        }
+        self.jump_to_match(forward, false);
+    }
+
+    /// Toggle limiting the tree to search matches.
+    pub fn toggle_filter(&mut self) {
+        if self.search_query.is_empty() && !self.filter_enabled {
+            self.set_status("No search query (press / to search)".to_string());
+            return;
+        }
+        self.filter_enabled = !self.filter_enabled;
+        self.clamp_selection();
+    }
+
+    /// Cycle the quick filter (periodic, unconfigured, new, off).
+    pub fn cycle_quick_filter(&mut self) {
+        self.quick_filter = QuickFilter::cycle(self.quick_filter);
+        match self.quick_filter {
+            Some(filter) => self.set_status(format!("Filter: {} devices", filter.name())),
+            None => self.set_status("Quick filter off".to_string()),
+        }
+        self.clamp_selection();
+    }
+
+    /// Turn off the search filter and quick filter.
+    pub fn clear_filters(&mut self) {
+        self.filter_enabled = false;
+        self.quick_filter = None;
+        self.clamp_selection();
+    }
+
+    /// Whether a search or quick filter is limiting the tree.
+    pub fn filter_active(&self) -> bool {
+        (self.filter_enabled && !self.search_query.is_empty()) || self.quick_filter.is_some()
+    }
+
+    /// Describe the active filters (for the tree title).
+    pub fn filter_description(&self) -> Option<String> {
+        let mut parts = Vec::new();
+        if self.filter_enabled && !self.search_query.is_empty() {
+            parts.push(format!("/{}", self.search_query));
+        }
+        if let Some(filter) = self.quick_filter {
+            parts.push(filter.name().to_string());
+        }
+        (!parts.is_empty()).then(|| parts.join(", "))
+    }
+
+    /// Devices matching the search query, in tree order.
+    pub fn search_matches(&self) -> Vec<DevicePath> {
+        self.devices_tree_order()
+            .into_iter()
+            .filter(|d| self.passes_quick_filter(d))
+            .filter(|d| device_matches(d, &self.device_label(d), &self.search_query))
+            .map(|d| d.path.clone())
+            .collect()
+    }
+
+    /// All devices in tree order (controller, bus, port path).
+    fn devices_tree_order(&self) -> Vec<&UsbDevice> {
+        self.topology
+            .controllers_sorted()
+            .into_iter()
+            .flat_map(|c| [c.usb2_bus, c.usb3_bus])
+            .flatten()
+            .filter_map(|bus_num| self.topology.buses.get(&bus_num))
+            .flat_map(|bus| bus.devices_tree_order())
+            .collect()
+    }
+
+    fn jump_to_match(&mut self, forward: bool, include_current: bool) {
+        let matches = self.search_matches();
+        if matches.is_empty() {
+            if !self.search_query.is_empty() {
+                self.set_status(format!("No matches for '{}'", self.search_query));
+            }
+            return;
+        }
+
+        // Position of the current selection in tree order
+        let order: Vec<DevicePath> = self
+            .devices_tree_order()
+            .into_iter()
+            .map(|d| d.path.clone())
+            .collect();
+        let position = |path: &DevicePath| order.iter().position(|p| p == path);
+        let current = self.selected_device.as_ref().and_then(position);
+
+        let target = match current {
+            None if forward => 0,
+            None => matches.len() - 1,
+            Some(current) => {
+                let positions: Vec<usize> = matches.iter().filter_map(position).collect();
+                if forward {
+                    positions
+                        .iter()
+                        .position(|&p| p > current || (include_current && p == current))
+                        .unwrap_or(0)
+                } else {
+                    positions
+                        .iter()
+                        .rposition(|&p| p < current)
+                        .unwrap_or(matches.len() - 1)
+                }
+            }
+        };
+
+        let path = matches[target].clone();
+        self.view_mode = ViewMode::Tree;
+        self.reveal(&path);
+        self.select_path(&path);
+        if self.search_input.is_none() {
+            self.set_status(format!(
+                "Match {}/{} for '{}'",
+                target + 1,
+                matches.len(),
+                self.search_query
+            ));
+        }
+    }
+
+    /// Expand the controller and bus containing a device.
+    fn reveal(&mut self, path: &DevicePath) {
+        if let Some(bus_num) = path.bus_num() {
+            if let Some(controller) = self.topology.get_controller_for_bus(bus_num) {
+                self.expanded.insert(controller.id.0.clone());
+            }
+            self.expanded.insert(format!("bus{}", bus_num));
+        }
+    }
+
+    /// Select a device if it is visible.
+    fn select_path(&mut self, path: &DevicePath) {
+        if let Some(index) = self
+            .visible_items()
+            .iter()
+            .position(|item| item.key() == path.0)
+        {
+            self.selected = index;
+            self.update_selected_device();
+        }
+    }
+
+    /// Keep the selection valid after the visible items change.
+    fn clamp_selection(&mut self) {
+        if let Some(path) = self.selected_device.clone()
+            && let Some(index) = self
+                .visible_items()
+                .iter()
+                .position(|item| item.key() == path.0)
+        {
+            self.selected = index;
+            return;
+        }
+        let len = self.visible_items().len();
+        self.selected = self.selected.min(len.saturating_sub(1));
+        self.update_selected_device();
+    }
+
+    fn passes_quick_filter(&self, device: &UsbDevice) -> bool {
+        match self.quick_filter {
+            None => true,
+            Some(QuickFilter::Periodic) => !device.periodic_endpoints().is_empty(),
+            Some(QuickFilter::Unconfigured) => !device.is_configured,
+            Some(QuickFilter::New) => self.is_new_device(&device.path.0),
+        }
+    }
+
+    /// Paths of devices passing the filters plus their ancestor hubs, or
+    /// `None` when no filter is active.
+    fn filtered_paths(&self) -> Option<HashSet<String>> {
+        if !self.filter_active() {
+            return None;
+        }
+        let mut kept = HashSet::new();
+        for device in self.devices_tree_order() {
+            let searched = !self.filter_enabled
+                || device_matches(device, &self.device_label(device), &self.search_query);
+            if searched && self.passes_quick_filter(device) {
+                let mut path = Some(device.path.clone());
+                while let Some(current) = path {
+                    path = current.parent();
+                    if !kept.insert(current.0) {
+                        break;
+                    }
+                }
+            }
+        }
+        Some(kept)
+    }
+
+    fn bus_has_kept_device(&self, bus_num: u8, kept: &HashSet<String>) -> bool {
+        self.topology.buses.get(&bus_num).is_some_and(|bus| {
+            bus.devices_tree_order()
+                .iter()
+                .any(|d| kept.contains(&d.path.0))
+        })
+    }
+
+    /// Toggle the event log pane.
+    pub fn toggle_event_log(&mut self) {
+        self.show_event_log = !self.show_event_log;
+    }
+
     /// Toggle bandwidth bar display.
     pub fn toggle_bandwidth_bars(&mut self) {
         self.show_bandwidth_bars = !self.show_bandwidth_bars;
@@ -305,6 +781,46 @@ impl App {
         self.update_selected_device();
     }
 
+    /// Select a visible item by index (e.g. from a mouse click).
+    pub fn select_index(&mut self, index: usize) {
+        if index < self.visible_items().len() {
+            self.selected = index;
+            self.update_selected_device();
+        }
+    }
+
+    /// Adjust the list scroll offset so the selection stays within `rows`.
+    pub fn fit_scroll(&mut self, rows: usize) {
+        let len = self.visible_items().len();
+        let rows = rows.max(1);
+        if self.selected < self.scroll_offset {
+            self.scroll_offset = self.selected;
+        } else if self.selected >= self.scroll_offset + rows {
+            self.scroll_offset = self.selected + 1 - rows;
+        }
+        self.scroll_offset = self.scroll_offset.min(len.saturating_sub(rows));
+    }
+
+    /// Scroll the list by `delta` rows, keeping the selection on screen.
+    pub fn scroll_list(&mut self, delta: i32, rows: usize) {
+        let len = self.visible_items().len();
+        let rows = rows.max(1);
+        let max_offset = len.saturating_sub(rows);
+        self.scroll_offset = if delta < 0 {
+            self.scroll_offset
+                .saturating_sub(delta.unsigned_abs() as usize)
+        } else {
+            (self.scroll_offset + delta as usize).min(max_offset)
+        };
+
+        let last_visible = (self.scroll_offset + rows - 1).min(len.saturating_sub(1));
+        let clamped = self.selected.clamp(self.scroll_offset, last_visible);
+        if clamped != self.selected {
+            self.selected = clamped;
+            self.update_selected_device();
+        }
+    }
+
     /// Jump to top.
     pub fn goto_top(&mut self) {
         self.selected = 0;
@@ -359,26 +875,87 @@ impl App {
     pub fn toggle_view_mode(&mut self) {
         self.view_mode = match self.view_mode {
             ViewMode::Tree => ViewMode::Summary,
-            ViewMode::Summary => ViewMode::Tree,
+            ViewMode::Summary | ViewMode::Endpoints | ViewMode::Ports => ViewMode::Tree,
         };
         self.selected = 0;
+        self.scroll_offset = 0;
     }
 
     /// Set view mode.
     pub fn set_view_mode(&mut self, mode: ViewMode) {
         self.view_mode = mode;
         self.selected = 0;
+        self.scroll_offset = 0;
+    }
+
+    /// Open the endpoint drill-down for the selected device.
+    pub fn open_endpoints(&mut self) {
+        if self.selected_device.is_some() {
+            self.view_mode = ViewMode::Endpoints;
+            self.endpoints_scroll = 0;
+        }
+    }
+
+    /// Return from the endpoint drill-down to the tree, keeping the selection.
+    pub fn close_endpoints(&mut self) {
+        self.view_mode = ViewMode::Tree;
+    }
+
+    /// Scroll the endpoint drill-down.
+    pub fn scroll_endpoints(&mut self, delta: i16) {
+        self.endpoints_scroll = self.endpoints_scroll.saturating_add_signed(delta);
+    }
+
+    /// Physical ports with a known chassis location, in map order.
+    pub fn physical_ports(&self) -> Vec<PhysicalPort> {
+        physical_ports(&self.topology)
+    }
+
+    /// Open the physical port map.
+    pub fn open_ports(&mut self) {
+        self.view_mode = ViewMode::Ports;
+        self.move_port_selection(0);
+    }
+
+    /// Return from the port map to the tree, keeping the tree selection.
+    pub fn close_ports(&mut self) {
+        self.view_mode = ViewMode::Tree;
+        self.update_selected_device();
+    }
+
+    /// Move the port map selection.
+    pub fn move_port_selection(&mut self, delta: i32) {
+        self.select_port(self.ports_selected.saturating_add_signed(delta as isize));
     }
 
-    /// Is item expanded?
+    /// Select a port on the map; details follow the plugged-in device.
+    pub fn select_port(&mut self, index: usize) {
+        let ports = self.physical_ports();
+        if ports.is_empty() {
+            self.ports_selected = 0;
+            return;
+        }
+        self.ports_selected = index.min(ports.len() - 1);
+
+        let port = &ports[self.ports_selected];
+        self.details_scroll = 0;
+        self.selected_device = port.devices.first().cloned();
+        self.selected_bus = port
+            .devices
+            .first()
+            .and_then(|d| d.bus_num())
+            .or_else(|| port.root_ports.first().map(|r| r.bus_num));
+    }
+
+    /// Is item expanded? Everything is expanded while a filter is active.
     pub fn is_expanded(&self, key: &str) -> bool {
-        self.expanded.contains(key)
+        self.filter_active() || self.expanded.contains(key)
     }
 
     /// Get visible tree items based on expansion state.
     pub fn visible_items(&self) -> Vec<TreeItem> {
         match self.view_mode {
-            ViewMode::Tree => self.tree_items(),
+            ViewMode::Tree | ViewMode::Endpoints | ViewMode::Ports => self.tree_items(),
             ViewMode::Summary => self.summary_items(),
         }
     }
@@ -386,12 +963,22 @@ impl App {
     /// Generate tree items.
     fn tree_items(&self) -> Vec<TreeItem> {
         let mut items = Vec::new();
+        let kept = self.filtered_paths();
 
         for controller in self.topology.controllers_sorted() {
+            if let Some(kept) = &kept {
+                let has_match = [controller.usb2_bus, controller.usb3_bus]
+                    .into_iter()
+                    .flatten()
+                    .any(|bus_num| self.bus_has_kept_device(bus_num, kept));
+                if !has_match {
+                    continue;
+                }
+            }
+
             items.push(TreeItem::Controller {
                 id: controller.id.0.clone(),
                 label: self
-                    .config
                     .controller_label(&controller.pci_address)
                     .unwrap_or_else(|| controller.pci_address.clone()),
                 pci_address: controller.pci_address.clone(),
@@ -401,11 +988,11 @@ impl App {
             if self.is_expanded(&controller.id.0) {
                 // Add USB 2.0 bus
                 if let Some(bus_num) = controller.usb2_bus {
-                    self.add_bus_items(&mut items, bus_num, 1);
+                    self.add_bus_items(&mut items, bus_num, 1, kept.as_ref());
                 }
                 // Add USB 3.x bus
                 if let Some(bus_num) = controller.usb3_bus {
-                    self.add_bus_items(&mut items, bus_num, 1);
+                    self.add_bus_items(&mut items, bus_num, 1, kept.as_ref());
                 }
             }
         }
@@ -414,23 +1001,36 @@ impl App {
     }
 
     /// Add bus and its devices to items list.
-    fn add_bus_items(&self, items: &mut Vec<TreeItem>, bus_num: u8, base_depth: usize) {
+    fn add_bus_items(
+        &self,
+        items: &mut Vec<TreeItem>,
+        bus_num: u8,
+        base_depth: usize,
+        kept: Option<&HashSet<String>>,
+    ) {
+        if kept.is_some_and(|kept| !self.bus_has_kept_device(bus_num, kept)) {
+            return;
+        }
         if let Some(bus) = self.topology.buses.get(&bus_num) {
             let pool = BandwidthPool::with_usage(bus.speed, bus.periodic_bandwidth_used_bps());
 
             items.push(TreeItem::Bus {
                 bus_num,
+                speed: bus.speed,
                 speed_name: bus.speed.short_name().to_string(),
                 usage_percent: pool.periodic_usage_percent(),
                 used_bps: pool.used_periodic_bps,
                 max_bps: pool.max_periodic_bps,
                 depth: base_depth,
-                label: self.config.bus_label(bus_num),
+                label: self.bus_label(bus_num),
             });
 
             let bus_key = format!("bus{}", bus_num);
             if self.is_expanded(&bus_key) {
                 for device in bus.devices_tree_order() {
+                    if kept.is_some_and(|kept| !kept.contains(&device.path.0)) {
+                        continue;
+                    }
                     let device_depth = base_depth + 1 + device.path.depth();
                     self.add_device_item(items, device, bus, device_depth);
                 }
@@ -438,37 +1038,45 @@ impl App {
         }
     }
 
+    /// Display label for a device: the config's label candidates in
+    /// priority order with pending edits applied, then the product string.
+    pub fn device_label(&self, device: &UsbDevice) -> String {
+        self.config
+            .device_candidates(device)
+            .into_iter()
+            .find_map(|candidate| {
+                candidate
+                    .target
+                    .and_then(|target| self.pending_labels.get(&target).cloned())
+                    .or(candidate.label)
+            })
+            .unwrap_or_else(|| device.display_name())
+    }
+
+    /// Physical location of the port a device is plugged into.
+    pub fn device_port_location(&self, device: &UsbDevice) -> Option<PhysicalLocation> {
+        self.config.device_location(device)
+    }
+
+    /// Controller label with pending edits applied.
+    pub fn controller_label(&self, pci_address: &str) -> Option<String> {
+        self.label_for(&LabelTarget::Controller(pci_address.to_string()))
+    }
+
+    /// Bus label with pending edits applied.
+    pub fn bus_label(&self, bus_num: u8) -> Option<String> {
+        self.label_for(&self.config.bus_target(bus_num))
+    }
+
     /// Add a device item.
     fn add_device_item(
         &self,
         items: &mut Vec<TreeItem>,
         device: &UsbDevice,
-        _bus: &UsbBus,
+        bus: &UsbBus,
         depth: usize,
     ) {
-        // Check for pending label first (by product key), then config, then device name
-        let product_key = if let Some(serial) = &device.serial {
-            format!(
-                "{:04x}:{:04x}:{}",
-                device.vendor_id, device.product_id, serial
-            )
-        } else {
-            format!("{:04x}:{:04x}", device.vendor_id, device.product_id)
-        };
-        let label = self
-            .pending_labels
-            .get(&product_key)
-            .cloned()
-            .or_else(|| {
-                self.config.device_label(
-                    &device.path.0,
-                    device.vendor_id,
-                    device.product_id,
-                    device.serial.as_deref(),
-                    device.physical_location.as_ref(),
-                )
-            })
-            .unwrap_or_else(|| device.display_name());
+        let label = self.device_label(device);
 
         let bandwidth = device.periodic_bandwidth_bps();
         let is_new = self.is_new_device(&device.path.0);
@@ -480,7 +1088,9 @@ impl App {
             is_hub: device.is_hub,
             vid_pid: device.vid_pid(),
             bandwidth_bps: bandwidth,
+            speed: device.speed,
             speed_name: device.speed.short_name().to_string(),
+            bus_speed: bus.speed,
             depth,
             has_children: !device.children.is_empty(),
             is_new,
@@ -491,19 +1101,25 @@ impl App {
 
     /// Generate summary items (one per bus).
     fn summary_items(&self) -> Vec<TreeItem> {
+        let kept = self.filtered_paths();
         self.topology
             .buses_sorted()
             .iter()
+            .filter(|bus| {
+                kept.as_ref()
+                    .is_none_or(|kept| self.bus_has_kept_device(bus.bus_num, kept))
+            })
             .map(|bus| {
                 let pool = BandwidthPool::with_usage(bus.speed, bus.periodic_bandwidth_used_bps());
                 TreeItem::Bus {
                     bus_num: bus.bus_num,
+                    speed: bus.speed,
                     speed_name: bus.speed.short_name().to_string(),
                     usage_percent: pool.periodic_usage_percent(),
                     used_bps: pool.used_periodic_bps,
                     max_bps: pool.max_periodic_bps,
                     depth: 0,
-                    label: self.config.bus_label(bus.bus_num),
+                    label: self.bus_label(bus.bus_num),
                 }
             })
             .collect()
@@ -522,6 +1138,16 @@ impl App {
             .and_then(|num| self.topology.buses.get(&num))
     }
 
+    /// Speed of the bus a device is attached to, which bus-time units are
+    /// measured against (the device's own speed if the bus is unknown).
+    pub fn bus_speed(&self, device: &UsbDevice) -> UsbSpeed {
+        device
+            .path
+            .bus_num()
+            .and_then(|num| self.topology.buses.get(&num))
+            .map_or(device.speed, |bus| bus.speed)
+    }
+
     /// Get device count string.
     pub fn device_count_str(&self) -> String {
         let total = self.topology.total_device_count();
@@ -541,6 +1167,7 @@ pub enum TreeItem {
     },
     Bus {
         bus_num: u8,
+        speed: UsbSpeed,
         speed_name: String,
         usage_percent: f64,
         used_bps: u64,
@@ -554,7 +1181,10 @@ pub enum TreeItem {
         is_hub: bool,
         vid_pid: String,
         bandwidth_bps: u64,
+        speed: UsbSpeed,
         speed_name: String,
+        /// Speed of the device's bus, for bus-time units.
+        bus_speed: UsbSpeed,
         depth: usize,
         has_children: bool,
         /// Is this a "new" device (discovered this session, not yet seen/labeled)?
@@ -586,7 +1216,7 @@ impl TreeItem {
     }
 
     /// Format as display line.
-    pub fn display_line(&self) -> String {
+    pub fn display_line(&self, units: Units) -> String {
         match self {
             TreeItem::Controller { label, .. } => {
                 format!("▶ {}", label)
@@ -605,11 +1235,17 @@ impl TreeItem {
                 label,
                 is_hub,
                 bandwidth_bps,
+                bus_speed,
                 ..
             } => {
                 let icon = if *is_hub { "🔀" } else { "📱" };
                 if *bandwidth_bps > 0 {
-                    format!("{} {} [{}]", icon, label, format_bandwidth(*bandwidth_bps))
+                    format!(
+                        "{} {} [{}]",
+                        icon,
+                        label,
+                        units.format(*bandwidth_bps, *bus_speed)
+                    )
                 } else {
                     format!("{} {}", icon, label)
                 }
@@ -617,3 +1253,153 @@ impl TreeItem {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::UsbSpeed;
+
+    fn probe() -> UsbDevice {
+        UsbDevice {
+            product: Some("DAPLink CMSIS-DAP".to_string()),
+            serial: Some("ABC".to_string()),
+            ..UsbDevice::fixture("1-1", 0x0d28, 0x0204)
+        }
+    }
+
+    #[test]
+    fn test_device_label_pending_edits() {
+        let mut config: Config = toml::from_str(
+            r#"
+            [devices]
+            "1-1" = "Bench probe"
+            "#,
+        )
+        .unwrap();
+        let topology = UsbTopology::fixture(1, vec![probe()]);
+        config.apply_defaults_from_topology(&topology);
+        let mut app = App::new(topology, config);
+        let device = probe();
+        // The device-path label wins over the auto-detected product name
+        assert_eq!(app.device_label(&device), "Bench probe");
+
+        app.set_pending_label(LabelTarget::Device("1-1".to_string()), "Left".to_string());
+        assert_eq!(app.device_label(&device), "Left");
+
+        app.set_pending_label(
+            LabelTarget::Product(device.config_key()),
+            "Mine".to_string(),
+        );
+        assert_eq!(app.device_label(&device), "Mine");
+    }
+
+    #[test]
+    fn test_device_label_from_rule() {
+        let mut config: Config = toml::from_str(
+            r#"
+            [[label_rules]]
+            label = "Probe #{serial}"
+            product = "(?i)cmsis-dap"
+            "#,
+        )
+        .unwrap();
+        let topology = UsbTopology::fixture(1, vec![probe()]);
+        config.apply_defaults_from_topology(&topology);
+        let mut app = App::new(topology, config);
+        assert_eq!(app.device_label(&probe()), "Probe #ABC");
+        app.search_query = "probe #abc".to_string();
+        assert_eq!(app.search_matches(), vec![DevicePath::new("1-1")]);
+    }
+
+    #[test]
+    fn test_bus_time_uses_bus_speed() {
+        use crate::model::{Direction, Endpoint, TransferType};
+
+        // Full-speed keyboard behind a high-speed hub: 64B every 1ms frame
+        let keyboard = UsbDevice {
+            speed: UsbSpeed::Full,
+            endpoints: vec![Endpoint {
+                address: 0x81,
+                transfer_type: TransferType::Interrupt,
+                direction: Direction::In,
+                max_packet_size: 64,
+                b_interval: 1,
+                interval_str: "1ms".to_string(),
+                interface: 0,
+            }],
+            ..UsbDevice::fixture("1-1", 0x046d, 0xc31c)
+        };
+        let mut app = App::new(
+            UsbTopology::fixture(1, vec![keyboard.clone()]),
+            Config::default(),
+        );
+        app.expand_all();
+        assert_eq!(app.bus_speed(&keyboard), UsbSpeed::High);
+        let line = app
+            .tree_items()
+            .iter()
+            .find(|item| matches!(item, TreeItem::Device { .. }))
+            .map(|item| item.display_line(Units::BusTime))
+            .unwrap();
+        assert!(line.ends_with("[0.1 µs/µframe]"), "{}", line);
+    }
+
+    #[test]
+    fn test_event_log_labels_and_capacity() {
+        let config: Config = toml::from_str(
+            r#"
+            [products]
+            "0d28:0204" = "Probe"
+
+            [devices]
+            "1-2" = "Cam"
+
+            [[label_rules]]
+            label = "Keyboard"
+            vendor_id = "04d9"
+            "#,
+        )
+        .unwrap();
+        let camera = |connected_ms| UsbDevice {
+            connected_duration_ms: Some(connected_ms),
+            ..UsbDevice::fixture("1-2", 0x046d, 0x0825)
+        };
+        let keyboard = |is_configured| UsbDevice {
+            is_configured,
+            ..UsbDevice::fixture("1-3", 0x04d9, 0x1603)
+        };
+        let before = || UsbTopology::fixture(1, vec![probe(), camera(5_000), keyboard(true)]);
+        // Probe unplugged, camera reset, keyboard lost its configuration
+        let after = || UsbTopology::fixture(1, vec![camera(100), keyboard(false)]);
+
+        let mut app = App::new(before(), config);
+        app.update_topology(after());
+        let mut logged: Vec<(&str, Option<&str>)> = app
+            .event_log
+            .iter()
+            .map(|e| (e.event.as_str(), e.name.as_deref()))
+            .collect();
+        logged.sort();
+        assert_eq!(
+            logged,
+            vec![
+                ("reenumerated", Some("Cam")),
+                ("removed", Some("Probe")),
+                ("unconfigured", Some("Keyboard")),
+            ]
+        );
+
+        while app.event_log.len() < EVENT_LOG_CAPACITY {
+            app.update_topology(before());
+            app.update_topology(after());
+        }
+        app.update_topology(before());
+        assert_eq!(app.event_log.len(), EVENT_LOG_CAPACITY);
+        assert_eq!(
+            app.event_log
+                .last()
+                .map(|e| (e.event.as_str(), e.name.as_deref())),
+            Some(("added", Some("Probe")))
+        );
+    }
+}
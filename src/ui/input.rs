@@ -0,0 +1,426 @@
+//! Single-line text editor for TUI prompts.
+//!
+//! The cursor is a byte offset that always sits on a char boundary, so
+//! multi-byte input (accents, CJK, emoji) moves and deletes as whole
+//! characters.
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use unicode_width::UnicodeWidthChar;
+
+/// Kind of the last edit, used to merge consecutive typing into one undo step.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EditKind {
+    Insert,
+    Delete,
+    Other,
+}
+
+/// Editable line with cursor, undo and suggestions.
+#[derive(Debug, Clone, Default)]
+pub struct LineEditor {
+    text: String,
+    cursor: usize,
+    undo: Vec<(String, usize)>,
+    last_edit: Option<EditKind>,
+    suggestions: Vec<String>,
+    suggestion: Option<usize>,
+}
+
+impl LineEditor {
+    /// Editor holding `text`, cursor at the end.
+    pub fn new(text: impl Into<String>) -> Self {
+        let text = text.into();
+        Self {
+            cursor: text.len(),
+            text,
+            ..Default::default()
+        }
+    }
+
+    /// Offer values to cycle through with Up/Down.
+    pub fn with_suggestions(mut self, suggestions: Vec<String>) -> Self {
+        self.suggestions = suggestions;
+        self
+    }
+
+    /// Replace the suggestions, keeping the text.
+    pub fn set_suggestions(&mut self, suggestions: Vec<String>) {
+        self.suggestions = suggestions;
+        self.suggestion = None;
+    }
+
+    /// Current text.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Cursor position as a byte offset into `text()`.
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    /// Suggestions offered for this input.
+    pub fn suggestions(&self) -> &[String] {
+        &self.suggestions
+    }
+
+    /// Index of the suggestion last inserted, if any.
+    pub fn suggestion_index(&self) -> Option<usize> {
+        self.suggestion
+    }
+
+    /// The part of the text that fits in `width` terminal columns, split
+    /// into (before the cursor, character under it, after it).
+    ///
+    /// Leading text scrolls off so the cursor stays in view; widths are
+    /// display columns, so CJK and emoji take two.
+    pub fn visible(&self, width: usize) -> (&str, Option<char>, &str) {
+        let (mut before, after) = self.text.split_at(self.cursor);
+        let under = after.chars().next();
+        let after = &after[under.map_or(0, char::len_utf8)..];
+
+        let cursor_width = under.map_or(1, |c| char_width(c).max(1));
+        let mut before_width: usize = before.chars().map(char_width).sum();
+        while before_width + cursor_width > width
+            && let Some(c) = before.chars().next()
+        {
+            before = &before[c.len_utf8()..];
+            before_width -= char_width(c);
+        }
+
+        let mut used = before_width + cursor_width;
+        let mut end = 0;
+        for (i, c) in after.char_indices() {
+            used += char_width(c);
+            if used > width {
+                break;
+            }
+            end = i + c.len_utf8();
+        }
+        (before, under, &after[..end])
+    }
+
+    /// Is the input empty?
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+
+    /// Take the text, consuming the editor.
+    pub fn into_text(self) -> String {
+        self.text
+    }
+
+    /// Replace the whole text (undoable), cursor at the end.
+    pub fn set_text(&mut self, text: impl Into<String>) {
+        self.checkpoint(EditKind::Other);
+        self.text = text.into();
+        self.cursor = self.text.len();
+    }
+
+    /// Insert a character at the cursor.
+    pub fn insert_char(&mut self, c: char) {
+        self.checkpoint(EditKind::Insert);
+        self.text.insert(self.cursor, c);
+        self.cursor += c.len_utf8();
+    }
+
+    /// Insert pasted text at the cursor; line breaks and tabs become spaces.
+    pub fn insert_str(&mut self, s: &str) {
+        let clean: String = s
+            .trim_end_matches(['\r', '\n'])
+            .chars()
+            .map(|c| if c.is_control() { ' ' } else { c })
+            .collect();
+        if clean.is_empty() {
+            return;
+        }
+        self.checkpoint(EditKind::Other);
+        self.text.insert_str(self.cursor, &clean);
+        self.cursor += clean.len();
+    }
+
+    /// Delete the character before the cursor.
+    pub fn backspace(&mut self) {
+        if let Some(start) = self.prev_boundary(self.cursor) {
+            self.checkpoint(EditKind::Delete);
+            self.text.replace_range(start..self.cursor, "");
+            self.cursor = start;
+        }
+    }
+
+    /// Delete the character under the cursor.
+    pub fn delete(&mut self) {
+        if let Some(end) = self.next_boundary(self.cursor) {
+            self.checkpoint(EditKind::Delete);
+            self.text.replace_range(self.cursor..end, "");
+        }
+    }
+
+    /// Delete the word before the cursor.
+    pub fn delete_word_back(&mut self) {
+        let start = self.word_start(self.cursor);
+        if start < self.cursor {
+            self.checkpoint(EditKind::Other);
+            self.text.replace_range(start..self.cursor, "");
+            self.cursor = start;
+        }
+    }
+
+    /// Delete the word after the cursor.
+    pub fn delete_word_forward(&mut self) {
+        let end = self.word_end(self.cursor);
+        if end > self.cursor {
+            self.checkpoint(EditKind::Other);
+            self.text.replace_range(self.cursor..end, "");
+        }
+    }
+
+    /// Delete from the start of the line to the cursor.
+    pub fn kill_to_start(&mut self) {
+        if self.cursor > 0 {
+            self.checkpoint(EditKind::Other);
+            self.text.replace_range(..self.cursor, "");
+            self.cursor = 0;
+        }
+    }
+
+    /// Delete from the cursor to the end of the line.
+    pub fn kill_to_end(&mut self) {
+        if self.cursor < self.text.len() {
+            self.checkpoint(EditKind::Other);
+            self.text.truncate(self.cursor);
+        }
+    }
+
+    /// Move one character left.
+    pub fn left(&mut self) {
+        self.move_to(self.prev_boundary(self.cursor).unwrap_or(0));
+    }
+
+    /// Move one character right.
+    pub fn right(&mut self) {
+        self.move_to(self.next_boundary(self.cursor).unwrap_or(self.text.len()));
+    }
+
+    /// Move to the start of the previous word.
+    pub fn word_left(&mut self) {
+        self.move_to(self.word_start(self.cursor));
+    }
+
+    /// Move past the end of the next word.
+    pub fn word_right(&mut self) {
+        self.move_to(self.word_end(self.cursor));
+    }
+
+    /// Move to the start of the line.
+    pub fn home(&mut self) {
+        self.move_to(0);
+    }
+
+    /// Move to the end of the line.
+    pub fn end(&mut self) {
+        self.move_to(self.text.len());
+    }
+
+    /// Revert the last edit. Returns false if there was nothing to undo.
+    pub fn undo(&mut self) -> bool {
+        match self.undo.pop() {
+            Some((text, cursor)) => {
+                self.text = text;
+                self.cursor = cursor;
+                self.last_edit = None;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Replace the text with the next (or previous) suggestion.
+    pub fn cycle_suggestion(&mut self, forward: bool) {
+        let len = self.suggestions.len();
+        if len == 0 {
+            return;
+        }
+        let next = match (self.suggestion, forward) {
+            (None, true) => 0,
+            (None, false) => len - 1,
+            (Some(i), true) => (i + 1) % len,
+            (Some(i), false) => (i + len - 1) % len,
+        };
+        self.set_text(self.suggestions[next].clone());
+        self.suggestion = Some(next);
+    }
+
+    /// Apply an editing key. Returns false if the key isn't an editing key
+    /// (e.g. Enter, Esc, Tab), leaving it to the caller.
+    pub fn handle_key(&mut self, key: KeyEvent) -> bool {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        let alt = key.modifiers.contains(KeyModifiers::ALT);
+        match key.code {
+            KeyCode::Char('a') if ctrl => self.home(),
+            KeyCode::Char('e') if ctrl => self.end(),
+            KeyCode::Char('b') if ctrl => self.left(),
+            KeyCode::Char('f') if ctrl => self.right(),
+            KeyCode::Char('b') if alt => self.word_left(),
+            KeyCode::Char('f') if alt => self.word_right(),
+            KeyCode::Char('w') if ctrl => self.delete_word_back(),
+            KeyCode::Char('d') if alt => self.delete_word_forward(),
+            KeyCode::Char('d') if ctrl => self.delete(),
+            KeyCode::Char('u') if ctrl => self.kill_to_start(),
+            KeyCode::Char('k') if ctrl => self.kill_to_end(),
+            KeyCode::Char('z') | KeyCode::Char('_') if ctrl => {
+                self.undo();
+            }
+            KeyCode::Char(_) if ctrl || alt => return false,
+            KeyCode::Char(c) => self.insert_char(c),
+            KeyCode::Backspace if ctrl || alt => self.delete_word_back(),
+            KeyCode::Backspace => self.backspace(),
+            KeyCode::Delete if ctrl || alt => self.delete_word_forward(),
+            KeyCode::Delete => self.delete(),
+            KeyCode::Left if ctrl || alt => self.word_left(),
+            KeyCode::Left => self.left(),
+            KeyCode::Right if ctrl || alt => self.word_right(),
+            KeyCode::Right => self.right(),
+            KeyCode::Home => self.home(),
+            KeyCode::End => self.end(),
+            KeyCode::Up => self.cycle_suggestion(false),
+            KeyCode::Down => self.cycle_suggestion(true),
+            _ => return false,
+        }
+        true
+    }
+
+    /// Record an undo snapshot, merging runs of typing or deleting.
+    fn checkpoint(&mut self, kind: EditKind) {
+        let merge = kind != EditKind::Other && self.last_edit == Some(kind);
+        if !merge {
+            self.undo.push((self.text.clone(), self.cursor));
+        }
+        self.last_edit = Some(kind);
+        self.suggestion = None;
+    }
+
+    fn move_to(&mut self, cursor: usize) {
+        self.cursor = cursor;
+        self.last_edit = None;
+    }
+
+    fn prev_boundary(&self, pos: usize) -> Option<usize> {
+        self.text[..pos].char_indices().next_back().map(|(i, _)| i)
+    }
+
+    fn next_boundary(&self, pos: usize) -> Option<usize> {
+        self.text[pos..].chars().next().map(|c| pos + c.len_utf8())
+    }
+
+    /// Start of the word before `pos`, skipping whitespace first.
+    fn word_start(&self, pos: usize) -> usize {
+        let before = &self.text[..pos];
+        let trimmed = before.trim_end();
+        trimmed
+            .char_indices()
+            .rev()
+            .find(|(_, c)| c.is_whitespace())
+            .map(|(i, c)| i + c.len_utf8())
+            .unwrap_or(0)
+    }
+
+    /// End of the word after `pos`, skipping whitespace first.
+    fn word_end(&self, pos: usize) -> usize {
+        let after = &self.text[pos..];
+        let skipped = after.len() - after.trim_start().len();
+        after[skipped..]
+            .char_indices()
+            .find(|(_, c)| c.is_whitespace())
+            .map(|(i, _)| pos + skipped + i)
+            .unwrap_or(self.text.len())
+    }
+}
+
+/// Display columns taken by a character (zero for control characters).
+fn char_width(c: char) -> usize {
+    c.width().unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    #[test]
+    fn test_cursor_movement_utf8() {
+        let mut editor = LineEditor::new("Café 🚀");
+        editor.left();
+        assert_eq!(editor.cursor(), "Café ".len());
+        editor.backspace();
+        editor.insert_char('—');
+        assert_eq!(editor.text(), "Café—🚀");
+        editor.home();
+        editor.delete();
+        editor.end();
+        editor.left();
+        editor.left();
+        editor.insert_char('!');
+        assert_eq!(editor.text(), "afé!—🚀");
+    }
+
+    #[test]
+    fn test_visible_by_display_width() {
+        let mut editor = LineEditor::new("ラベル🚀abc");
+        // Cursor at the end: 2+2+2+2+3 columns before it, plus the cursor
+        assert_eq!(editor.visible(6), ("🚀abc", None, ""));
+        assert_eq!(editor.visible(5), ("abc", None, ""));
+        editor.home();
+        assert_eq!(editor.visible(5), ("", Some('ラ'), "ベ"));
+        editor.right();
+        assert_eq!(editor.visible(3), ("", Some('ベ'), ""));
+        assert_eq!(editor.visible(20), ("ラ", Some('ベ'), "ル🚀abc"));
+    }
+
+    #[test]
+    fn test_word_delete() {
+        let mut editor = LineEditor::new("Left rear  port");
+        editor.delete_word_back();
+        assert_eq!(editor.text(), "Left rear  ");
+        editor.delete_word_back();
+        assert_eq!(editor.text(), "Left ");
+        editor.home();
+        editor.delete_word_forward();
+        assert_eq!(editor.text(), " ");
+    }
+
+    #[test]
+    fn test_undo_merges_typing() {
+        let mut editor = LineEditor::new("Cam");
+        for c in "era".chars() {
+            editor.insert_char(c);
+        }
+        editor.insert_str(" 2\n");
+        assert_eq!(editor.text(), "Camera 2");
+        assert!(editor.undo());
+        assert_eq!(editor.text(), "Camera");
+        assert!(editor.undo());
+        assert_eq!(editor.text(), "Cam");
+        assert!(!editor.undo());
+    }
+
+    #[test]
+    fn test_keys_and_suggestions() {
+        let mut editor =
+            LineEditor::new("").with_suggestions(vec!["DAPLink".into(), "ARM DAPLink".into()]);
+        assert!(editor.handle_key(key(KeyCode::Down, KeyModifiers::NONE)));
+        assert_eq!(editor.text(), "DAPLink");
+        editor.handle_key(key(KeyCode::Down, KeyModifiers::NONE));
+        assert_eq!(editor.text(), "ARM DAPLink");
+        editor.handle_key(key(KeyCode::Char('a'), KeyModifiers::CONTROL));
+        assert_eq!(editor.cursor(), 0);
+        editor.handle_key(key(KeyCode::Char('k'), KeyModifiers::CONTROL));
+        assert!(editor.is_empty());
+        assert!(!editor.handle_key(key(KeyCode::Enter, KeyModifiers::NONE)));
+        editor.handle_key(key(KeyCode::Char('z'), KeyModifiers::CONTROL));
+        assert_eq!(editor.text(), "ARM DAPLink");
+    }
+}
@@ -0,0 +1,395 @@
+//! Screen layout and footer hints, shared by rendering and mouse hit-testing.
+
+use crate::model::{PhysicalPort, Side};
+use crate::ui::app::{App, TreeItem, ViewMode};
+use crossterm::event::KeyCode;
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use std::ops::Range;
+
+/// Height of the event log pane, including borders.
+pub const EVENT_LOG_HEIGHT: u16 = 8;
+
+/// Areas of the main screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScreenLayout {
+    /// Tree, summary or endpoint drill-down (left).
+    pub list: Rect,
+    /// Details pane (right).
+    pub details: Rect,
+    /// Event log pane, when shown.
+    pub event_log: Option<Rect>,
+    /// Device status line.
+    pub status: Rect,
+    /// Footer with key hints.
+    pub footer: Rect,
+}
+
+impl ScreenLayout {
+    /// Split the terminal area.
+    pub fn new(area: Rect, show_event_log: bool) -> Self {
+        // Content area + device status + footer
+        let outer = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Min(0),
+                Constraint::Length(1),
+                Constraint::Length(1),
+            ])
+            .split(area);
+
+        let (panes, event_log) = if show_event_log {
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(0), Constraint::Length(EVENT_LOG_HEIGHT)])
+                .split(outer[0]);
+            (chunks[0], Some(chunks[1]))
+        } else {
+            (outer[0], None)
+        };
+
+        // Tree on left, details on right
+        let main = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
+            .split(panes);
+
+        Self {
+            list: main[0],
+            details: main[1],
+            event_log,
+            status: outer[1],
+            footer: outer[2],
+        }
+    }
+
+    /// Rows available for list items inside the list border.
+    pub fn list_rows(&self) -> usize {
+        self.list.height.saturating_sub(2) as usize
+    }
+}
+
+/// Lines above the first bus in the summary view: title and a blank line.
+pub const SUMMARY_HEADING_LINES: usize = 2;
+
+/// Lines per bus in the summary view: header, bar, usage, device count and a
+/// trailing blank line (plus one for the history sparkline, when recorded).
+pub const SUMMARY_BUS_LINES: usize = 5;
+
+/// Rows of each bus in the summary view.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SummaryLayout {
+    /// Item index and rows (inside the border) of each bus, top to bottom.
+    pub buses: Vec<(usize, Range<usize>)>,
+}
+
+impl SummaryLayout {
+    /// Lay out the bus items; `has_history` tells which buses get a sparkline.
+    pub fn new(items: &[TreeItem], has_history: impl Fn(u8) -> bool) -> Self {
+        let mut top = SUMMARY_HEADING_LINES;
+        let mut buses = Vec::new();
+        for (index, item) in items.iter().enumerate() {
+            let TreeItem::Bus { bus_num, .. } = item else {
+                continue;
+            };
+            let height = SUMMARY_BUS_LINES + usize::from(has_history(*bus_num));
+            buses.push((index, top..top + height));
+            top += height;
+        }
+        Self { buses }
+    }
+
+    /// Item index of the bus under a row.
+    pub fn index_at(&self, row: usize) -> Option<usize> {
+        self.buses
+            .iter()
+            .find(|(_, rows)| rows.contains(&row))
+            .map(|(index, _)| *index)
+    }
+}
+
+/// Height of a port box in the port map, including borders.
+pub const PORT_BOX_HEIGHT: u16 = 5;
+
+/// Areas of the physical port map.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PortMapLayout {
+    /// Titled frame for each chassis side that has ports.
+    pub sides: Vec<(String, Rect)>,
+    /// Box for each port, in the same order as the ports.
+    pub boxes: Vec<Rect>,
+    /// Middle of the chassis (also the frame for top/bottom/lid/dock ports).
+    pub chassis: Rect,
+}
+
+impl PortMapLayout {
+    /// Lay out the chassis inside `area` (the inside of the view's border).
+    ///
+    /// Back ports go along the top edge and front ports along the bottom,
+    /// with left and right ports in columns either side. Top, bottom, lid,
+    /// dock and unplaced ports sit in the middle.
+    pub fn new(area: Rect, ports: &[PhysicalPort]) -> Self {
+        let group = |side: Side| match side {
+            Side::Back => 0,
+            Side::Front => 1,
+            Side::Left => 2,
+            Side::Right => 3,
+            _ => 4,
+        };
+        let mut groups: [Vec<usize>; 5] = Default::default();
+        for (index, port) in ports.iter().enumerate() {
+            groups[group(port.side())].push(index);
+        }
+
+        let edge_height = |present: bool| {
+            if present { PORT_BOX_HEIGHT + 2 } else { 0 }
+        };
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(edge_height(!groups[0].is_empty())),
+                Constraint::Min(0),
+                Constraint::Length(edge_height(!groups[1].is_empty())),
+            ])
+            .split(area);
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Ratio(1, 3); 3])
+            .split(rows[1]);
+
+        let mut layout = Self {
+            sides: Vec::new(),
+            boxes: vec![Rect::default(); ports.len()],
+            chassis: columns[1],
+        };
+        let frames = [
+            (rows[0], Direction::Horizontal),
+            (rows[2], Direction::Horizontal),
+            (columns[0], Direction::Vertical),
+            (columns[2], Direction::Vertical),
+            (columns[1], Direction::Vertical),
+        ];
+        for (indices, (frame, direction)) in groups.iter().zip(frames) {
+            if indices.is_empty() {
+                continue;
+            }
+            let mut names: Vec<&str> = indices.iter().map(|&i| ports[i].side().name()).collect();
+            names.dedup();
+            layout.sides.push((names.join(" / "), frame));
+
+            let inner = Rect::new(
+                frame.x + 1,
+                frame.y + 1,
+                frame.width.saturating_sub(2),
+                frame.height.saturating_sub(2),
+            );
+            let constraints: Vec<Constraint> = match direction {
+                Direction::Horizontal => {
+                    vec![Constraint::Ratio(1, indices.len() as u32); indices.len()]
+                }
+                Direction::Vertical => indices
+                    .iter()
+                    .map(|_| Constraint::Length(PORT_BOX_HEIGHT))
+                    .collect(),
+            };
+            let cells = Layout::default()
+                .direction(direction)
+                .constraints(constraints)
+                .split(inner);
+            for (&index, cell) in indices.iter().zip(cells.iter()) {
+                layout.boxes[index] = *cell;
+            }
+        }
+        layout
+    }
+}
+
+/// A clickable key hint in the footer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FooterHint {
+    /// Key shown to the user.
+    pub keys: &'static str,
+    /// Action description, including trailing spacing.
+    pub action: String,
+    /// Key sent when the hint is clicked, if clicking makes sense.
+    pub code: Option<KeyCode>,
+    /// Draw the hint emphasised (pending work).
+    pub emphasis: bool,
+}
+
+impl FooterHint {
+    fn new(keys: &'static str, action: &str, code: Option<KeyCode>) -> Self {
+        Self {
+            keys,
+            action: action.to_string(),
+            code,
+            emphasis: false,
+        }
+    }
+
+    /// Rendered width in columns.
+    pub fn width(&self) -> u16 {
+        (self.keys.chars().count() + self.action.chars().count()) as u16
+    }
+}
+
+/// Key hints for the current state, empty while the footer shows a prompt or
+/// status message.
+pub fn footer_hints(app: &App) -> Vec<FooterHint> {
+    if app.search_input.is_some() || app.status().is_some() {
+        return Vec::new();
+    }
+
+    let mut hints = Vec::new();
+    if app.view_mode == ViewMode::Endpoints {
+        hints.push(FooterHint::new("j/k", " Scroll  ", None));
+        hints.push(FooterHint::new("Esc", " Back  ", Some(KeyCode::Esc)));
+    } else if app.view_mode == ViewMode::Ports {
+        hints.push(FooterHint::new("j/k", " Port  ", None));
+        if !app.label_targets().is_empty() {
+            hints.push(FooterHint::new("e", " Edit  ", Some(KeyCode::Char('e'))));
+        }
+        hints.push(FooterHint::new("Esc", " Back  ", Some(KeyCode::Esc)));
+    } else {
+        let other_view = match app.view_mode {
+            ViewMode::Tree => 's',
+            _ => 't',
+        };
+        hints.push(FooterHint::new("j/k", " Nav  ", None));
+        hints.push(FooterHint::new("Enter", " Expand  ", Some(KeyCode::Enter)));
+        hints.push(FooterHint::new("x", " All  ", Some(KeyCode::Char('x'))));
+        hints.push(FooterHint::new(
+            "t/s",
+            " View  ",
+            Some(KeyCode::Char(other_view)),
+        ));
+        hints.push(FooterHint::new("p", " Ports  ", Some(KeyCode::Char('p'))));
+        hints.push(FooterHint::new("b", " Bars  ", Some(KeyCode::Char('b'))));
+        hints.push(FooterHint::new("l", " Log  ", Some(KeyCode::Char('l'))));
+        hints.push(FooterHint::new("/", " Search  ", Some(KeyCode::Char('/'))));
+        hints.push(FooterHint::new("F", " Filter  ", Some(KeyCode::Char('F'))));
+
+        // Context-specific: show edit, and mark if a device is selected
+        if !app.label_targets().is_empty() {
+            hints.push(FooterHint::new("e", " Edit  ", Some(KeyCode::Char('e'))));
+        }
+        if app.selected_device.is_some() {
+            // Show mark-seen only for new devices
+            let items = app.visible_items();
+            if let Some(TreeItem::Device { is_new: true, .. }) = items.get(app.selected) {
+                hints.push(FooterHint::new(
+                    "m",
+                    " Mark seen  ",
+                    Some(KeyCode::Char('m')),
+                ));
+            }
+        }
+
+        // Show write if there are pending labels
+        if app.pending_label_count() > 0 {
+            let mut hint = FooterHint::new(
+                "w",
+                &format!(" Write ({})  ", app.pending_label_count()),
+                Some(KeyCode::Char('w')),
+            );
+            hint.emphasis = true;
+            hints.push(hint);
+        }
+    }
+    hints.push(FooterHint::new("?", " Help  ", Some(KeyCode::Char('?'))));
+    hints.push(FooterHint::new("q", " Quit", Some(KeyCode::Char('q'))));
+    hints
+}
+
+/// Hint under a footer column, if any.
+pub fn hint_at(hints: &[FooterHint], column: u16) -> Option<&FooterHint> {
+    let mut start = 0;
+    for hint in hints {
+        let end = start + hint.width();
+        if column >= start && column < end {
+            return Some(hint);
+        }
+        start = end;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layout_with_event_log() {
+        let area = Rect::new(0, 0, 100, 40);
+        let layout = ScreenLayout::new(area, false);
+        assert_eq!(layout.event_log, None);
+        assert_eq!(layout.list.height, 38);
+        assert_eq!(layout.list_rows(), 36);
+        assert_eq!(layout.footer.y, 39);
+
+        let layout = ScreenLayout::new(area, true);
+        assert_eq!(layout.list.height, 38 - EVENT_LOG_HEIGHT);
+        assert_eq!(layout.event_log.map(|r| r.height), Some(EVENT_LOG_HEIGHT));
+    }
+
+    #[test]
+    fn test_port_map_sides() {
+        use crate::model::{ControllerId, PhysicalLocation, RootPort};
+
+        let port = |panel: &str, port_num| PhysicalPort {
+            location: PhysicalLocation {
+                panel: panel.to_string(),
+                ..Default::default()
+            },
+            controller_id: ControllerId("usb1".to_string()),
+            root_ports: vec![RootPort {
+                bus_num: 1,
+                port_num,
+            }],
+            devices: vec![],
+        };
+        let ports = vec![port("back", 1), port("left", 2), port("right", 3)];
+        let map = PortMapLayout::new(Rect::new(0, 0, 90, 30), &ports);
+
+        let names: Vec<&str> = map.sides.iter().map(|(n, _)| n.as_str()).collect();
+        assert_eq!(names, ["Back", "Left", "Right"]);
+        let [back, left, right] = [map.boxes[0], map.boxes[1], map.boxes[2]];
+        assert!(back.y < left.y);
+        assert!(left.x < map.chassis.x && map.chassis.x < right.x);
+        assert_eq!(left.height, PORT_BOX_HEIGHT);
+    }
+
+    #[test]
+    fn test_summary_rows() {
+        use crate::model::UsbSpeed;
+
+        let bus = |bus_num| TreeItem::Bus {
+            bus_num,
+            speed: UsbSpeed::High,
+            speed_name: "480M".to_string(),
+            usage_percent: 0.0,
+            used_bps: 0,
+            max_bps: 0,
+            depth: 0,
+            label: None,
+        };
+        let layout = SummaryLayout::new(&[bus(1), bus(2), bus(3)], |b| b == 2);
+        assert_eq!(layout.buses, vec![(0, 2..7), (1, 7..13), (2, 13..18)]);
+        assert_eq!(layout.index_at(1), None);
+        assert_eq!(layout.index_at(6), Some(0));
+        assert_eq!(layout.index_at(12), Some(1));
+        assert_eq!(layout.index_at(18), None);
+    }
+
+    #[test]
+    fn test_hint_at() {
+        let hints = vec![
+            FooterHint::new("j/k", " Nav  ", None),
+            FooterHint::new("q", " Quit", Some(KeyCode::Char('q'))),
+        ];
+        assert_eq!(hint_at(&hints, 0).map(|h| h.keys), Some("j/k"));
+        assert_eq!(hint_at(&hints, 8).map(|h| h.keys), Some("j/k"));
+        assert_eq!(hint_at(&hints, 9).map(|h| h.keys), Some("q"));
+        assert_eq!(hint_at(&hints, 14).map(|h| h.keys), Some("q"));
+        assert_eq!(hint_at(&hints, 15), None);
+    }
+}
@@ -1,7 +1,19 @@
 //! TUI components.
 
 pub mod app;
+pub mod input;
+pub mod layout;
+pub mod mouse;
 pub mod render;
+pub mod samples;
+pub mod search;
+pub mod theme;
 
 pub use app::{App, EditState, TreeItem, ViewMode};
+pub use input::LineEditor;
+pub use layout::ScreenLayout;
+pub use mouse::handle_mouse;
 pub use render::render;
+pub use samples::UsageHistory;
+pub use search::QuickFilter;
+pub use theme::Theme;
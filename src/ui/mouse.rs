@@ -0,0 +1,144 @@
+//! Mouse handling for the TUI.
+//!
+//! Clicks and wheel events are hit-tested against the same [`ScreenLayout`]
+//! used for rendering, and list rows are mapped back to
+//! [`App::visible_items`] through [`App::scroll_offset`].
+
+use crate::ui::app::{App, TreeItem, ViewMode};
+use crate::ui::layout::{PortMapLayout, ScreenLayout, SummaryLayout, footer_hints, hint_at};
+use crossterm::event::{KeyCode, MouseButton, MouseEvent, MouseEventKind};
+use ratatui::layout::{Position, Rect};
+
+/// Rows scrolled per wheel step.
+const WHEEL_STEP: i32 = 3;
+
+/// Apply a mouse event to the app.
+///
+/// Returns the key to dispatch when a footer hint was clicked.
+pub fn handle_mouse(app: &mut App, mouse: MouseEvent, layout: &ScreenLayout) -> Option<KeyCode> {
+    // Prompts keep the keyboard focus
+    if app.edit_mode.is_some() || app.search_input.is_some() {
+        return None;
+    }
+    let position = Position::new(mouse.column, mouse.row);
+
+    match mouse.kind {
+        MouseEventKind::Down(MouseButton::Left) if app.show_help => {
+            app.show_help = false;
+            None
+        }
+        MouseEventKind::Down(MouseButton::Left) => {
+            if layout.footer.contains(position) {
+                let hints = footer_hints(app);
+                return hint_at(&hints, mouse.column - layout.footer.x).and_then(|h| h.code);
+            }
+            if app.view_mode == ViewMode::Ports {
+                click_port(app, layout.list, position);
+            } else if let Some((column, row)) = inner_position(layout.list, position) {
+                click_list(app, column, row);
+            }
+            None
+        }
+        MouseEventKind::ScrollDown | MouseEventKind::ScrollUp => {
+            let delta = if mouse.kind == MouseEventKind::ScrollDown {
+                WHEEL_STEP
+            } else {
+                -WHEEL_STEP
+            };
+            if layout.list.contains(position) {
+                match app.view_mode {
+                    ViewMode::Tree => app.scroll_list(delta, layout.list_rows()),
+                    ViewMode::Summary => app.move_selection(delta.signum()),
+                    ViewMode::Endpoints => app.scroll_endpoints(delta as i16),
+                    ViewMode::Ports => app.move_port_selection(delta.signum()),
+                }
+            } else if layout.details.contains(position) {
+                if delta > 0 {
+                    app.scroll_details_down();
+                } else {
+                    app.scroll_details_up();
+                }
+            }
+            None
+        }
+        _ => None,
+    }
+}
+
+/// Column and row relative to the inside of a bordered block.
+fn inner_position(area: Rect, position: Position) -> Option<(u16, u16)> {
+    let inner = Rect::new(
+        area.x + 1,
+        area.y + 1,
+        area.width.saturating_sub(2),
+        area.height.saturating_sub(2),
+    );
+    inner
+        .contains(position)
+        .then(|| (position.x - inner.x, position.y - inner.y))
+}
+
+fn click_list(app: &mut App, column: u16, row: u16) {
+    match app.view_mode {
+        ViewMode::Tree => {
+            let index = app.scroll_offset + row as usize;
+            let items = app.visible_items();
+            let Some(item) = items.get(index) else {
+                return;
+            };
+            let on_marker = marker_column(item).is_some_and(|c| column == c || column == c + 1);
+            app.select_index(index);
+            if on_marker {
+                app.toggle_expand();
+            }
+        }
+        ViewMode::Summary => {
+            let layout = SummaryLayout::new(&app.visible_items(), |bus_num| {
+                app.usage_history.peak_usage(bus_num).is_some()
+            });
+            if let Some(index) = layout.index_at(row as usize) {
+                app.select_index(index);
+            }
+        }
+        ViewMode::Endpoints | ViewMode::Ports => {}
+    }
+}
+
+fn click_port(app: &mut App, area: Rect, position: Position) {
+    let ports = app.physical_ports();
+    let inner = Rect::new(
+        area.x + 1,
+        area.y + 1,
+        area.width.saturating_sub(2),
+        area.height.saturating_sub(2),
+    );
+    let map = PortMapLayout::new(inner, &ports);
+    if let Some(index) = map.boxes.iter().position(|b| b.contains(position)) {
+        app.select_port(index);
+    }
+}
+
+/// Column of the ▶/▼ expand marker within a tree row.
+fn marker_column(item: &TreeItem) -> Option<u16> {
+    let indent = (item.depth() * 2) as u16;
+    match item {
+        TreeItem::Controller { .. } => Some(indent),
+        // After the "├─" connector
+        TreeItem::Bus { .. } => Some(indent + 2),
+        TreeItem::Device { .. } => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inner_position() {
+        let area = Rect::new(10, 5, 20, 10);
+        assert_eq!(inner_position(area, Position::new(10, 5)), None);
+        assert_eq!(inner_position(area, Position::new(11, 6)), Some((0, 0)));
+        assert_eq!(inner_position(area, Position::new(28, 13)), Some((17, 7)));
+        assert_eq!(inner_position(area, Position::new(29, 13)), None);
+    }
+}
@@ -1,13 +1,23 @@
 //! TUI rendering with ratatui.
 
-use crate::model::{BandwidthPool, ControllerType, bandwidth::bandwidth_bar, format_bandwidth};
+use crate::config::LabelTarget;
+use crate::history::format_timestamp;
+use crate::model::{
+    BandwidthPool, ControllerId, ControllerType, Endpoint, EndpointShares, UsbDevice, UsbInterface,
+    bandwidth::{bandwidth_bar, sparkline},
+};
 use crate::ui::app::{App, TreeItem, ViewMode};
+use crate::ui::input::LineEditor;
+use crate::ui::layout::{PortMapLayout, ScreenLayout, SummaryLayout, footer_hints};
+use crate::ui::theme::Theme;
 use ratatui::{
     Frame,
-    layout::{Constraint, Direction, Layout, Rect},
-    style::{Color, Modifier, Style},
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, Clear, List, ListItem, ListState, Padding, Paragraph, Wrap},
+    widgets::{
+        Block, BorderType, Borders, Clear, List, ListItem, ListState, Padding, Paragraph, Wrap,
+    },
 };
 
 /// Main render function.
@@ -18,45 +28,107 @@ pub fn render(frame: &mut Frame, app: &App) {
         return;
     }
 
-    // Main layout: content area + device status + footer
-    let outer_chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([
-            Constraint::Min(0),
-            Constraint::Length(1),
-            Constraint::Length(1),
-        ])
-        .split(frame.area());
-
-    // Content area: tree on left, details on right
-    let main_chunks = Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
-        .split(outer_chunks[0]);
-
-    // Left side: tree view or summary
-    match app.view_mode {
-        ViewMode::Tree => render_tree(frame, app, main_chunks[0]),
-        ViewMode::Summary => render_summary(frame, app, main_chunks[0]),
-    }
-
-    // Right side: details
-    render_details(frame, app, main_chunks[1]);
+    let layout = ScreenLayout::new(frame.area(), app.show_event_log);
+    render_content(frame, app, &layout);
 
     // Device status line (path + config key for easy copying)
-    render_device_status(frame, app, outer_chunks[1]);
+    render_device_status(frame, app, layout.status);
 
     // Footer with contextual keybindings
-    render_footer(frame, app, outer_chunks[2]);
+    render_footer(frame, app, layout.footer);
 
     // Help overlay if active
     if app.show_help {
-        render_help(frame);
+        render_help(frame, &app.theme);
+    }
+}
+
+/// Render the content area: tree/summary and details, plus the event log.
+fn render_content(frame: &mut Frame, app: &App, layout: &ScreenLayout) {
+    match app.view_mode {
+        ViewMode::Tree => render_tree(frame, app, layout.list),
+        ViewMode::Summary => render_summary(frame, app, layout.list),
+        ViewMode::Endpoints => render_endpoints(frame, app, layout.list),
+        ViewMode::Ports => render_ports(frame, app, layout.list),
     }
+    render_details(frame, app, layout.details);
+
+    if let Some(log_area) = layout.event_log {
+        render_event_log(frame, app, log_area);
+    }
+}
+
+/// Render the event log pane, newest entries at the bottom.
+fn render_event_log(frame: &mut Frame, app: &App, area: Rect) {
+    let theme = &app.theme;
+    let rows = area.height.saturating_sub(2) as usize;
+    let skip = app.event_log.len().saturating_sub(rows);
+
+    let lines: Vec<Line> = app
+        .event_log
+        .iter()
+        .skip(skip)
+        .map(|event| {
+            let color = match event.event.as_str() {
+                "removed" | "unconfigured" | "over_current" => theme.error,
+                "added" => theme.new,
+                "reenumerated" => theme.warn,
+                _ => theme.highlight,
+            };
+            let mut spans = vec![
+                Span::styled(
+                    format!("{} ", format_timestamp(event.timestamp)),
+                    Style::default().fg(theme.muted),
+                ),
+                Span::styled(format!("{:<18}", event.event), Style::default().fg(color)),
+                Span::styled(
+                    event
+                        .name
+                        .clone()
+                        .or_else(|| event.key.clone())
+                        .unwrap_or_default(),
+                    Style::default().fg(theme.text),
+                ),
+            ];
+            if let Some(path) = &event.path {
+                spans.push(Span::styled(
+                    format!(" [{}]", path),
+                    Style::default().fg(theme.muted),
+                ));
+            }
+            if let Some(details) = &event.details {
+                spans.push(Span::styled(
+                    format!("  {}", details),
+                    Style::default().fg(theme.muted),
+                ));
+            }
+            Line::from(spans)
+        })
+        .collect();
+
+    let title = format!(" Event Log ({}) ", app.event_log.len());
+    let paragraph = if lines.is_empty() {
+        Paragraph::new(Line::from(Span::styled(
+            "No topology changes yet",
+            Style::default().fg(theme.muted),
+        )))
+    } else {
+        Paragraph::new(lines)
+    };
+    frame.render_widget(
+        paragraph.block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(title)
+                .border_style(Style::default().fg(theme.text)),
+        ),
+        area,
+    );
 }
 
 /// Render tree view.
 fn render_tree(frame: &mut Frame, app: &App, area: Rect) {
+    let theme = &app.theme;
     let items = app.visible_items();
 
     let list_items: Vec<ListItem> = items
@@ -78,21 +150,19 @@ fn render_tree(frame: &mut Frame, app: &App, area: Rect) {
                     let expanded = app.is_expanded(id);
                     let prefix = if expanded { "▼ " } else { "▶ " };
                     let (color, type_badge) = match controller_type {
-                        ControllerType::Usb4 => (Color::Magenta, " [USB4/TB]"),
-                        ControllerType::Usb => (Color::Cyan, ""),
+                        ControllerType::Usb4 => (theme.usb4, " [USB4/TB]"),
+                        ControllerType::Usb => (theme.controller, ""),
                     };
                     let mut style = Style::default().fg(color);
                     if is_selected {
-                        style = style.bg(Color::DarkGray).add_modifier(Modifier::BOLD);
+                        style = theme.selected(style);
                     }
                     spans.push(Span::raw(prefix));
                     spans.push(Span::styled(label.clone(), style));
                     if !type_badge.is_empty() {
                         spans.push(Span::styled(
                             type_badge,
-                            Style::default()
-                                .fg(Color::Magenta)
-                                .add_modifier(Modifier::DIM),
+                            Style::default().fg(theme.usb4).add_modifier(Modifier::DIM),
                         ));
                     }
                 }
@@ -106,9 +176,9 @@ fn render_tree(frame: &mut Frame, app: &App, area: Rect) {
                     let key = format!("bus{}", bus_num);
                     let expanded = app.is_expanded(&key);
                     let prefix = if expanded { "├─▼ " } else { "├─▶ " };
-                    let mut style = Style::default().fg(Color::Yellow);
+                    let mut style = Style::default().fg(theme.bus);
                     if is_selected {
-                        style = style.bg(Color::DarkGray).add_modifier(Modifier::BOLD);
+                        style = theme.selected(style);
                     }
                     let name = label.clone().unwrap_or_else(|| format!("Bus {}", bus_num));
 
@@ -118,12 +188,12 @@ fn render_tree(frame: &mut Frame, app: &App, area: Rect) {
                     // Optional inline bandwidth bar
                     if app.show_bandwidth_bars {
                         let bar = bandwidth_bar(*usage_percent, 10);
-                        let bar_color = if *usage_percent > 80.0 {
-                            Color::Red
+                        let bar_color = if *usage_percent > app.config.settings.high_usage_percent {
+                            theme.error
                         } else if *usage_percent > 50.0 {
-                            Color::Yellow
+                            theme.warn
                         } else {
-                            Color::Green
+                            theme.ok
                         };
                         spans.push(Span::raw(" "));
                         spans.push(Span::styled(bar, Style::default().fg(bar_color)));
@@ -134,7 +204,7 @@ fn render_tree(frame: &mut Frame, app: &App, area: Rect) {
                     } else {
                         spans.push(Span::styled(
                             format!(" [{:.1}%]", usage_percent),
-                            Style::default().fg(Color::DarkGray),
+                            Style::default().fg(theme.muted),
                         ));
                     }
                 }
@@ -144,6 +214,7 @@ fn render_tree(frame: &mut Frame, app: &App, area: Rect) {
                     is_hub,
                     has_children,
                     bandwidth_bps,
+                    bus_speed,
                     is_new,
                     discovery_number,
                     is_configured,
@@ -165,10 +236,10 @@ fn render_tree(frame: &mut Frame, app: &App, area: Rect) {
 
                     let mut style = Style::default();
                     if !is_configured {
-                        style = style.fg(Color::Red);
+                        style = style.fg(theme.error);
                     }
                     if is_selected {
-                        style = style.bg(Color::DarkGray).add_modifier(Modifier::BOLD);
+                        style = theme.selected(style);
                     }
 
                     spans.push(Span::raw(prefix));
@@ -176,7 +247,7 @@ fn render_tree(frame: &mut Frame, app: &App, area: Rect) {
                     if *depth == 2 {
                         spans.push(Span::styled(
                             format!("[{}] ", path.0),
-                            Style::default().fg(Color::DarkGray),
+                            Style::default().fg(theme.muted),
                         ));
                     }
                     spans.push(Span::styled(format!("{} {}", icon, label), style));
@@ -185,12 +256,17 @@ fn render_tree(frame: &mut Frame, app: &App, area: Rect) {
                     if !is_configured {
                         spans.push(Span::styled(
                             " [NOT CONFIGURED]",
-                            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+                            Style::default()
+                                .fg(theme.error)
+                                .add_modifier(Modifier::BOLD),
                         ));
                     } else if *bandwidth_bps > 0 {
                         spans.push(Span::styled(
-                            format!(" [{}]", format_bandwidth(*bandwidth_bps)),
-                            Style::default().fg(Color::DarkGray),
+                            format!(
+                                " [{}]",
+                                app.config.units().format(*bandwidth_bps, *bus_speed)
+                            ),
+                            Style::default().fg(theme.muted),
                         ));
                     }
 
@@ -198,15 +274,13 @@ fn render_tree(frame: &mut Frame, app: &App, area: Rect) {
                     if *is_new {
                         spans.push(Span::styled(
                             " ●NEW",
-                            Style::default()
-                                .fg(Color::LightGreen)
-                                .add_modifier(Modifier::BOLD),
+                            Style::default().fg(theme.new).add_modifier(Modifier::BOLD),
                         ));
                         // Discovery order number
                         if let Some(n) = discovery_number {
                             spans.push(Span::styled(
                                 format!(" [{}]", n),
-                                Style::default().fg(Color::LightGreen),
+                                Style::default().fg(theme.new),
                             ));
                         }
                     }
@@ -241,60 +315,71 @@ fn render_tree(frame: &mut Frame, app: &App, area: Rect) {
         )
     };
 
+    let title = match app.filter_description() {
+        Some(filter) => format!("{}[filter: {}] ", title, filter),
+        None => title,
+    };
+
     let list = List::new(list_items).block(
         Block::default()
             .title(title)
             .borders(Borders::ALL)
-            .border_style(Style::default().fg(Color::White)),
+            .border_style(Style::default().fg(theme.text)),
     );
 
-    // Use ListState for automatic scroll-to-selection
-    let mut state = ListState::default().with_selected(Some(app.selected));
+    // Offset is tracked by the app so mouse clicks can be mapped back to rows
+    let mut state = ListState::default()
+        .with_selected(Some(app.selected))
+        .with_offset(app.scroll_offset);
     frame.render_stateful_widget(list, area, &mut state);
 }
 
 /// Render summary view (all buses).
 fn render_summary(frame: &mut Frame, app: &App, area: Rect) {
-    let buses = app.topology.buses_sorted();
+    let theme = &app.theme;
+    let items = app.visible_items();
+    let layout = SummaryLayout::new(&items, |bus_num| {
+        app.usage_history.peak_usage(bus_num).is_some()
+    });
 
     let mut lines = Vec::new();
     lines.push(Line::from(Span::styled(
         "Bus Overview",
         Style::default()
-            .fg(Color::Cyan)
+            .fg(theme.accent)
             .add_modifier(Modifier::BOLD),
     )));
     lines.push(Line::from(""));
 
-    for (i, bus) in buses.iter().enumerate() {
+    for (index, rows) in &layout.buses {
+        let Some(bus) = (match &items[*index] {
+            TreeItem::Bus { bus_num, .. } => app.topology.buses.get(bus_num),
+            _ => None,
+        }) else {
+            continue;
+        };
+        // Keep rows in step with mouse hit-testing (a bus missing from the
+        // topology leaves blank rows)
+        lines.resize(rows.start, Line::from(""));
         let pool = BandwidthPool::with_usage(bus.speed, bus.periodic_bandwidth_used_bps());
-        let is_selected = i == app.selected;
+        let is_selected = *index == app.selected;
 
         let style = if is_selected {
-            Style::default()
-                .bg(Color::DarkGray)
-                .add_modifier(Modifier::BOLD)
+            theme.selected(Style::default())
         } else {
             Style::default()
         };
 
         let speed_style = if bus.is_superspeed() {
-            Style::default().fg(Color::Magenta)
+            Style::default().fg(theme.usb3)
         } else {
-            Style::default().fg(Color::Green)
+            Style::default().fg(theme.usb2)
         };
 
-        let usage_color = if pool.is_critical() {
-            Color::Red
-        } else if pool.is_high_usage() {
-            Color::Yellow
-        } else {
-            Color::Green
-        };
+        let usage_color = theme.usage(pool.periodic_usage_percent(), &app.config);
 
         // Bus header with pairing info
         let label = app
-            .config
             .bus_label(bus.bus_num)
             .unwrap_or_else(|| format!("Bus {}", bus.bus_num));
 
@@ -307,7 +392,7 @@ fn render_summary(frame: &mut Frame, app: &App, area: Rect) {
         lines.push(Line::from(vec![
             Span::styled(format!("{:<20}", label), style),
             Span::styled(format!("{:>6}", bus.speed.short_name()), speed_style),
-            Span::styled(paired_info, Style::default().fg(Color::DarkGray)),
+            Span::styled(paired_info, Style::default().fg(theme.muted)),
         ]));
 
         // Bandwidth bar
@@ -325,13 +410,13 @@ fn render_summary(frame: &mut Frame, app: &App, area: Rect) {
         lines.push(Line::from(vec![
             Span::raw("  Used: "),
             Span::styled(
-                format!("{:>12}", format_bandwidth(pool.used_periodic_bps)),
-                Style::default().fg(Color::White),
+                format!("{:>12}", pool.format_used(app.config.units())),
+                Style::default().fg(theme.text),
             ),
             Span::raw(" / "),
             Span::styled(
-                format_bandwidth(pool.max_periodic_bps),
-                Style::default().fg(Color::DarkGray),
+                pool.format_max(app.config.units()),
+                Style::default().fg(theme.muted),
             ),
         ]));
 
@@ -340,26 +425,433 @@ fn render_summary(frame: &mut Frame, app: &App, area: Rect) {
             Span::raw("  Devices: "),
             Span::styled(
                 format!("{}", bus.device_count()),
-                Style::default().fg(Color::White),
+                Style::default().fg(theme.text),
+            ),
+        ]));
+
+        // Usage history (catches devices that only reserve bandwidth intermittently)
+        if let Some(peak) = app.usage_history.peak_usage(bus.bus_num) {
+            let history = app.usage_history.usage(bus.bus_num);
+            let devices = match app.usage_history.device_range(bus.bus_num) {
+                Some((min, max)) if min != max => format!("  devices {}-{}", min, max),
+                _ => String::new(),
+            };
+            lines.push(Line::from(vec![
+                Span::raw("  "),
+                Span::styled(
+                    format!("{:<30}", sparkline(&history, peak, 30)),
+                    Style::default().fg(theme.usage(peak, &app.config)),
+                ),
+                Span::styled(
+                    format!(" peak {:.1}%{}", peak, devices),
+                    Style::default().fg(theme.muted),
+                ),
+            ]));
+        }
+
+        lines.push(Line::from(""));
+        debug_assert_eq!(lines.len(), rows.end);
+    }
+
+    // Unwrapped, so each line is exactly one summary row
+    let paragraph = Paragraph::new(lines).block(
+        Block::default()
+            .title(" Bus Summary (press 't' for tree) ")
+            .borders(Borders::ALL),
+    );
+
+    frame.render_widget(paragraph, area);
+}
+
+/// Render the physical port map as a chassis schematic.
+fn render_ports(frame: &mut Frame, app: &App, area: Rect) {
+    let theme = &app.theme;
+    let units = app.config.units();
+    let ports = app.physical_ports();
+    let selected = ports.get(app.ports_selected.min(ports.len().saturating_sub(1)));
+
+    let controller_name = |id: &ControllerId| {
+        app.topology
+            .controllers
+            .get(id)
+            .map(|c| {
+                app.controller_label(&c.pci_address)
+                    .unwrap_or_else(|| c.pci_address.clone())
+            })
+            .unwrap_or_else(|| id.0.clone())
+    };
+
+    let mut title = format!(" Ports ({}) ", ports.len());
+    if let Some(port) = selected {
+        let shared = ports
+            .iter()
+            .filter(|p| p.controller_id == port.controller_id)
+            .count();
+        if shared > 1 {
+            title.push_str(&format!(
+                "[{} ports share {}] ",
+                shared,
+                controller_name(&port.controller_id)
+            ));
+        }
+    }
+    let block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.text));
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    if ports.is_empty() {
+        let paragraph = Paragraph::new(vec![
+            Line::from("No physical port locations reported by the firmware."),
+            Line::from(""),
+            Line::from(Span::styled(
+                "Ports appear here when ACPI provides physical_location for root",
+                Style::default().fg(theme.muted),
+            )),
+            Line::from(Span::styled(
+                "hub ports; name them with [[physical_ports]] in the config.",
+                Style::default().fg(theme.muted),
+            )),
+        ])
+        .wrap(Wrap { trim: true });
+        frame.render_widget(paragraph, inner);
+        return;
+    }
+
+    let map = PortMapLayout::new(inner, &ports);
+    if !map.sides.iter().any(|(_, rect)| *rect == map.chassis) {
+        frame.render_widget(
+            Paragraph::new(Span::styled("chassis", Style::default().fg(theme.muted)))
+                .alignment(Alignment::Center)
+                .block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .border_type(BorderType::Rounded)
+                        .border_style(Style::default().fg(theme.muted)),
+                ),
+            map.chassis,
+        );
+    }
+    for (name, rect) in &map.sides {
+        frame.render_widget(
+            Block::default()
+                .title(format!(" {} ", name))
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(theme.muted)),
+            *rect,
+        );
+    }
+
+    for (index, (port, rect)) in ports.iter().zip(&map.boxes).enumerate() {
+        let is_selected = index == app.ports_selected;
+        let shares_controller = selected.is_some_and(|s| s.controller_id == port.controller_id);
+        let border_style = if is_selected {
+            Style::default()
+                .fg(theme.highlight)
+                .add_modifier(Modifier::BOLD)
+        } else if shares_controller {
+            Style::default().fg(theme.warn)
+        } else {
+            Style::default().fg(theme.muted)
+        };
+
+        let name = app
+            .label_for(&LabelTarget::PhysicalPort(port.location.clone()))
+            .unwrap_or_else(|| match port.location.display() {
+                loc if loc.is_empty() => "Port".to_string(),
+                loc => loc,
+            });
+
+        // Line 1: what's plugged in
+        let devices: Vec<&UsbDevice> = port
+            .devices
+            .iter()
+            .filter_map(|path| app.topology.get_device(path))
+            .collect();
+        let plugged = match devices.first() {
+            Some(device) => {
+                let mut spans = vec![Span::styled(
+                    app.device_label(device),
+                    Style::default().fg(theme.text),
+                )];
+                if devices.len() > 1 {
+                    spans.push(Span::styled(
+                        format!(" +{}", devices.len() - 1),
+                        Style::default().fg(theme.muted),
+                    ));
+                }
+                Line::from(spans)
+            }
+            None => Line::from(Span::styled("empty", Style::default().fg(theme.muted))),
+        };
+
+        // Line 2: bus and controller, flagging controllers shared with other ports
+        let buses: Vec<String> = port.bus_nums().iter().map(|b| b.to_string()).collect();
+        let others = ports
+            .iter()
+            .filter(|p| p.controller_id == port.controller_id)
+            .count()
+            - 1;
+        let mut wiring = Vec::new();
+        if others > 0 {
+            wiring.push(Span::styled(
+                format!("⇄{} ", others),
+                Style::default().fg(theme.warn),
+            ));
+        }
+        wiring.push(Span::styled(
+            format!(
+                "Bus {} · {}",
+                buses.join("+"),
+                controller_name(&port.controller_id)
+            ),
+            Style::default().fg(theme.muted),
+        ));
+
+        // Line 3: speed and the connector's share of its bus budget
+        let usage = match devices.first() {
+            Some(device) => {
+                let bps = port.periodic_bandwidth_bps(&app.topology);
+                let bus = app
+                    .topology
+                    .buses
+                    .get(&device.path.bus_num().unwrap_or_default());
+                let budget = bus.map(|b| b.max_periodic_bandwidth_bps()).unwrap_or(0);
+                let bus_speed = bus.map_or(device.speed, |b| b.speed);
+                let share = if budget == 0 {
+                    0.0
+                } else {
+                    bps as f64 / budget as f64 * 100.0
+                };
+                let color = theme.usage(share, &app.config);
+                Line::from(vec![
+                    Span::styled(
+                        format!("{:<6}", device.speed.short_name()),
+                        Style::default().fg(if device.speed.is_superspeed() {
+                            theme.usb3
+                        } else {
+                            theme.usb2
+                        }),
+                    ),
+                    Span::styled(bandwidth_bar(share, 8), Style::default().fg(color)),
+                    Span::styled(
+                        format!(" {:.0}% {}", share, units.format(bps, bus_speed)),
+                        Style::default().fg(color),
+                    ),
+                ])
+            }
+            None => {
+                let fastest = port
+                    .bus_nums()
+                    .iter()
+                    .filter_map(|b| app.topology.buses.get(b))
+                    .map(|b| b.speed)
+                    .max_by_key(|s| s.max_periodic_bandwidth_bps());
+                Line::from(Span::styled(
+                    fastest
+                        .map(|s| format!("free, up to {}", s.short_name()))
+                        .unwrap_or_default(),
+                    Style::default().fg(theme.ok),
+                ))
+            }
+        };
+
+        let paragraph = Paragraph::new(vec![plugged, Line::from(wiring), usage]).block(
+            Block::default()
+                .title(format!(" {} ", name))
+                .borders(Borders::ALL)
+                .border_style(border_style),
+        );
+        frame.render_widget(paragraph, *rect);
+    }
+}
+
+/// Render the interface/endpoint drill-down for the selected device.
+fn render_endpoints(frame: &mut Frame, app: &App, area: Rect) {
+    let theme = &app.theme;
+    let units = app.config.units();
+    let mut lines = Vec::new();
+
+    let Some(device) = app.get_selected_device() else {
+        let paragraph = Paragraph::new("No device selected").block(
+            Block::default()
+                .title(" Endpoints (Esc to return) ")
+                .borders(Borders::ALL),
+        );
+        frame.render_widget(paragraph, area);
+        return;
+    };
+
+    // Bus periodic budget that every endpoint share is measured against
+    let bus = device
+        .path
+        .bus_num()
+        .and_then(|num| app.topology.buses.get(&num));
+    let shares = EndpointShares::new(device, bus.map(|b| b.speed));
+    let bus_speed = bus.map_or(device.speed, |b| b.speed);
+
+    if let Some(bus) = bus {
+        let pool = BandwidthPool::with_usage(bus.speed, bus.periodic_bandwidth_used_bps());
+        lines.push(Line::from(vec![
+            Span::styled("Bus budget:  ", Style::default().fg(theme.muted)),
+            Span::styled(
+                format!(
+                    "{} / {} ({:.1}% used)",
+                    pool.format_used(units),
+                    pool.format_max(units),
+                    pool.periodic_usage_percent()
+                ),
+                Style::default().fg(theme.usage(pool.periodic_usage_percent(), &app.config)),
             ),
         ]));
+    }
+    let device_bw = device.periodic_bandwidth_bps();
+    lines.push(Line::from(vec![
+        Span::styled("This device: ", Style::default().fg(theme.muted)),
+        Span::styled(
+            format!(
+                "{} ({:.1}% of bus budget)",
+                units.format(device_bw, bus_speed),
+                shares.percent(device_bw)
+            ),
+            Style::default().fg(theme.text),
+        ),
+    ]));
+
+    // Group endpoints by interface (older snapshots have no interface data)
+    let groups: Vec<(Option<&UsbInterface>, Vec<&Endpoint>)> = if device.interfaces.is_empty() {
+        vec![(None, device.endpoints.iter().collect())]
+    } else {
+        device
+            .interfaces
+            .iter()
+            .map(|iface| {
+                let eps = device
+                    .endpoints
+                    .iter()
+                    .filter(|ep| ep.interface == iface.number)
+                    .collect();
+                (Some(iface), eps)
+            })
+            .collect()
+    };
 
+    for (iface, endpoints) in groups {
         lines.push(Line::from(""));
+        match iface {
+            Some(iface) => {
+                lines.push(Line::from(vec![
+                    Span::styled(
+                        format!("Interface {} alt {}", iface.number, iface.alt_setting),
+                        Style::default()
+                            .fg(theme.accent)
+                            .add_modifier(Modifier::BOLD),
+                    ),
+                    Span::styled(
+                        format!(
+                            "  {} ({:02x}/{:02x}/{:02x})",
+                            iface.class_name(),
+                            iface.class,
+                            iface.subclass,
+                            iface.protocol
+                        ),
+                        Style::default().fg(theme.text),
+                    ),
+                    Span::styled(
+                        format!("  driver: {}", iface.driver.as_deref().unwrap_or("none")),
+                        Style::default().fg(theme.muted),
+                    ),
+                ]));
+                if let Some(name) = &iface.name {
+                    lines.push(Line::from(Span::styled(
+                        format!("  {}", name),
+                        Style::default().fg(theme.muted),
+                    )));
+                }
+            }
+            None => lines.push(Line::from(Span::styled(
+                "Endpoints",
+                Style::default()
+                    .fg(theme.accent)
+                    .add_modifier(Modifier::BOLD),
+            ))),
+        }
+
+        if endpoints.is_empty() {
+            lines.push(Line::from(Span::styled(
+                "  (no endpoints besides control)",
+                Style::default().fg(theme.muted),
+            )));
+        }
+
+        for ep in endpoints {
+            lines.push(Line::from(vec![
+                Span::raw("  "),
+                Span::styled(
+                    format!("EP{:02X}", ep.address),
+                    Style::default().fg(theme.text),
+                ),
+                Span::raw(format!(
+                    " {:<3} {:<11} {}B ×{} @ {} (bInterval {})",
+                    ep.direction.to_string(),
+                    ep.transfer_type.to_string(),
+                    ep.base_packet_size(),
+                    ep.multiplier(),
+                    ep.interval_str,
+                    ep.b_interval
+                )),
+            ]));
+
+            if !ep.transfer_type.reserves_bandwidth() {
+                lines.push(Line::from(Span::styled(
+                    "       no periodic reservation",
+                    Style::default().fg(theme.muted),
+                )));
+                continue;
+            }
+
+            let bw = ep.bandwidth_bps(device.speed);
+            let percent = shares.percent(bw);
+            let color = theme.usage(percent, &app.config);
+            let mut spans = vec![
+                Span::raw("       "),
+                Span::styled(bandwidth_bar(percent, 20), Style::default().fg(color)),
+                Span::styled(format!(" {:>5.1}% ", percent), Style::default().fg(color)),
+                Span::raw(units.format(bw, bus_speed)),
+            ];
+            // Largest periodic endpoint, marked as the likely culprit
+            if shares.is_largest(ep) {
+                spans.push(Span::styled(
+                    "  ◀ largest",
+                    Style::default().fg(theme.warn).add_modifier(Modifier::BOLD),
+                ));
+            }
+            lines.push(Line::from(spans));
+        }
     }
 
+    let title = format!(
+        " Endpoints: {} ({}) - Esc to return ",
+        app.device_label(device),
+        device.path.0
+    );
     let paragraph = Paragraph::new(lines)
         .block(
             Block::default()
-                .title(" Bus Summary (press 't' for tree) ")
-                .borders(Borders::ALL),
+                .title(title)
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(theme.text)),
         )
-        .wrap(Wrap { trim: true });
+        .scroll((app.endpoints_scroll, 0));
 
     frame.render_widget(paragraph, area);
 }
 
 /// Render details panel.
 fn render_details(frame: &mut Frame, app: &App, area: Rect) {
+    let theme = &app.theme;
     let mut lines = Vec::new();
 
     // Show device details if selected
@@ -367,44 +859,44 @@ fn render_details(frame: &mut Frame, app: &App, area: Rect) {
         lines.push(Line::from(Span::styled(
             "Device Details",
             Style::default()
-                .fg(Color::Cyan)
+                .fg(theme.accent)
                 .add_modifier(Modifier::BOLD),
         )));
         lines.push(Line::from(""));
 
         // Name
         lines.push(Line::from(vec![
-            Span::styled("Name: ", Style::default().fg(Color::DarkGray)),
-            Span::styled(device.display_name(), Style::default().fg(Color::White)),
+            Span::styled("Name: ", Style::default().fg(theme.muted)),
+            Span::styled(device.display_name(), Style::default().fg(theme.text)),
         ]));
 
         // Config Key (VID:PID:iSerial) - prominent for easy copying
         lines.push(Line::from(vec![
-            Span::styled("Key:  ", Style::default().fg(Color::DarkGray)),
+            Span::styled("Key:  ", Style::default().fg(theme.muted)),
             Span::styled(
                 device.config_key(),
                 Style::default()
-                    .fg(Color::Yellow)
+                    .fg(theme.highlight)
                     .add_modifier(Modifier::BOLD),
             ),
         ]));
 
         // Path
         lines.push(Line::from(vec![
-            Span::styled("Path: ", Style::default().fg(Color::DarkGray)),
+            Span::styled("Path: ", Style::default().fg(theme.muted)),
             Span::raw(&device.path.0),
         ]));
 
         // Speed
         lines.push(Line::from(vec![
-            Span::styled("Speed: ", Style::default().fg(Color::DarkGray)),
+            Span::styled("Speed: ", Style::default().fg(theme.muted)),
             Span::raw(device.speed.to_string()),
         ]));
 
         // Manufacturer
         if let Some(mfr) = &device.manufacturer {
             lines.push(Line::from(vec![
-                Span::styled("Manufacturer: ", Style::default().fg(Color::DarkGray)),
+                Span::styled("Manufacturer: ", Style::default().fg(theme.muted)),
                 Span::raw(mfr),
             ]));
         }
@@ -412,7 +904,7 @@ fn render_details(frame: &mut Frame, app: &App, area: Rect) {
         // Product
         if let Some(prod) = &device.product {
             lines.push(Line::from(vec![
-                Span::styled("Product: ", Style::default().fg(Color::DarkGray)),
+                Span::styled("Product: ", Style::default().fg(theme.muted)),
                 Span::raw(prod),
             ]));
         }
@@ -420,14 +912,14 @@ fn render_details(frame: &mut Frame, app: &App, area: Rect) {
         // Serial (only if not already shown in config key)
         if let Some(serial) = &device.serial {
             lines.push(Line::from(vec![
-                Span::styled("Serial: ", Style::default().fg(Color::DarkGray)),
+                Span::styled("Serial: ", Style::default().fg(theme.muted)),
                 Span::raw(serial),
             ]));
         }
 
         // USB Version
         lines.push(Line::from(vec![
-            Span::styled("USB Version: ", Style::default().fg(Color::DarkGray)),
+            Span::styled("USB Version: ", Style::default().fg(theme.muted)),
             Span::raw(&device.usb_version),
         ]));
 
@@ -435,13 +927,13 @@ fn render_details(frame: &mut Frame, app: &App, area: Rect) {
         if let Some(duration_ms) = device.connected_duration_ms {
             let duration_str = format_duration_ms(duration_ms);
             lines.push(Line::from(vec![
-                Span::styled("Connected: ", Style::default().fg(Color::DarkGray)),
+                Span::styled("Connected: ", Style::default().fg(theme.muted)),
                 Span::raw(duration_str),
             ]));
         }
         if let Some(lanes) = device.rx_lanes {
             lines.push(Line::from(vec![
-                Span::styled("Link: ", Style::default().fg(Color::DarkGray)),
+                Span::styled("Link: ", Style::default().fg(theme.muted)),
                 Span::raw(format!("{} rx lane(s)", lanes)),
             ]));
         }
@@ -451,17 +943,17 @@ fn render_details(frame: &mut Frame, app: &App, area: Rect) {
             let loc_str = loc.display();
             if !loc_str.is_empty() {
                 lines.push(Line::from(vec![
-                    Span::styled("Location: ", Style::default().fg(Color::DarkGray)),
-                    Span::styled(loc_str, Style::default().fg(Color::Magenta)),
+                    Span::styled("Location: ", Style::default().fg(theme.muted)),
+                    Span::styled(loc_str, Style::default().fg(theme.secondary)),
                 ]));
                 // Show raw ACPI values for debugging port identification
                 lines.push(Line::from(vec![
-                    Span::styled("  (ACPI: ", Style::default().fg(Color::DarkGray)),
+                    Span::styled("  (ACPI: ", Style::default().fg(theme.muted)),
                     Span::raw(format!(
                         "panel={} vert={} horiz={}",
                         loc.panel, loc.vertical_position, loc.horizontal_position
                     )),
-                    Span::styled(")", Style::default().fg(Color::DarkGray)),
+                    Span::styled(")", Style::default().fg(theme.muted)),
                 ]));
             }
         }
@@ -471,7 +963,7 @@ fn render_details(frame: &mut Frame, app: &App, area: Rect) {
         lines.push(Line::from(Span::styled(
             format!("Endpoints ({})", device.endpoints.len()),
             Style::default()
-                .fg(Color::Cyan)
+                .fg(theme.accent)
                 .add_modifier(Modifier::BOLD),
         )));
 
@@ -479,7 +971,7 @@ fn render_details(frame: &mut Frame, app: &App, area: Rect) {
         if !periodic.is_empty() {
             lines.push(Line::from(Span::styled(
                 "Periodic (bandwidth-reserving):",
-                Style::default().fg(Color::Yellow),
+                Style::default().fg(theme.highlight),
             )));
 
             for ep in &periodic {
@@ -488,7 +980,7 @@ fn render_details(frame: &mut Frame, app: &App, area: Rect) {
                     Span::raw("  "),
                     Span::styled(
                         format!("EP{:02X}", ep.address),
-                        Style::default().fg(Color::White),
+                        Style::default().fg(theme.text),
                     ),
                     Span::raw(format!(
                         " {} {} {}B @ {}",
@@ -498,8 +990,8 @@ fn render_details(frame: &mut Frame, app: &App, area: Rect) {
                 lines.push(Line::from(vec![
                     Span::raw("       "),
                     Span::styled(
-                        format!("→ {}", format_bandwidth(bw)),
-                        Style::default().fg(Color::Green),
+                        format!("→ {}", app.config.units().format(bw, app.bus_speed(device))),
+                        Style::default().fg(theme.ok),
                     ),
                 ]));
             }
@@ -507,18 +999,16 @@ fn render_details(frame: &mut Frame, app: &App, area: Rect) {
             let total_bw = device.periodic_bandwidth_bps();
             lines.push(Line::from(""));
             lines.push(Line::from(vec![
-                Span::styled("Total: ", Style::default().fg(Color::DarkGray)),
+                Span::styled("Total: ", Style::default().fg(theme.muted)),
                 Span::styled(
-                    format_bandwidth(total_bw),
-                    Style::default()
-                        .fg(Color::Green)
-                        .add_modifier(Modifier::BOLD),
+                    app.config.units().format(total_bw, app.bus_speed(device)),
+                    Style::default().fg(theme.ok).add_modifier(Modifier::BOLD),
                 ),
             ]));
         } else {
             lines.push(Line::from(Span::styled(
                 "No periodic endpoints",
-                Style::default().fg(Color::DarkGray),
+                Style::default().fg(theme.muted),
             )));
         }
     } else if let Some(bus) = app.get_selected_bus() {
@@ -526,28 +1016,28 @@ fn render_details(frame: &mut Frame, app: &App, area: Rect) {
         lines.push(Line::from(Span::styled(
             "Bus Details",
             Style::default()
-                .fg(Color::Cyan)
+                .fg(theme.accent)
                 .add_modifier(Modifier::BOLD),
         )));
         lines.push(Line::from(""));
 
         lines.push(Line::from(vec![
-            Span::styled("Bus Number: ", Style::default().fg(Color::DarkGray)),
+            Span::styled("Bus Number: ", Style::default().fg(theme.muted)),
             Span::raw(format!("{}", bus.bus_num)),
         ]));
 
         lines.push(Line::from(vec![
-            Span::styled("Speed: ", Style::default().fg(Color::DarkGray)),
+            Span::styled("Speed: ", Style::default().fg(theme.muted)),
             Span::raw(bus.speed.to_string()),
         ]));
 
         lines.push(Line::from(vec![
-            Span::styled("USB Version: ", Style::default().fg(Color::DarkGray)),
+            Span::styled("USB Version: ", Style::default().fg(theme.muted)),
             Span::raw(&bus.version),
         ]));
 
         lines.push(Line::from(vec![
-            Span::styled("Root Ports: ", Style::default().fg(Color::DarkGray)),
+            Span::styled("Root Ports: ", Style::default().fg(theme.muted)),
             Span::raw(format!("{}", bus.num_ports)),
         ]));
 
@@ -556,15 +1046,14 @@ fn render_details(frame: &mut Frame, app: &App, area: Rect) {
             && controller.controller_type == ControllerType::Usb4
         {
             lines.push(Line::from(vec![
-                Span::styled("Controller: ", Style::default().fg(Color::DarkGray)),
-                Span::styled("USB4/Thunderbolt", Style::default().fg(Color::Magenta)),
+                Span::styled("Controller: ", Style::default().fg(theme.muted)),
+                Span::styled("USB4/Thunderbolt", Style::default().fg(theme.secondary)),
             ]));
         }
 
         // Show paired bus (USB 2.0 <-> USB 3.x share same physical ports)
         if let Some(paired_num) = app.topology.get_paired_bus(bus.bus_num) {
             let paired_label = app
-                .config
                 .bus_label(paired_num)
                 .unwrap_or_else(|| format!("Bus {}", paired_num));
             let paired_speed = app
@@ -574,16 +1063,16 @@ fn render_details(frame: &mut Frame, app: &App, area: Rect) {
                 .map(|b| b.speed.short_name())
                 .unwrap_or("?");
             lines.push(Line::from(vec![
-                Span::styled("Paired with: ", Style::default().fg(Color::DarkGray)),
+                Span::styled("Paired with: ", Style::default().fg(theme.muted)),
                 Span::styled(
                     format!("{} ({})", paired_label, paired_speed),
-                    Style::default().fg(Color::Magenta),
+                    Style::default().fg(theme.secondary),
                 ),
             ]));
         }
 
         lines.push(Line::from(vec![
-            Span::styled("Devices: ", Style::default().fg(Color::DarkGray)),
+            Span::styled("Devices: ", Style::default().fg(theme.muted)),
             Span::raw(format!("{}", bus.device_count())),
         ]));
 
@@ -591,32 +1080,32 @@ fn render_details(frame: &mut Frame, app: &App, area: Rect) {
         lines.push(Line::from(Span::styled(
             "Bandwidth",
             Style::default()
-                .fg(Color::Cyan)
+                .fg(theme.accent)
                 .add_modifier(Modifier::BOLD),
         )));
 
         let pool = BandwidthPool::with_usage(bus.speed, bus.periodic_bandwidth_used_bps());
-        let usage_color = if pool.is_critical() {
-            Color::Red
-        } else if pool.is_high_usage() {
-            Color::Yellow
-        } else {
-            Color::Green
-        };
+        let usage_color = theme.usage(pool.periodic_usage_percent(), &app.config);
 
         lines.push(Line::from(vec![
-            Span::styled("Used: ", Style::default().fg(Color::DarkGray)),
-            Span::styled(pool.format_used(), Style::default().fg(usage_color)),
+            Span::styled("Used: ", Style::default().fg(theme.muted)),
+            Span::styled(
+                pool.format_used(app.config.units()),
+                Style::default().fg(usage_color),
+            ),
         ]));
 
         lines.push(Line::from(vec![
-            Span::styled("Max:  ", Style::default().fg(Color::DarkGray)),
-            Span::raw(pool.format_max()),
+            Span::styled("Max:  ", Style::default().fg(theme.muted)),
+            Span::raw(pool.format_max(app.config.units())),
         ]));
 
         lines.push(Line::from(vec![
-            Span::styled("Avail: ", Style::default().fg(Color::DarkGray)),
-            Span::styled(pool.format_available(), Style::default().fg(Color::Green)),
+            Span::styled("Avail: ", Style::default().fg(theme.muted)),
+            Span::styled(
+                pool.format_available(app.config.units()),
+                Style::default().fg(theme.ok),
+            ),
         ]));
 
         lines.push(Line::from(""));
@@ -631,21 +1120,57 @@ fn render_details(frame: &mut Frame, app: &App, area: Rect) {
             ),
         ]));
 
+        // History since startup (last samples)
+        let samples = app.usage_history.samples(bus.bus_num);
+        if let Some(peak) = app.usage_history.peak_usage(bus.bus_num) {
+            let usage = app.usage_history.usage(bus.bus_num);
+            let counts = app.usage_history.device_counts(bus.bus_num);
+            let max_count = counts.iter().copied().fold(0.0, f64::max);
+            let (min_devices, max_devices) = app
+                .usage_history
+                .device_range(bus.bus_num)
+                .unwrap_or((0, 0));
+
+            lines.push(Line::from(""));
+            lines.push(Line::from(Span::styled(
+                format!("History ({} samples)", samples.len()),
+                Style::default()
+                    .fg(theme.accent)
+                    .add_modifier(Modifier::BOLD),
+            )));
+            lines.push(Line::from(vec![
+                Span::styled("Usage:   ", Style::default().fg(theme.muted)),
+                Span::styled(
+                    sparkline(&usage, peak, 25),
+                    Style::default().fg(theme.usage(peak, &app.config)),
+                ),
+                Span::raw(format!(" peak {:.1}%", peak)),
+            ]));
+            lines.push(Line::from(vec![
+                Span::styled("Devices: ", Style::default().fg(theme.muted)),
+                Span::styled(
+                    sparkline(&counts, max_count, 25),
+                    Style::default().fg(theme.text),
+                ),
+                Span::raw(format!(" {}-{}", min_devices, max_devices)),
+            ]));
+        }
+
         // Port health section
         if !bus.ports.is_empty() {
             lines.push(Line::from(""));
             lines.push(Line::from(Span::styled(
                 "Port Status",
                 Style::default()
-                    .fg(Color::Cyan)
+                    .fg(theme.accent)
                     .add_modifier(Modifier::BOLD),
             )));
 
             let total_oc = bus.total_over_current_count();
             if total_oc > 0 {
                 lines.push(Line::from(vec![
-                    Span::styled("⚠ Over-current events: ", Style::default().fg(Color::Red)),
-                    Span::styled(format!("{}", total_oc), Style::default().fg(Color::Red)),
+                    Span::styled("⚠ Over-current events: ", Style::default().fg(theme.error)),
+                    Span::styled(format!("{}", total_oc), Style::default().fg(theme.error)),
                 ]));
             }
 
@@ -661,19 +1186,19 @@ fn render_details(frame: &mut Frame, app: &App, area: Rect) {
                 };
 
                 let (state_color, state_icon) = if port.state.is_problematic() {
-                    (Color::Red, "⚠")
+                    (theme.error, "⚠")
                 } else if port.state == crate::model::PortState::Configured {
-                    (Color::Green, "●")
+                    (theme.ok, "●")
                 } else if port.state == crate::model::PortState::Suspended {
-                    (Color::Yellow, "○")
+                    (theme.warn, "○")
                 } else {
-                    (Color::DarkGray, "○")
+                    (theme.muted, "○")
                 };
 
                 lines.push(Line::from(vec![
                     Span::styled(
                         format!("  Port {}: ", port.port_num),
-                        Style::default().fg(Color::DarkGray),
+                        Style::default().fg(theme.muted),
                     ),
                     Span::styled(
                         format!("{} {}", state_icon, state_str),
@@ -685,7 +1210,7 @@ fn render_details(frame: &mut Frame, app: &App, area: Rect) {
     } else {
         lines.push(Line::from(Span::styled(
             "Select a device or bus",
-            Style::default().fg(Color::DarkGray),
+            Style::default().fg(theme.muted),
         )));
     }
 
@@ -697,8 +1222,8 @@ fn render_details(frame: &mut Frame, app: &App, area: Rect) {
 }
 
 /// Render help overlay.
-fn render_help(frame: &mut Frame) {
-    let area = centered_rect(50, 70, frame.area());
+fn render_help(frame: &mut Frame, theme: &Theme) {
+    let area = centered_rect(50, 80, frame.area());
 
     frame.render_widget(Clear, area);
 
@@ -706,7 +1231,7 @@ fn render_help(frame: &mut Frame) {
         Line::from(Span::styled(
             "usbbw Help",
             Style::default()
-                .fg(Color::Cyan)
+                .fg(theme.accent)
                 .add_modifier(Modifier::BOLD),
         )),
         Line::from(""),
@@ -718,7 +1243,7 @@ fn render_help(frame: &mut Frame) {
         Line::from("  k/↑     Move up"),
         Line::from("  J/PgDn  Scroll details down"),
         Line::from("  K/PgUp  Scroll details up"),
-        Line::from("  Enter   Expand/collapse"),
+        Line::from("  Enter   Expand/collapse, endpoints of a device"),
         Line::from("  g       Go to top"),
         Line::from("  G       Go to bottom"),
         Line::from("  x       Expand/collapse all"),
@@ -729,13 +1254,26 @@ fn render_help(frame: &mut Frame) {
         )),
         Line::from("  t       Tree view"),
         Line::from("  s       Summary view"),
+        Line::from("  p       Physical port map (Esc to return)"),
         Line::from("  b       Toggle bandwidth bars"),
+        Line::from("  l       Toggle event log"),
+        Line::from(""),
+        Line::from(Span::styled(
+            "Search",
+            Style::default().add_modifier(Modifier::BOLD),
+        )),
+        Line::from("  /       Search label, product, VID:PID, serial, path"),
+        Line::from("  n/N     Next/previous match"),
+        Line::from("  f       Show only search matches"),
+        Line::from("  F       Quick filter: periodic/unconfigured/new"),
+        Line::from("  Esc     Clear filters"),
         Line::from(""),
         Line::from(Span::styled(
             "Device Labels",
             Style::default().add_modifier(Modifier::BOLD),
         )),
-        Line::from("  e       Edit label for selected device"),
+        Line::from("  e       Edit label (Tab switches target)"),
+        Line::from("          ↑/↓ suggestions, Ctrl+Z undo, Ctrl+W word"),
         Line::from("  m       Mark device as seen (clear NEW)"),
         Line::from("  w       Write pending labels to config"),
         Line::from(""),
@@ -765,9 +1303,9 @@ fn render_help(frame: &mut Frame) {
             Block::default()
                 .title(" Help ")
                 .borders(Borders::ALL)
-                .border_style(Style::default().fg(Color::Cyan)),
+                .border_style(Style::default().fg(theme.accent)),
         )
-        .style(Style::default().bg(Color::Black));
+        .style(Style::default().bg(theme.popup_bg));
 
     frame.render_widget(paragraph, area);
 }
@@ -795,25 +1333,29 @@ fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
 
 /// Render device status line showing path and config key for easy copying.
 fn render_device_status(frame: &mut Frame, app: &App, area: Rect) {
+    let theme = &app.theme;
     let spans = if let Some(device) = app.get_selected_device() {
         vec![
-            Span::styled(" Device: ", Style::default().fg(Color::DarkGray)),
-            Span::styled(&device.path.0, Style::default().fg(Color::Cyan)),
+            Span::styled(" Device: ", Style::default().fg(theme.muted)),
+            Span::styled(&device.path.0, Style::default().fg(theme.accent)),
             Span::styled("  ", Style::default()),
             Span::styled(
                 device.config_key(),
                 Style::default()
-                    .fg(Color::Yellow)
+                    .fg(theme.highlight)
                     .add_modifier(Modifier::BOLD),
             ),
         ]
     } else if let Some(bus) = app.get_selected_bus() {
         vec![
-            Span::styled(" Bus: ", Style::default().fg(Color::DarkGray)),
-            Span::styled(format!("{}", bus.bus_num), Style::default().fg(Color::Cyan)),
+            Span::styled(" Bus: ", Style::default().fg(theme.muted)),
+            Span::styled(
+                format!("{}", bus.bus_num),
+                Style::default().fg(theme.accent),
+            ),
             Span::styled(
                 format!("  {}", bus.speed.short_name()),
-                Style::default().fg(Color::DarkGray),
+                Style::default().fg(theme.muted),
             ),
         ]
     } else {
@@ -826,143 +1368,165 @@ fn render_device_status(frame: &mut Frame, app: &App, area: Rect) {
 
 /// Render contextual footer with keybindings.
 fn render_footer(frame: &mut Frame, app: &App, area: Rect) {
+    let theme = &app.theme;
     let mut spans = Vec::new();
 
-    // Check for status message first
-    if let Some(status) = app.status() {
+    // Search prompt takes over the footer while typing
+    if let Some(input) = &app.search_input {
+        spans.push(Span::styled("/", Style::default().fg(theme.highlight)));
+        spans.push(Span::styled(
+            format!("{}█", input),
+            Style::default().fg(theme.text),
+        ));
+        spans.push(Span::styled(
+            "  Enter done  Esc clear",
+            Style::default().fg(theme.muted),
+        ));
+    } else if let Some(status) = app.status() {
         spans.push(Span::styled(
             status,
-            Style::default()
-                .fg(Color::Green)
-                .add_modifier(Modifier::BOLD),
+            Style::default().fg(theme.ok).add_modifier(Modifier::BOLD),
         ));
     } else {
-        // Navigation keys
-        spans.push(Span::styled("j/k", Style::default().fg(Color::Yellow)));
-        spans.push(Span::raw(" Nav  "));
-
-        spans.push(Span::styled("Enter", Style::default().fg(Color::Yellow)));
-        spans.push(Span::raw(" Expand  "));
-
-        spans.push(Span::styled("x", Style::default().fg(Color::Yellow)));
-        spans.push(Span::raw(" All  "));
-
-        // View toggles
-        spans.push(Span::styled("t/s", Style::default().fg(Color::Yellow)));
-        spans.push(Span::raw(" View  "));
-
-        spans.push(Span::styled("b", Style::default().fg(Color::Yellow)));
-        spans.push(Span::raw(" Bars  "));
-
-        // Context-specific: show edit/mark if device selected
-        if app.selected_device.is_some() {
-            spans.push(Span::styled("e", Style::default().fg(Color::Yellow)));
-            spans.push(Span::raw(" Edit  "));
-
-            // Show mark-seen only for new devices
-            let items = app.visible_items();
-            if let Some(TreeItem::Device { is_new: true, .. }) = items.get(app.selected) {
-                spans.push(Span::styled("m", Style::default().fg(Color::Yellow)));
-                spans.push(Span::raw(" Mark seen  "));
-            }
+        for hint in footer_hints(app) {
+            let (key_style, action_style) = if hint.emphasis {
+                (
+                    Style::default().fg(theme.new),
+                    Style::default().fg(theme.new),
+                )
+            } else {
+                (Style::default().fg(theme.highlight), Style::default())
+            };
+            spans.push(Span::styled(hint.keys, key_style));
+            spans.push(Span::styled(hint.action, action_style));
         }
-
-        // Show write if there are pending labels
-        if app.pending_label_count() > 0 {
-            spans.push(Span::styled("w", Style::default().fg(Color::LightGreen)));
-            spans.push(Span::styled(
-                format!(" Write ({})  ", app.pending_label_count()),
-                Style::default().fg(Color::LightGreen),
-            ));
-        }
-
-        spans.push(Span::styled("?", Style::default().fg(Color::Yellow)));
-        spans.push(Span::raw(" Help  "));
-
-        spans.push(Span::styled("q", Style::default().fg(Color::Yellow)));
-        spans.push(Span::raw(" Quit"));
     }
 
-    let paragraph = Paragraph::new(Line::from(spans)).style(Style::default().bg(Color::DarkGray));
+    let paragraph = Paragraph::new(Line::from(spans)).style(theme.bar());
 
     frame.render_widget(paragraph, area);
 }
 
 /// Render with edit overlay.
 fn render_with_edit_overlay(frame: &mut Frame, app: &App) {
+    let theme = &app.theme;
     // Render the main content (dimmed)
-    let outer_chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([
-            Constraint::Min(0),
-            Constraint::Length(1),
-            Constraint::Length(1),
-        ])
-        .split(frame.area());
-
-    let main_chunks = Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
-        .split(outer_chunks[0]);
-
-    match app.view_mode {
-        ViewMode::Tree => render_tree(frame, app, main_chunks[0]),
-        ViewMode::Summary => render_summary(frame, app, main_chunks[0]),
-    }
-    render_details(frame, app, main_chunks[1]);
+    let layout = ScreenLayout::new(frame.area(), app.show_event_log);
+    render_content(frame, app, &layout);
 
     // Device status line
-    render_device_status(frame, app, outer_chunks[1]);
+    render_device_status(frame, app, layout.status);
 
     // Edit footer
     let footer = Paragraph::new(Line::from(vec![
-        Span::styled("Editing label...  ", Style::default().fg(Color::Yellow)),
-        Span::styled("Enter", Style::default().fg(Color::Cyan)),
+        Span::styled("Editing label...  ", Style::default().fg(theme.highlight)),
+        Span::styled("Enter", Style::default().fg(theme.accent)),
         Span::raw(" Save  "),
-        Span::styled("Esc", Style::default().fg(Color::Cyan)),
+        Span::styled("Tab", Style::default().fg(theme.accent)),
+        Span::raw(" Target  "),
+        Span::styled("Esc", Style::default().fg(theme.accent)),
         Span::raw(" Cancel"),
     ]))
-    .style(Style::default().bg(Color::DarkGray));
-    frame.render_widget(footer, outer_chunks[2]);
+    .style(theme.bar());
+    frame.render_widget(footer, layout.footer);
 
     // Edit popup overlay
     if let Some(edit) = &app.edit_mode {
-        let popup_area = centered_rect(50, 20, frame.area());
+        let popup_area = centered_rect(60, 30, frame.area());
         frame.render_widget(Clear, popup_area);
 
         let inner = Layout::default()
             .direction(Direction::Vertical)
-            .constraints([Constraint::Length(3), Constraint::Length(3)])
+            .constraints([
+                Constraint::Length(1),
+                Constraint::Length(1),
+                Constraint::Length(1),
+                Constraint::Length(3),
+            ])
             .margin(1)
             .split(popup_area);
 
         // Title
-        let title = Paragraph::new(format!("Edit label for {}", edit.display_name))
-            .style(Style::default().fg(Color::Cyan));
+        let target = edit.target();
+        let scope = match target {
+            LabelTarget::Product(key) if key.matches(':').count() == 1 => {
+                " (all devices of this type, no serial)"
+            }
+            _ => "",
+        };
+        let title = Paragraph::new(format!("Label for {}{}", target, scope))
+            .style(Style::default().fg(theme.accent));
         frame.render_widget(title, inner[0]);
 
-        // Input field
-        let input_text = format!("{}█", edit.input);
-        let input = Paragraph::new(input_text)
+        // Target choices
+        let mut spans = vec![Span::styled("Tab ", Style::default().fg(theme.muted))];
+        for (i, choice) in edit.targets.iter().enumerate() {
+            let style = if i == edit.target_index {
+                theme.selected(Style::default().fg(theme.highlight))
+            } else {
+                Style::default().fg(theme.muted)
+            };
+            spans.push(Span::styled(format!(" {} ", choice.kind()), style));
+        }
+        frame.render_widget(Paragraph::new(Line::from(spans)), inner[1]);
+        frame.render_widget(
+            Paragraph::new(Span::styled(
+                match suggestion_hint(&edit.input) {
+                    Some(hint) => format!("Saved to [{}] · {}", target.table(), hint),
+                    None => format!("Saved to [{}]", target.table()),
+                },
+                Style::default().fg(theme.muted),
+            )),
+            inner[2],
+        );
+
+        // Input field, scrolled so the cursor stays visible
+        let field_width = inner[3].width.saturating_sub(4) as usize;
+        let input = Paragraph::new(input_line(&edit.input, field_width, theme))
             .block(
                 Block::default()
                     .borders(Borders::ALL)
-                    .border_style(Style::default().fg(Color::Yellow))
+                    .border_style(Style::default().fg(theme.highlight))
                     .padding(Padding::horizontal(1)),
             )
-            .style(Style::default().fg(Color::White));
-        frame.render_widget(input, inner[1]);
+            .style(Style::default().fg(theme.text));
+        frame.render_widget(input, inner[3]);
 
         // Outer block
         let block = Block::default()
             .title(" Enter Label ")
             .borders(Borders::ALL)
-            .border_style(Style::default().fg(Color::Cyan))
-            .style(Style::default().bg(Color::Black));
+            .border_style(Style::default().fg(theme.accent))
+            .style(Style::default().bg(theme.popup_bg));
         frame.render_widget(block, popup_area);
     }
 }
 
+/// Input text with a block cursor, trimmed to `width` columns around it.
+fn input_line(input: &LineEditor, width: usize, theme: &Theme) -> Line<'static> {
+    let (before, under, after) = input.visible(width);
+    let cursor_style = Style::default()
+        .fg(theme.text)
+        .add_modifier(Modifier::REVERSED);
+    Line::from(vec![
+        Span::raw(before.to_string()),
+        Span::styled(under.map_or(" ".to_string(), String::from), cursor_style),
+        Span::raw(after.to_string()),
+    ])
+}
+
+/// "↑/↓ suggestions" hint with the position of the current one.
+fn suggestion_hint(input: &LineEditor) -> Option<String> {
+    let count = input.suggestions().len();
+    if count == 0 {
+        return None;
+    }
+    Some(match input.suggestion_index() {
+        Some(i) => format!("↑/↓ suggestion {}/{}", i + 1, count),
+        None => format!("↑/↓ {} suggestion(s): {}", count, input.suggestions()[0]),
+    })
+}
+
 /// Format duration in milliseconds as human-readable string.
 fn format_duration_ms(ms: u64) -> String {
     let secs = ms / 1000;
@@ -0,0 +1,109 @@
+//! Rolling per-bus usage samples for the TUI sparklines.
+
+use crate::model::UsbTopology;
+use std::collections::{HashMap, VecDeque};
+
+/// Number of samples kept per bus (two minutes at the default refresh rate).
+pub const DEFAULT_SAMPLE_CAPACITY: usize = 120;
+
+/// Bus state captured at one refresh.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BusSample {
+    /// Periodic bandwidth usage (0.0 - 100.0).
+    pub usage_percent: f64,
+    /// Devices on the bus.
+    pub device_count: usize,
+}
+
+/// Ring buffer of samples for each bus.
+#[derive(Debug, Clone)]
+pub struct UsageHistory {
+    capacity: usize,
+    buses: HashMap<u8, VecDeque<BusSample>>,
+}
+
+impl Default for UsageHistory {
+    fn default() -> Self {
+        Self::new(DEFAULT_SAMPLE_CAPACITY)
+    }
+}
+
+impl UsageHistory {
+    /// Create an empty history keeping `capacity` samples per bus.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            buses: HashMap::new(),
+        }
+    }
+
+    /// Record one sample for every bus in the topology.
+    pub fn record(&mut self, topology: &UsbTopology) {
+        for bus in topology.buses.values() {
+            let samples = self.buses.entry(bus.bus_num).or_default();
+            if samples.len() == self.capacity {
+                samples.pop_front();
+            }
+            samples.push_back(BusSample {
+                usage_percent: bus.periodic_usage_percent(),
+                device_count: bus.device_count(),
+            });
+        }
+    }
+
+    /// Samples for a bus, oldest first.
+    pub fn samples(&self, bus_num: u8) -> Vec<BusSample> {
+        self.buses
+            .get(&bus_num)
+            .map(|s| s.iter().copied().collect())
+            .unwrap_or_default()
+    }
+
+    /// Usage values for a bus, oldest first.
+    pub fn usage(&self, bus_num: u8) -> Vec<f64> {
+        self.samples(bus_num)
+            .iter()
+            .map(|s| s.usage_percent)
+            .collect()
+    }
+
+    /// Device counts for a bus, oldest first.
+    pub fn device_counts(&self, bus_num: u8) -> Vec<f64> {
+        self.samples(bus_num)
+            .iter()
+            .map(|s| s.device_count as f64)
+            .collect()
+    }
+
+    /// Highest usage seen on a bus within the window.
+    pub fn peak_usage(&self, bus_num: u8) -> Option<f64> {
+        self.usage(bus_num).into_iter().reduce(f64::max)
+    }
+
+    /// Lowest and highest device count seen on a bus within the window.
+    pub fn device_range(&self, bus_num: u8) -> Option<(usize, usize)> {
+        let samples = self.samples(bus_num);
+        let min = samples.iter().map(|s| s.device_count).min()?;
+        let max = samples.iter().map(|s| s.device_count).max()?;
+        Some((min, max))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ring_buffer_capacity() {
+        let mut history = UsageHistory::new(3);
+        let topology = UsbTopology::fixture(1, vec![]);
+        for _ in 0..5 {
+            history.record(&topology);
+        }
+        assert_eq!(history.samples(1).len(), 3);
+        assert_eq!(history.peak_usage(1), Some(0.0));
+        assert_eq!(history.device_range(1), Some((0, 0)));
+        assert!(history.samples(2).is_empty());
+        assert_eq!(history.peak_usage(2), None);
+    }
+}
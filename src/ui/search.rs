@@ -0,0 +1,98 @@
+//! TUI search and quick filters.
+
+use crate::model::UsbDevice;
+
+/// Quick filter limiting the tree to devices in a given state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuickFilter {
+    /// Devices with interrupt/isochronous endpoints.
+    Periodic,
+    /// Devices that failed bandwidth allocation.
+    Unconfigured,
+    /// Devices discovered this session and not yet seen.
+    New,
+}
+
+impl QuickFilter {
+    /// Short name for titles and status messages.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Periodic => "periodic",
+            Self::Unconfigured => "unconfigured",
+            Self::New => "new",
+        }
+    }
+
+    /// Next filter in the cycle (none → periodic → unconfigured → new → none).
+    pub fn cycle(current: Option<Self>) -> Option<Self> {
+        match current {
+            None => Some(Self::Periodic),
+            Some(Self::Periodic) => Some(Self::Unconfigured),
+            Some(Self::Unconfigured) => Some(Self::New),
+            Some(Self::New) => None,
+        }
+    }
+}
+
+/// Check whether a device matches a search query.
+///
+/// Case-insensitive substring match against the label, product, manufacturer,
+/// VID:PID, serial and port path.
+pub fn device_matches(device: &UsbDevice, label: &str, query: &str) -> bool {
+    let query = query.to_lowercase();
+    if query.is_empty() {
+        return true;
+    }
+    let vid_pid = device.vid_pid();
+    [
+        Some(label),
+        device.product.as_deref(),
+        device.manufacturer.as_deref(),
+        Some(vid_pid.as_str()),
+        device.serial.as_deref(),
+        Some(device.path.0.as_str()),
+    ]
+    .into_iter()
+    .flatten()
+    .any(|field| field.to_lowercase().contains(&query))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn device() -> UsbDevice {
+        UsbDevice {
+            manufacturer: Some("ARM".to_string()),
+            product: Some("DAPLink CMSIS-DAP".to_string()),
+            serial: Some("0240000034544e45".to_string()),
+            ..UsbDevice::fixture("3-1.2", 0x0d28, 0x0204)
+        }
+    }
+
+    #[test]
+    fn test_device_matches() {
+        let dev = device();
+        assert!(device_matches(&dev, "Sidecar RoT", "sidecar"));
+        assert!(device_matches(&dev, "", "cmsis"));
+        assert!(device_matches(&dev, "", "arm"));
+        assert!(device_matches(&dev, "", "0d28:0204"));
+        assert!(device_matches(&dev, "", "34544e"));
+        assert!(device_matches(&dev, "", "3-1.2"));
+        assert!(!device_matches(&dev, "", "webcam"));
+    }
+
+    #[test]
+    fn test_quick_filter_cycle() {
+        let mut filter = None;
+        let mut names = Vec::new();
+        for _ in 0..4 {
+            filter = QuickFilter::cycle(filter);
+            names.push(filter.map(|f| f.name()));
+        }
+        assert_eq!(
+            names,
+            [Some("periodic"), Some("unconfigured"), Some("new"), None]
+        );
+    }
+}
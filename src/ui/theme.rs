@@ -0,0 +1,256 @@
+//! TUI colour themes.
+
+use crate::config::{Config, ThemeColors};
+use ratatui::style::{Color, Modifier, Style};
+use std::str::FromStr;
+
+/// Colour palette consumed by all render functions.
+#[derive(Debug, Clone)]
+pub struct Theme {
+    /// Section headings, borders of popups, key hints in the edit footer.
+    pub accent: Color,
+    /// Primary text (values).
+    pub text: Color,
+    /// Secondary text (field names, paths, hints).
+    pub muted: Color,
+    /// Emphasized values (config keys, key hints).
+    pub highlight: Color,
+    /// USB controllers in the tree.
+    pub controller: Color,
+    /// USB4/Thunderbolt controllers.
+    pub usb4: Color,
+    /// Buses in the tree.
+    pub bus: Color,
+    /// USB 2.0 and lower speeds.
+    pub usb2: Color,
+    /// USB 3.x speeds.
+    pub usb3: Color,
+    /// Locations and paired buses.
+    pub secondary: Color,
+    /// Healthy state / low usage.
+    pub ok: Color,
+    /// High usage / attention.
+    pub warn: Color,
+    /// Critical usage / errors.
+    pub error: Color,
+    /// NEW device indicator.
+    pub new: Color,
+    /// Selected row background.
+    pub selection_bg: Color,
+    /// Footer bar background.
+    pub bar_bg: Color,
+    /// Popup background.
+    pub popup_bg: Color,
+    /// Use reverse video instead of background colours for selection and bars.
+    pub reverse: bool,
+}
+
+impl Theme {
+    /// Default palette for dark terminals.
+    pub fn dark() -> Self {
+        Self {
+            accent: Color::Cyan,
+            text: Color::White,
+            muted: Color::DarkGray,
+            highlight: Color::Yellow,
+            controller: Color::Cyan,
+            usb4: Color::Magenta,
+            bus: Color::Yellow,
+            usb2: Color::Green,
+            usb3: Color::Magenta,
+            secondary: Color::Magenta,
+            ok: Color::Green,
+            warn: Color::Yellow,
+            error: Color::Red,
+            new: Color::LightGreen,
+            selection_bg: Color::DarkGray,
+            bar_bg: Color::DarkGray,
+            popup_bg: Color::Black,
+            reverse: false,
+        }
+    }
+
+    /// Palette for light terminals (no yellow or white text).
+    pub fn light() -> Self {
+        Self {
+            accent: Color::Blue,
+            text: Color::Black,
+            muted: Color::DarkGray,
+            highlight: Color::Magenta,
+            controller: Color::Blue,
+            usb4: Color::Magenta,
+            bus: Color::Indexed(130),
+            usb2: Color::Green,
+            usb3: Color::Magenta,
+            secondary: Color::Magenta,
+            ok: Color::Green,
+            warn: Color::Indexed(130),
+            error: Color::Red,
+            new: Color::Indexed(28),
+            selection_bg: Color::Indexed(252),
+            bar_bg: Color::Indexed(252),
+            popup_bg: Color::White,
+            reverse: false,
+        }
+    }
+
+    /// Bright colours with reverse-video selection.
+    pub fn high_contrast() -> Self {
+        Self {
+            accent: Color::LightCyan,
+            text: Color::White,
+            muted: Color::Gray,
+            highlight: Color::LightYellow,
+            controller: Color::LightCyan,
+            usb4: Color::LightMagenta,
+            bus: Color::LightYellow,
+            usb2: Color::LightGreen,
+            usb3: Color::LightMagenta,
+            secondary: Color::LightMagenta,
+            ok: Color::LightGreen,
+            warn: Color::LightYellow,
+            error: Color::LightRed,
+            new: Color::LightGreen,
+            selection_bg: Color::Reset,
+            bar_bg: Color::Reset,
+            popup_bg: Color::Black,
+            reverse: true,
+        }
+    }
+
+    /// No colours at all; emphasis via bold and reverse video only.
+    pub fn monochrome() -> Self {
+        Self {
+            accent: Color::Reset,
+            text: Color::Reset,
+            muted: Color::Reset,
+            highlight: Color::Reset,
+            controller: Color::Reset,
+            usb4: Color::Reset,
+            bus: Color::Reset,
+            usb2: Color::Reset,
+            usb3: Color::Reset,
+            secondary: Color::Reset,
+            ok: Color::Reset,
+            warn: Color::Reset,
+            error: Color::Reset,
+            new: Color::Reset,
+            selection_bg: Color::Reset,
+            bar_bg: Color::Reset,
+            popup_bg: Color::Reset,
+            reverse: true,
+        }
+    }
+
+    /// Built-in theme by name ("dark", "light", "high-contrast", "monochrome").
+    pub fn by_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().replace('_', "-").as_str() {
+            "dark" => Some(Self::dark()),
+            "light" => Some(Self::light()),
+            "high-contrast" => Some(Self::high_contrast()),
+            "monochrome" | "mono" | "none" => Some(Self::monochrome()),
+            _ => None,
+        }
+    }
+
+    /// Build the theme from `settings.theme` and the `[theme]` colour
+    /// overrides. `NO_COLOR` forces the monochrome theme.
+    pub fn from_config(config: &Config) -> Self {
+        if std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty()) {
+            return Self::monochrome();
+        }
+
+        let mut theme = Self::by_name(&config.settings.theme).unwrap_or_else(Self::dark);
+        theme.apply(&config.theme);
+        theme
+    }
+
+    /// Apply user-defined colours. Unparseable colours are ignored.
+    pub fn apply(&mut self, colors: &ThemeColors) {
+        let overrides = [
+            (&mut self.accent, &colors.accent),
+            (&mut self.text, &colors.text),
+            (&mut self.muted, &colors.muted),
+            (&mut self.highlight, &colors.highlight),
+            (&mut self.controller, &colors.controller),
+            (&mut self.usb4, &colors.usb4),
+            (&mut self.bus, &colors.bus),
+            (&mut self.usb2, &colors.usb2),
+            (&mut self.usb3, &colors.usb3),
+            (&mut self.secondary, &colors.secondary),
+            (&mut self.ok, &colors.ok),
+            (&mut self.warn, &colors.warn),
+            (&mut self.error, &colors.error),
+            (&mut self.new, &colors.new),
+            (&mut self.selection_bg, &colors.selection_bg),
+            (&mut self.bar_bg, &colors.bar_bg),
+            (&mut self.popup_bg, &colors.popup_bg),
+        ];
+        for (slot, value) in overrides {
+            if let Some(color) = value.as_deref().and_then(|v| Color::from_str(v).ok()) {
+                *slot = color;
+            }
+        }
+    }
+
+    /// Style for the selected row.
+    pub fn selected(&self, style: Style) -> Style {
+        if self.reverse {
+            style.add_modifier(Modifier::REVERSED | Modifier::BOLD)
+        } else {
+            style.bg(self.selection_bg).add_modifier(Modifier::BOLD)
+        }
+    }
+
+    /// Style for status/footer bars.
+    pub fn bar(&self) -> Style {
+        if self.reverse {
+            Style::default().add_modifier(Modifier::REVERSED)
+        } else {
+            Style::default().bg(self.bar_bg)
+        }
+    }
+
+    /// Colour for a bandwidth usage level.
+    pub fn usage(&self, percent: f64, config: &Config) -> Color {
+        if percent > config.settings.critical_usage_percent {
+            self.error
+        } else if percent > config.settings.high_usage_percent {
+            self.warn
+        } else {
+            self.ok
+        }
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::dark()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_by_name() {
+        assert!(Theme::by_name("light").is_some());
+        assert!(Theme::by_name("High_Contrast").unwrap().reverse);
+        assert!(Theme::by_name("solarized").is_none());
+    }
+
+    #[test]
+    fn test_user_colors() {
+        let mut theme = Theme::dark();
+        theme.apply(&ThemeColors {
+            accent: Some("#ff8800".to_string()),
+            error: Some("lightred".to_string()),
+            ok: Some("not-a-color".to_string()),
+            ..Default::default()
+        });
+        assert_eq!(theme.accent, Color::Rgb(0xff, 0x88, 0x00));
+        assert_eq!(theme.error, Color::LightRed);
+        assert_eq!(theme.ok, Color::Green);
+    }
+}